@@ -1,5 +1,567 @@
-/// XDG-compliant discovery cache for partial progress and resume support.
-// TODO: Implement XDG-compliant discovery cache with partial progress and resume support.
-#[allow(dead_code)]
-#[derive(Debug)]
-pub struct DiscoveryCache;
+//! XDG-compliant discovery and zone lookup caches.
+//!
+//! Each zone's most recent discovery is stored as a JSON file under the platform cache
+//! directory, one per [`CacheOptions`] combination, and zone name lookups share a single
+//! JSON file beside it. Unreadable or corrupt entries are treated as a miss, never a
+//! hard error.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::TiaError;
+use crate::resource::{DiscoverConfig, Resource};
+
+/// The discovery options that change which resources a run returns. Runs made with
+/// different options are cached apart, so one never stands in for the other.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheOptions {
+    pub include_disabled: bool,
+    pub include_defaults: bool,
+}
+
+impl From<&DiscoverConfig> for CacheOptions {
+    fn from(config: &DiscoverConfig) -> Self {
+        Self {
+            include_disabled: config.include_disabled,
+            include_defaults: config.include_defaults,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedDiscovery {
+    pub zone: String,
+    /// Entries written before options were recorded used the defaults.
+    #[serde(default)]
+    pub options: CacheOptions,
+    /// Seconds since the Unix epoch at which the entry was written.
+    pub cached_at: u64,
+    pub resources: Vec<Resource>,
+}
+
+impl CachedDiscovery {
+    /// Whether the entry is younger than `ttl` at `now` (seconds since the epoch).
+    ///
+    /// An entry stamped in the future, e.g. after a clock change or when copied from
+    /// another machine, can't be aged reliably and is treated as stale.
+    pub fn is_fresh(&self, ttl: Duration, now: u64) -> bool {
+        is_fresh(&self.zone, self.cached_at, ttl, now)
+    }
+}
+
+fn is_fresh(zone: &str, cached_at: u64, ttl: Duration, now: u64) -> bool {
+    match now.checked_sub(cached_at) {
+        Some(age) => age < ttl.as_secs(),
+        None => {
+            tracing::warn!(
+                zone,
+                cached_at,
+                now,
+                "cache entry is timestamped in the future; treating it as stale"
+            );
+            false
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DiscoveryCache {
+    dir: PathBuf,
+}
+
+impl DiscoveryCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Returns the cache rooted at `$XDG_CACHE_HOME/tia/discovery` (or the platform
+    /// equivalent), or `None` when no cache directory can be determined.
+    pub fn default_location() -> Option<Self> {
+        dirs::cache_dir().map(|dir| Self::new(dir.join("tia").join("discovery")))
+    }
+
+    pub fn store(
+        &self,
+        zone: &str,
+        options: CacheOptions,
+        resources: &[Resource],
+    ) -> Result<(), TiaError> {
+        fs::create_dir_all(&self.dir)?;
+
+        let entry = CachedDiscovery {
+            zone: zone.to_string(),
+            options,
+            cached_at: now_secs(),
+            resources: resources.to_vec(),
+        };
+        let json = serde_json::to_vec_pretty(&entry).map_err(|e| TiaError::Cache(e.to_string()))?;
+        fs::write(self.entry_path(zone, options), json)?;
+
+        Ok(())
+    }
+
+    /// The entry stored for `zone` by a run with the same `options`.
+    pub fn load(&self, zone: &str, options: CacheOptions) -> Option<CachedDiscovery> {
+        let path = self.entry_path(zone, options);
+        let bytes = fs::read(&path).ok()?;
+
+        match serde_json::from_slice::<CachedDiscovery>(&bytes) {
+            Ok(entry) => Some(entry).filter(|entry| entry.options == options),
+            Err(e) => {
+                tracing::warn!(path = %path.display(), error = %e, "ignoring corrupt cache entry");
+                None
+            }
+        }
+    }
+
+    /// Like [`load`](Self::load), but entries older than `ttl` are a miss.
+    pub fn load_fresh(
+        &self,
+        zone: &str,
+        options: CacheOptions,
+        ttl: Duration,
+    ) -> Option<CachedDiscovery> {
+        self.load(zone, options)
+            .filter(|entry| entry.is_fresh(ttl, now_secs()))
+    }
+
+    /// Options other than the defaults are appended after a `+`, which a sanitized zone
+    /// never contains.
+    fn entry_path(&self, zone: &str, options: CacheOptions) -> PathBuf {
+        let mut file_stem: String = zone
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        if options.include_disabled {
+            file_stem.push_str("+disabled");
+        }
+        if options.include_defaults {
+            file_stem.push_str("+defaults");
+        }
+        self.dir.join(format!("{}.json", file_stem))
+    }
+}
+
+/// A zone lookup result, keyed in [`ZoneCache`] by the name or id it was looked up by.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedZone {
+    pub zone_id: String,
+    pub account_id: String,
+    pub name: String,
+    /// Seconds since the Unix epoch at which the entry was written.
+    pub cached_at: u64,
+}
+
+/// Serializes [`ZoneCache::put`] within the process; concurrent zone lookups would
+/// otherwise lose each other's entries.
+static ZONE_CACHE_WRITE: Mutex<()> = Mutex::new(());
+
+/// Zone lookups stored together in one JSON file, so repeated runs against the same
+/// zone skip the API round trip.
+#[derive(Debug, Clone)]
+pub struct ZoneCache {
+    path: PathBuf,
+}
+
+impl ZoneCache {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Returns the cache at `$XDG_CACHE_HOME/tia/zones.json` (or the platform
+    /// equivalent), or `None` when no cache directory can be determined.
+    pub fn default_location() -> Option<Self> {
+        dirs::cache_dir().map(|dir| Self::new(dir.join("tia").join("zones.json")))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The entry for `zone` if it's younger than `ttl`.
+    pub fn get(&self, zone: &str, ttl: Duration) -> Option<CachedZone> {
+        self.read()
+            .remove(zone)
+            .filter(|entry| is_fresh(zone, entry.cached_at, ttl, now_secs()))
+    }
+
+    /// Records a lookup, replacing any previous entry for `zone`.
+    ///
+    /// The file is rewritten through a temporary sibling so an interrupted write never
+    /// leaves a partial cache behind.
+    pub fn put(
+        &self,
+        zone: &str,
+        zone_id: &str,
+        account_id: &str,
+        name: &str,
+    ) -> Result<(), TiaError> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let _guard = ZONE_CACHE_WRITE
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut entries = self.read();
+        entries.insert(
+            zone.to_string(),
+            CachedZone {
+                zone_id: zone_id.to_string(),
+                account_id: account_id.to_string(),
+                name: name.to_string(),
+                cached_at: now_secs(),
+            },
+        );
+        let json =
+            serde_json::to_vec_pretty(&entries).map_err(|e| TiaError::Cache(e.to_string()))?;
+
+        let tmp = self.path.with_extension("json.tmp");
+        fs::write(&tmp, json)?;
+        fs::rename(&tmp, &self.path)?;
+
+        Ok(())
+    }
+
+    fn read(&self) -> BTreeMap<String, CachedZone> {
+        let Ok(bytes) = fs::read(&self.path) else {
+            return BTreeMap::new();
+        };
+
+        serde_json::from_slice(&bytes).unwrap_or_else(|e| {
+            tracing::warn!(path = %self.path.display(), error = %e, "ignoring corrupt zone cache");
+            BTreeMap::new()
+        })
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::delta::ResourceDelta;
+
+    fn temp_cache(name: &str) -> DiscoveryCache {
+        let dir = std::env::temp_dir().join(format!("tia-cache-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        DiscoveryCache::new(dir)
+    }
+
+    fn record(id: &str, metadata: serde_json::Value) -> Resource {
+        Resource {
+            resource_type: "cloudflare_dns_record".to_string(),
+            resource_id: id.to_string(),
+            name: format!("{}.example.com", id),
+            zone_id: "zone123".to_string(),
+            zone_name: None,
+            account_id: None,
+            metadata,
+        }
+    }
+
+    #[test]
+    fn test_store_then_load_roundtrip() {
+        let cache = temp_cache("roundtrip");
+        let resources = vec![record("r1", serde_json::json!({"type": "A"}))];
+
+        cache
+            .store("example.com", CacheOptions::default(), &resources)
+            .unwrap();
+        let entry = cache.load("example.com", CacheOptions::default()).unwrap();
+
+        assert_eq!(entry.zone, "example.com");
+        assert_eq!(entry.resources, resources);
+        assert!(entry.cached_at > 0);
+    }
+
+    #[test]
+    fn test_load_missing_entry_is_miss() {
+        let cache = temp_cache("missing");
+        assert!(cache.load("example.com", CacheOptions::default()).is_none());
+    }
+
+    #[test]
+    fn test_load_corrupt_entry_is_miss() {
+        let cache = temp_cache("corrupt");
+        fs::create_dir_all(&cache.dir).unwrap();
+        fs::write(
+            cache.entry_path("example.com", CacheOptions::default()),
+            b"{\"zone\": \"exam",
+        )
+        .unwrap();
+
+        assert!(cache.load("example.com", CacheOptions::default()).is_none());
+    }
+
+    #[test]
+    fn test_is_fresh_within_ttl() {
+        let entry = CachedDiscovery {
+            zone: "example.com".to_string(),
+            options: CacheOptions::default(),
+            cached_at: 1_000,
+            resources: vec![],
+        };
+        let ttl = Duration::from_secs(60);
+
+        assert!(entry.is_fresh(ttl, 1_000));
+        assert!(entry.is_fresh(ttl, 1_059));
+        assert!(!entry.is_fresh(ttl, 1_060));
+    }
+
+    #[test]
+    fn test_future_timestamped_entry_is_miss() {
+        let cache = temp_cache("future");
+        fs::create_dir_all(&cache.dir).unwrap();
+        let entry = CachedDiscovery {
+            zone: "example.com".to_string(),
+            options: CacheOptions::default(),
+            cached_at: now_secs() + 3_600,
+            resources: vec![record("r1", serde_json::json!({}))],
+        };
+        fs::write(
+            cache.entry_path("example.com", CacheOptions::default()),
+            serde_json::to_vec(&entry).unwrap(),
+        )
+        .unwrap();
+
+        assert!(cache.load("example.com", CacheOptions::default()).is_some());
+        assert!(
+            cache
+                .load_fresh(
+                    "example.com",
+                    CacheOptions::default(),
+                    Duration::from_secs(u64::MAX)
+                )
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_load_fresh_returns_recent_entry() {
+        let cache = temp_cache("fresh");
+        cache
+            .store("example.com", CacheOptions::default(), &[])
+            .unwrap();
+
+        assert!(
+            cache
+                .load_fresh(
+                    "example.com",
+                    CacheOptions::default(),
+                    Duration::from_secs(300)
+                )
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn test_load_fresh_miss_then_warm_hit() {
+        let cache = temp_cache("warm");
+        let ttl = Duration::from_secs(300);
+        assert!(
+            cache
+                .load_fresh("example.com", CacheOptions::default(), ttl)
+                .is_none()
+        );
+
+        let resources = vec![record("r1", serde_json::json!({"type": "A"}))];
+        cache
+            .store("example.com", CacheOptions::default(), &resources)
+            .unwrap();
+
+        let entry = cache
+            .load_fresh("example.com", CacheOptions::default(), ttl)
+            .unwrap();
+        assert_eq!(entry.resources, resources);
+        assert!(
+            cache
+                .load_fresh("example.org", CacheOptions::default(), ttl)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_load_fresh_expired_entry_is_miss() {
+        let cache = temp_cache("expired");
+        fs::create_dir_all(&cache.dir).unwrap();
+        let entry = CachedDiscovery {
+            zone: "example.com".to_string(),
+            options: CacheOptions::default(),
+            cached_at: now_secs() - 600,
+            resources: vec![record("r1", serde_json::json!({}))],
+        };
+        fs::write(
+            cache.entry_path("example.com", CacheOptions::default()),
+            serde_json::to_vec(&entry).unwrap(),
+        )
+        .unwrap();
+
+        assert!(
+            cache
+                .load_fresh(
+                    "example.com",
+                    CacheOptions::default(),
+                    Duration::from_secs(300)
+                )
+                .is_none()
+        );
+        assert!(
+            cache
+                .load_fresh(
+                    "example.com",
+                    CacheOptions::default(),
+                    Duration::from_secs(900)
+                )
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn test_entry_path_sanitizes_zone() {
+        let cache = DiscoveryCache::new("/cache");
+        assert_eq!(
+            cache.entry_path("../etc/passwd", CacheOptions::default()),
+            PathBuf::from("/cache/.._etc_passwd.json")
+        );
+    }
+
+    #[test]
+    fn test_entries_are_keyed_by_options() {
+        let cache = temp_cache("options");
+        let ttl = Duration::from_secs(300);
+        let with_disabled = CacheOptions {
+            include_disabled: true,
+            ..Default::default()
+        };
+        let with_defaults = CacheOptions {
+            include_defaults: true,
+            ..Default::default()
+        };
+        let default_run = vec![record("enabled", serde_json::json!({}))];
+        let disabled_run = vec![
+            record("enabled", serde_json::json!({})),
+            record("disabled", serde_json::json!({})),
+        ];
+
+        cache
+            .store("example.com", CacheOptions::default(), &default_run)
+            .unwrap();
+        cache
+            .store("example.com", with_disabled, &disabled_run)
+            .unwrap();
+
+        let hit = |options| {
+            cache
+                .load_fresh("example.com", options, ttl)
+                .map(|entry| entry.resources)
+        };
+        assert_eq!(hit(CacheOptions::default()), Some(default_run));
+        assert_eq!(hit(with_disabled), Some(disabled_run));
+        assert_eq!(hit(with_defaults), None);
+    }
+
+    fn temp_zone_cache(name: &str) -> ZoneCache {
+        let dir = std::env::temp_dir().join(format!("tia-zones-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        ZoneCache::new(dir.join("zones.json"))
+    }
+
+    #[test]
+    fn test_zone_cache_put_then_get() {
+        let cache = temp_zone_cache("roundtrip");
+        cache
+            .put("example.com", "zone123", "acct456", "example.com")
+            .unwrap();
+        cache
+            .put("example.org", "zone789", "acct456", "example.org")
+            .unwrap();
+
+        let entry = cache.get("example.com", Duration::from_secs(60)).unwrap();
+
+        assert_eq!(entry.zone_id, "zone123");
+        assert_eq!(entry.account_id, "acct456");
+        assert_eq!(entry.name, "example.com");
+        assert_eq!(
+            cache
+                .get("example.org", Duration::from_secs(60))
+                .unwrap()
+                .zone_id,
+            "zone789"
+        );
+        assert!(cache.get("example.net", Duration::from_secs(60)).is_none());
+    }
+
+    #[test]
+    fn test_zone_cache_expired_entry_is_miss() {
+        let cache = temp_zone_cache("expired");
+        cache
+            .put("example.com", "zone123", "acct456", "example.com")
+            .unwrap();
+
+        assert!(cache.get("example.com", Duration::ZERO).is_none());
+    }
+
+    #[test]
+    fn test_zone_cache_corrupt_or_partial_file_is_miss() {
+        let cache = temp_zone_cache("corrupt");
+        fs::create_dir_all(cache.path().parent().unwrap()).unwrap();
+        fs::write(cache.path(), b"{\"example.com\": {\"zone_id\": \"zo").unwrap();
+
+        assert!(cache.get("example.com", Duration::from_secs(60)).is_none());
+
+        // A corrupt file is replaced by the next successful write.
+        cache
+            .put("example.com", "zone123", "acct456", "example.com")
+            .unwrap();
+        assert!(cache.get("example.com", Duration::from_secs(60)).is_some());
+    }
+
+    #[test]
+    fn test_delta_against_seeded_cache() {
+        let cache = temp_cache("seeded");
+        cache
+            .store(
+                "example.com",
+                CacheOptions::default(),
+                &[
+                    record("kept", serde_json::json!({"type": "A"})),
+                    record("gone", serde_json::json!({"type": "A"})),
+                    record("edited", serde_json::json!({"type": "A"})),
+                ],
+            )
+            .unwrap();
+
+        let current = vec![
+            record("kept", serde_json::json!({"type": "A"})),
+            record("edited", serde_json::json!({"type": "CNAME"})),
+            record("fresh", serde_json::json!({"type": "A"})),
+        ];
+
+        let previous = cache.load("example.com", CacheOptions::default()).unwrap();
+        let delta = ResourceDelta::between(&previous.resources, &current);
+
+        assert_eq!(delta.added.len(), 1);
+        assert_eq!(delta.added[0].resource_id, "fresh");
+        assert_eq!(delta.removed.len(), 1);
+        assert_eq!(delta.removed[0].resource_id, "gone");
+        assert_eq!(delta.changed.len(), 1);
+        assert_eq!(delta.changed[0].current.resource_id, "edited");
+    }
+}
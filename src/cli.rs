@@ -1,3 +1,7 @@
 pub mod args;
 
-pub use args::{Cli, CloudflareCommand, ProviderCommand};
+pub use args::{
+    AwsCommand, AwsDiscoverArgs, Cli, CloudflareCommand, DiffArgs, DiffFormat, DiffTarget,
+    DiscoverArgs, EncodingArgs, GenerateArgs, ImportStyle, LogFormat, OutputFormat,
+    ProviderCommand, VerifyArgs,
+};
@@ -1,97 +1,1621 @@
-use clap::{Parser, Subcommand};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use clap::{Parser, Subcommand, ValueEnum};
+use regex::Regex;
+use tia::TiaError;
+use tia::config::{DEFAULT_CONFIG_FILE, FileConfig, ZONES_ENV, load_zones_file, parse_zone_list};
+use tia::ignore::DEFAULT_IGNORE_FILE;
+use tia::output::SortKey;
+use tia::output::encoding::{EncodedWriter, LineEnding};
+use tracing::level_filters::LevelFilter;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about)]
 pub struct Cli {
     #[command(subcommand)]
     pub command: ProviderCommand,
+
+    /// Only log errors. Overrides `RUST_LOG`.
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// Log more: `-v` for debug, `-vv` for trace. Overrides `RUST_LOG`.
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    pub verbose: u8,
+
+    /// How log lines are written to stderr.
+    #[arg(
+        long,
+        value_enum,
+        global = true,
+        env = "TIA_LOG_FORMAT",
+        default_value_t = LogFormat::Text
+    )]
+    pub log_format: LogFormat,
+
+    /// API requests in flight at once, across every zone and resource type
+    /// [default: 4].
+    #[arg(
+        long,
+        global = true,
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    pub max_concurrency: Option<usize>,
+
+    /// Cloudflare API base URL, e.g. an internal gateway. Overrides
+    /// `CLOUDFLARE_API_BASE_URL`.
+    #[arg(long, global = true, value_name = "URL")]
+    pub api_base: Option<String>,
+}
+
+impl Cli {
+    /// The log level the flags ask for, or `None` to defer to `RUST_LOG`.
+    pub fn log_level(&self) -> Option<LevelFilter> {
+        match (self.quiet, self.verbose) {
+            (true, _) => Some(LevelFilter::ERROR),
+            (false, 0) => None,
+            (false, 1) => Some(LevelFilter::DEBUG),
+            (false, _) => Some(LevelFilter::TRACE),
+        }
+    }
+}
+
+#[derive(Subcommand, Debug)]
+#[allow(clippy::large_enum_variant)] // NOTE: Parsed once per run, so the size doesn't matter
+pub enum ProviderCommand {
+    Cloudflare {
+        #[command(subcommand)]
+        command: CloudflareCommand,
+    },
+    Aws {
+        #[command(subcommand)]
+        command: AwsCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum CloudflareCommand {
+    Discover(DiscoverArgs),
+    Generate(GenerateArgs),
+    Diff(DiffArgs),
+    /// Check which resource types the token may read before running discovery.
+    Verify(VerifyArgs),
+}
+
+#[derive(Subcommand, Debug)]
+pub enum AwsCommand {
+    /// Discover Route53 record sets. Credentials are read from `AWS_ACCESS_KEY_ID`,
+    /// `AWS_SECRET_ACCESS_KEY` and, for temporary credentials, `AWS_SESSION_TOKEN`.
+    Discover(AwsDiscoverArgs),
+}
+
+#[derive(clap::Args, Debug)]
+pub struct AwsDiscoverArgs {
+    /// Hosted zone to discover, by name or id; repeatable, or comma-separated.
+    #[arg(long = "zone", value_name = "ZONE", value_delimiter = ',')]
+    pub zones: Vec<String>,
+
+    /// Discover every hosted zone the credentials can list; takes precedence over `--zone`.
+    #[arg(long)]
+    pub all_zones: bool,
+
+    #[command(flatten)]
+    pub timeouts: TimeoutArgs,
+
+    /// How to print the discovered resources [default: text].
+    #[arg(long, value_enum)]
+    pub format: Option<OutputFormat>,
+
+    #[command(flatten)]
+    pub json_style: JsonStyleArgs,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct DiscoverArgs {
+    #[command(flatten)]
+    pub config: ConfigArgs,
+
+    #[arg(long, env = "CLOUDFLARE_API_TOKEN", hide_env_values = true)]
+    pub token: Option<String>,
+
+    #[command(flatten)]
+    pub zone: ZoneArgs,
+
+    #[command(flatten)]
+    pub timeouts: TimeoutArgs,
+
+    #[command(flatten)]
+    pub retry: RetryArgs,
+
+    #[command(flatten)]
+    pub zone_cache: ZoneCacheArgs,
+
+    /// Don't warn for each skipped resource type; only report the total at the end.
+    #[arg(long)]
+    pub quiet_skip: bool,
+
+    /// Fail instead of skipping a resource type the token can't read.
+    #[arg(long, conflicts_with = "quiet_skip")]
+    pub strict: bool,
+
+    /// Only discover this resource type, e.g. `cloudflare_dns_record`; repeatable.
+    #[arg(long = "type", value_name = "RESOURCE_TYPE")]
+    pub resource_types: Vec<String>,
+
+    /// Skip this resource type, even if `--type` names it; repeatable.
+    #[arg(long = "exclude-type", value_name = "RESOURCE_TYPE")]
+    pub exclude_types: Vec<String>,
+
+    /// Also include rules the API reports as disabled, e.g. legacy rate limits.
+    #[arg(long)]
+    pub include_disabled: bool,
+
+    /// Also include entries skipped as defaults or unmanageable: zone settings left at
+    /// their default and Cloudflare-managed rulesets.
+    #[arg(long)]
+    pub include_defaults: bool,
+
+    /// Stop at the first zone or resource type that fails; by default the failure is
+    /// reported and the other zones are still discovered.
+    #[arg(long)]
+    pub fail_fast: bool,
+
+    /// How to print the discovered resources [default: text].
+    #[arg(long, value_enum, conflicts_with = "watch")]
+    pub format: Option<OutputFormat>,
+
+    /// Print the resources as JSON projected to these fields, e.g.
+    /// `resource_type,resource_id,metadata.type`; needs `--format json` or
+    /// `--format json-envelope`.
+    #[arg(long, value_delimiter = ',', requires = "format")]
+    pub fields: Option<Vec<String>>,
+
+    #[command(flatten)]
+    pub json_style: JsonStyleArgs,
+
+    /// Order the resources by this field before printing them [default: type, then
+    /// name].
+    #[arg(long, value_enum)]
+    pub sort: Option<SortField>,
+
+    /// Re-run discovery every `--interval` seconds and print what changed each cycle.
+    #[arg(long, conflicts_with = "since")]
+    pub watch: bool,
+
+    #[arg(long, default_value_t = 300, requires = "watch", value_parser = clap::value_parser!(u64).range(1..))]
+    pub interval: u64,
+
+    /// Items requested per page from list endpoints, clamped to 5-1000 (lower where
+    /// an endpoint caps it). Larger pages mean fewer requests but more memory.
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub page_size: Option<u32>,
+
+    /// Zone lookups, and resource types fetched at once across every zone unless
+    /// --concurrency-across-zones or --concurrency-per-zone is set [default: 4].
+    #[arg(long, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    pub concurrency: Option<usize>,
+
+    /// Zones whose resource types are fetched at the same time. With this or
+    /// --concurrency-per-zone, up to their product of resource types run at once and
+    /// --concurrency stands in for whichever is unset.
+    #[arg(long, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    pub concurrency_across_zones: Option<usize>,
+
+    /// Resource types fetched at once within each zone; see --concurrency-across-zones.
+    #[arg(long, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    pub concurrency_per_zone: Option<usize>,
+
+    /// Print the API requests discovery would send, one per line, without sending any.
+    #[arg(long, conflicts_with = "watch")]
+    pub dry_run: bool,
+
+    /// Reuse the zone's last complete discovery if it's younger than this many seconds,
+    /// without calling the API. Only applies to a single zone without type filters.
+    #[arg(long, value_name = "SECONDS", conflicts_with_all = ["no_cache", "watch", "dry_run"])]
+    pub cache_ttl: Option<u64>,
+
+    /// Replace resource, zone and other ids in the printed resources with a short
+    /// stable hash, e.g. for pasting into tickets.
+    #[arg(long, conflicts_with = "watch")]
+    pub redact_ids: bool,
+
+    /// Print a line to stderr for each page fetched, with the running item count.
+    #[arg(long)]
+    pub progress: bool,
+
+    /// File of glob patterns for resources to leave out; ignored if it doesn't exist.
+    #[arg(long, default_value = DEFAULT_IGNORE_FILE)]
+    pub ignore: PathBuf,
+
+    /// Only keep resources whose name matches this regex, e.g. `^api\.`.
+    #[arg(long, value_name = "REGEX")]
+    pub name_filter: Option<Regex>,
+
+    #[command(flatten)]
+    pub since: SinceArgs,
+
+    #[command(flatten)]
+    pub expect: ExpectArgs,
+
+    /// Resolve CNAME targets and record their addresses in metadata.
+    #[cfg(feature = "resolve-targets")]
+    #[arg(long)]
+    pub resolve_targets: bool,
+}
+
+/// Comparison against the manifest of an earlier `generate --manifest` run.
+#[derive(clap::Args, Debug, Default)]
+pub struct SinceArgs {
+    /// Report the importable resources added, removed or changed since this manifest was
+    /// written.
+    #[arg(long, value_name = "MANIFEST")]
+    pub since: Option<PathBuf>,
+
+    /// Also write the changes found by `--since` to this file as JSON, replacing it.
+    #[arg(long, value_name = "PATH", requires = "since")]
+    pub since_json: Option<PathBuf>,
+}
+
+/// Layout of JSON printed to stdout; whichever of `--compact` and `--pretty` comes last
+/// wins.
+#[derive(clap::Args, Debug, Default)]
+pub struct JsonStyleArgs {
+    /// Print JSON on a single line, e.g. for piping into other tools.
+    #[arg(long, overrides_with = "pretty")]
+    pub compact: bool,
+
+    /// Print indented JSON for reading [default].
+    #[arg(long, overrides_with = "compact")]
+    pub pretty: bool,
+}
+
+impl JsonStyleArgs {
+    pub fn pretty(&self) -> bool {
+        !self.compact
+    }
+}
+
+/// Bounds on the discovered resource count, checked after filtering so CI can catch
+/// permission regressions that silently shrink the result.
+#[derive(clap::Args, Debug, Default)]
+pub struct ExpectArgs {
+    /// Fail unless exactly this many resources are discovered.
+    #[arg(long, conflicts_with_all = ["expect_min", "expect_max", "watch"])]
+    pub expect_count: Option<usize>,
+
+    /// Fail if fewer than this many resources are discovered.
+    #[arg(long, conflicts_with = "watch")]
+    pub expect_min: Option<usize>,
+
+    /// Fail if more than this many resources are discovered.
+    #[arg(long, conflicts_with = "watch")]
+    pub expect_max: Option<usize>,
+}
+
+impl ExpectArgs {
+    pub fn check(&self, count: usize) -> Result<(), String> {
+        if let Some(expected) = self.expect_count
+            && count != expected
+        {
+            return Err(format!(
+                "expected exactly {} resources, discovered {}",
+                expected, count
+            ));
+        }
+        if let Some(min) = self.expect_min
+            && count < min
+        {
+            return Err(format!(
+                "expected at least {} resources, discovered {}",
+                min, count
+            ));
+        }
+        if let Some(max) = self.expect_max
+            && count > max
+        {
+            return Err(format!(
+                "expected at most {} resources, discovered {}",
+                max, count
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(clap::Args, Debug, Default)]
+pub struct RetryArgs {
+    /// HTTP statuses to retry, replacing the default `429,500,502,503,504`; pass an
+    /// empty value to disable retries.
+    #[arg(long, value_delimiter = ',', num_args = 0.., value_parser = clap::value_parser!(u16).range(100..=599))]
+    pub retry_on: Option<Vec<u16>>,
+}
+
+#[derive(clap::Args, Debug, Default)]
+pub struct ConfigArgs {
+    /// Config file with default `token`, `zones`, `types` and `format` values; flags
+    /// and environment variables override it. Defaults to `tia.toml` if present.
+    #[arg(long = "config", value_name = "PATH")]
+    pub path: Option<PathBuf>,
+}
+
+impl ConfigArgs {
+    /// An explicitly passed file must exist; the default one is optional.
+    pub fn load(&self) -> Result<FileConfig, TiaError> {
+        match &self.path {
+            Some(path) => FileConfig::load(path, true),
+            None => FileConfig::load(Path::new(DEFAULT_CONFIG_FILE), false),
+        }
+    }
+}
+
+/// Fills `value` from the config file only when neither a flag nor an environment
+/// variable set it.
+fn fill<T>(value: &mut Option<T>, file: Option<T>) {
+    if value.is_none() {
+        *value = file;
+    }
+}
+
+fn fill_list(values: &mut Vec<String>, file: Option<Vec<String>>) {
+    if values.is_empty() {
+        *values = file.unwrap_or_default();
+    }
+}
+
+impl ZoneArgs {
+    /// Adds the zones listed in `zones_file`. Run before applying the config file, so
+    /// listed zones count as set on the command line.
+    pub fn read_zones_file(&mut self) -> Result<(), TiaError> {
+        if let Some(path) = &self.zones_file {
+            self.zones.extend(load_zones_file(path)?);
+        }
+        Ok(())
+    }
+
+    /// Falls back to `CLOUDFLARE_ZONES`, then to the config file, when no zone was
+    /// given on the command line or in `CLOUDFLARE_ZONE_ID`.
+    fn fill(&mut self, file: Option<Vec<String>>) {
+        if !self.all_zones {
+            let env = std::env::var(ZONES_ENV).ok();
+            fill_list(&mut self.zones, env.as_deref().map(parse_zone_list));
+            fill_list(&mut self.zones, file);
+        }
+    }
+}
+
+impl DiscoverArgs {
+    /// Applies `file` underneath the values from flags and the environment.
+    pub fn apply_config(&mut self, file: FileConfig) -> Result<(), TiaError> {
+        fill(&mut self.token, file.token);
+        self.zone.fill(file.zones);
+        fill_list(&mut self.resource_types, file.types);
+        if let Some(format) = file.format {
+            let format = OutputFormat::from_str(&format, true)
+                .map_err(|_| TiaError::Config(format!("unknown format '{}'", format)))?;
+            fill(&mut self.format, Some(format));
+        }
+        Ok(())
+    }
+}
+
+impl GenerateArgs {
+    /// Applies `file` underneath the values from flags and the environment; `format`
+    /// doesn't apply to generated HCL and is ignored.
+    pub fn apply_config(&mut self, file: FileConfig) {
+        fill(&mut self.token, file.token);
+        self.zone.fill(file.zones);
+        fill_list(&mut self.resource_types, file.types);
+    }
+}
+
+impl VerifyArgs {
+    pub fn apply_config(&mut self, file: FileConfig) {
+        fill(&mut self.token, file.token);
+        self.zone.fill(file.zones);
+        fill_list(&mut self.resource_types, file.types);
+    }
+}
+
+impl DiffArgs {
+    /// Only `token` and `zones` apply to a diff.
+    pub fn apply_config(&mut self, file: FileConfig) {
+        fill(&mut self.token, file.token);
+        self.zone.fill(file.zones);
+    }
+}
+
+#[derive(clap::Args, Debug, Default)]
+pub struct ZoneArgs {
+    /// Zone to discover, by name or id; repeatable, or comma-separated.
+    #[arg(
+        long = "zone",
+        value_name = "ZONE",
+        env = "CLOUDFLARE_ZONE_ID",
+        value_delimiter = ','
+    )]
+    pub zones: Vec<String>,
+
+    /// Discover every zone the token can access; takes precedence over `--zone`.
+    #[arg(long)]
+    pub all_zones: bool,
+
+    /// File listing zones to discover, one name or id per line; blank lines and `#`
+    /// comments are ignored. Adds to `--zone`.
+    #[arg(long, value_name = "PATH", conflicts_with = "all_zones")]
+    pub zones_file: Option<PathBuf>,
+
+    /// Account to discover account-level resources (lists, Workers scripts, ...) from,
+    /// in place of each zone's own account; without a zone, only those are discovered.
+    #[arg(long, value_name = "ACCOUNT_ID")]
+    pub account_id: Option<String>,
+
+    /// Discover account-level resources in every account the token can access, not
+    /// just the zones' accounts.
+    #[arg(long, conflicts_with = "account_id")]
+    pub all_accounts: bool,
+}
+
+#[derive(clap::Args, Debug, Default)]
+pub struct ZoneCacheArgs {
+    /// Always look the zone up through the API instead of the on-disk zone cache.
+    #[arg(long)]
+    pub no_cache: bool,
+
+    /// Seconds a cached zone lookup stays valid.
+    #[arg(long, default_value_t = 86_400, conflicts_with = "no_cache")]
+    pub zone_cache_ttl: u64,
+}
+
+impl ZoneCacheArgs {
+    /// `None` when the cache is disabled.
+    pub fn ttl(&self) -> Option<Duration> {
+        (!self.no_cache).then(|| Duration::from_secs(self.zone_cache_ttl))
+    }
+}
+
+#[derive(clap::Args, Debug, Default)]
+pub struct TimeoutArgs {
+    /// Convenience that sets both the connect and read timeouts, in seconds.
+    #[arg(long, value_parser = clap::builder::RangedU64ValueParser::<u64>::new().range(1..))]
+    pub timeout: Option<u64>,
+
+    /// Seconds to wait for a connection to be established.
+    #[arg(long, value_parser = clap::builder::RangedU64ValueParser::<u64>::new().range(1..))]
+    pub connect_timeout: Option<u64>,
+
+    /// Seconds to wait for a complete response [default: 30].
+    #[arg(long, value_parser = clap::builder::RangedU64ValueParser::<u64>::new().range(1..))]
+    pub read_timeout: Option<u64>,
+}
+
+impl TimeoutArgs {
+    /// The specific flags win over `--timeout` for their half.
+    pub fn connect(&self) -> Option<Duration> {
+        self.connect_timeout
+            .or(self.timeout)
+            .map(Duration::from_secs)
+    }
+
+    pub fn read(&self) -> Option<Duration> {
+        self.read_timeout.or(self.timeout).map(Duration::from_secs)
+    }
 }
 
-#[derive(Subcommand, Debug)]
-pub enum ProviderCommand {
-    Cloudflare {
-        #[command(subcommand)]
-        command: CloudflareCommand,
-    },
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use serial_test::serial;
+
+    #[test]
+    fn test_log_level_from_flags() {
+        let level = |args: &[&str]| Cli::parse_from(args).log_level();
+
+        assert_eq!(level(&["tia", "cloudflare", "discover"]), None);
+        assert_eq!(
+            level(&["tia", "-q", "cloudflare", "discover"]),
+            Some(LevelFilter::ERROR)
+        );
+        assert_eq!(
+            level(&["tia", "cloudflare", "discover", "-v"]),
+            Some(LevelFilter::DEBUG)
+        );
+        assert_eq!(
+            level(&["tia", "cloudflare", "discover", "-vv"]),
+            Some(LevelFilter::TRACE)
+        );
+    }
+
+    #[test]
+    fn test_log_format_flag_is_global() {
+        let cli = Cli::parse_from(["tia", "cloudflare", "discover", "--log-format=json"]);
+        assert_eq!(cli.log_format, LogFormat::Json);
+
+        let cli = Cli::parse_from(["tia", "--log-format", "text", "cloudflare", "discover"]);
+        assert_eq!(cli.log_format, LogFormat::Text);
+    }
+
+    #[test]
+    fn test_api_base_flag_is_global() {
+        let cli = Cli::parse_from([
+            "tia",
+            "cloudflare",
+            "verify",
+            "--api-base",
+            "https://gateway.internal/v4",
+        ]);
+        assert_eq!(cli.api_base.as_deref(), Some("https://gateway.internal/v4"));
+
+        let cli = Cli::parse_from(["tia", "cloudflare", "discover"]);
+        assert_eq!(cli.api_base, None);
+    }
+
+    #[test]
+    fn test_quiet_conflicts_with_verbose() {
+        let err = Cli::try_parse_from(["tia", "-q", "-v", "cloudflare", "discover"]).unwrap_err();
+        assert_eq!(err.kind(), clap::error::ErrorKind::ArgumentConflict);
+    }
+
+    #[test]
+    fn test_discover_args_token_from_flag() {
+        let cli = Cli::parse_from(["tia", "cloudflare", "discover", "--token=test_token"]);
+
+        if let ProviderCommand::Cloudflare {
+            command: CloudflareCommand::Discover(args),
+        } = cli.command
+        {
+            assert_eq!(args.token, Some("test_token".to_string()));
+        } else {
+            panic!(
+                "Expected Cloudflare Discover command, got {:?}",
+                cli.command
+            );
+        }
+    }
+
+    #[test]
+    fn test_discover_args_zone_from_flag() {
+        let cli = Cli::parse_from(["tia", "cloudflare", "discover", "--zone=example.com"]);
+
+        if let ProviderCommand::Cloudflare {
+            command: CloudflareCommand::Discover(args),
+        } = cli.command
+        {
+            assert_eq!(args.zone.zones, vec!["example.com".to_string()]);
+        } else {
+            panic!(
+                "Expected Cloudflare Discover command, got {:?}",
+                cli.command
+            );
+        }
+    }
+
+    #[test]
+    fn test_discover_args_repeated_zones() {
+        let cli = Cli::parse_from([
+            "tia",
+            "cloudflare",
+            "discover",
+            "--zone",
+            "a.com",
+            "--zone=b.com,c.com",
+        ]);
+
+        if let ProviderCommand::Cloudflare {
+            command: CloudflareCommand::Discover(args),
+        } = cli.command
+        {
+            assert_eq!(args.zone.zones, vec!["a.com", "b.com", "c.com"]);
+            assert!(!args.zone.all_zones);
+        } else {
+            panic!(
+                "Expected Cloudflare Discover command, got {:?}",
+                cli.command
+            );
+        }
+    }
+
+    #[test]
+    fn test_aws_discover_args() {
+        let cli = Cli::parse_from([
+            "tia",
+            "aws",
+            "discover",
+            "--zone=example.com,Z123",
+            "--format=json",
+        ]);
+
+        if let ProviderCommand::Aws {
+            command: AwsCommand::Discover(args),
+        } = cli.command
+        {
+            assert_eq!(args.zones, vec!["example.com", "Z123"]);
+            assert!(!args.all_zones);
+            assert_eq!(args.format, Some(OutputFormat::Json));
+        } else {
+            panic!("Expected Aws Discover command, got {:?}", cli.command);
+        }
+    }
+
+    #[test]
+    fn test_dry_run_conflicts_with_watch() {
+        let cli = Cli::parse_from(["tia", "cloudflare", "discover", "--dry-run"]);
+        if let ProviderCommand::Cloudflare {
+            command: CloudflareCommand::Discover(args),
+        } = cli.command
+        {
+            assert!(args.dry_run);
+        } else {
+            panic!(
+                "Expected Cloudflare Discover command, got {:?}",
+                cli.command
+            );
+        }
+
+        let result = Cli::try_parse_from(["tia", "cloudflare", "discover", "--dry-run", "--watch"]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_verify_args() {
+        let cli = Cli::parse_from([
+            "tia",
+            "cloudflare",
+            "verify",
+            "--zone=example.com",
+            "--type",
+            "cloudflare_dns_record",
+        ]);
+
+        if let ProviderCommand::Cloudflare {
+            command: CloudflareCommand::Verify(args),
+        } = cli.command
+        {
+            assert_eq!(args.zone.zones, vec!["example.com"]);
+            assert_eq!(args.resource_types, vec!["cloudflare_dns_record"]);
+        } else {
+            panic!("Expected Cloudflare Verify command, got {:?}", cli.command);
+        }
+    }
+
+    #[test]
+    fn test_page_size_rejects_zero() {
+        let cli = Cli::parse_from(["tia", "cloudflare", "discover", "--page-size", "500"]);
+        if let ProviderCommand::Cloudflare {
+            command: CloudflareCommand::Discover(args),
+        } = cli.command
+        {
+            assert_eq!(args.page_size, Some(500));
+        } else {
+            panic!(
+                "Expected Cloudflare Discover command, got {:?}",
+                cli.command
+            );
+        }
+
+        let result = Cli::try_parse_from(["tia", "cloudflare", "discover", "--page-size", "0"]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_name_filter_compiles_regex() {
+        let cli = Cli::parse_from(["tia", "cloudflare", "discover", "--name-filter", r"^api\."]);
+        if let ProviderCommand::Cloudflare {
+            command: CloudflareCommand::Discover(args),
+        } = cli.command
+        {
+            let pattern = args.name_filter.unwrap();
+            assert!(pattern.is_match("api.example.com"));
+            assert!(!pattern.is_match("www.api.example.com"));
+        } else {
+            panic!(
+                "Expected Cloudflare Discover command, got {:?}",
+                cli.command
+            );
+        }
+    }
+
+    #[test]
+    fn test_name_filter_rejects_invalid_regex() {
+        let err = Cli::try_parse_from(["tia", "cloudflare", "discover", "--name-filter", "api("])
+            .unwrap_err();
+
+        assert_eq!(err.kind(), clap::error::ErrorKind::ValueValidation);
+        assert!(err.to_string().contains("--name-filter"), "{}", err);
+    }
+
+    #[test]
+    fn test_zones_file_adds_to_zone_flags() {
+        let path = std::env::temp_dir().join(format!("tia-zones-{}.txt", std::process::id()));
+        std::fs::write(&path, "# batch\nb.com\n\nc.com\n").unwrap();
+        let cli = Cli::parse_from([
+            "tia",
+            "cloudflare",
+            "discover",
+            "--zone=a.com",
+            "--zones-file",
+            path.to_str().unwrap(),
+        ]);
+
+        if let ProviderCommand::Cloudflare {
+            command: CloudflareCommand::Discover(mut args),
+        } = cli.command
+        {
+            args.zone.read_zones_file().unwrap();
+            std::fs::remove_file(&path).unwrap();
+            assert_eq!(args.zone.zones, vec!["a.com", "b.com", "c.com"]);
+        } else {
+            panic!(
+                "Expected Cloudflare Discover command, got {:?}",
+                cli.command
+            );
+        }
+    }
+
+    #[test]
+    fn test_zones_file_conflicts_with_all_zones() {
+        let err = Cli::try_parse_from([
+            "tia",
+            "cloudflare",
+            "discover",
+            "--all-zones",
+            "--zones-file=zones.txt",
+        ])
+        .unwrap_err();
+        assert_eq!(err.kind(), clap::error::ErrorKind::ArgumentConflict);
+    }
+
+    #[test]
+    fn test_all_accounts_conflicts_with_account_id() {
+        let cli = Cli::parse_from(["tia", "cloudflare", "discover", "--all-accounts"]);
+        if let ProviderCommand::Cloudflare {
+            command: CloudflareCommand::Discover(args),
+        } = cli.command
+        {
+            assert!(args.zone.all_accounts);
+        } else {
+            panic!(
+                "Expected Cloudflare Discover command, got {:?}",
+                cli.command
+            );
+        }
+
+        let err = Cli::try_parse_from([
+            "tia",
+            "cloudflare",
+            "discover",
+            "--all-accounts",
+            "--account-id=01a7362d577a6c3019a474fd6f485823",
+        ])
+        .unwrap_err();
+        assert_eq!(err.kind(), clap::error::ErrorKind::ArgumentConflict);
+    }
+
+    #[test]
+    fn test_generate_args_all_zones() {
+        let cli = Cli::parse_from(["tia", "cloudflare", "generate", "--all-zones"]);
+
+        if let ProviderCommand::Cloudflare {
+            command: CloudflareCommand::Generate(args),
+        } = cli.command
+        {
+            assert!(args.zone.all_zones);
+        } else {
+            panic!(
+                "Expected Cloudflare Generate command, got {:?}",
+                cli.command
+            );
+        }
+    }
+
+    #[test]
+    fn test_discover_args_both_token_and_zone() {
+        let cli = Cli::parse_from([
+            "tia",
+            "cloudflare",
+            "discover",
+            "--token=my_token",
+            "--zone=my_zone",
+        ]);
+
+        if let ProviderCommand::Cloudflare {
+            command: CloudflareCommand::Discover(args),
+        } = cli.command
+        {
+            assert_eq!(args.token, Some("my_token".to_string()));
+            assert_eq!(args.zone.zones, vec!["my_zone".to_string()]);
+        } else {
+            panic!(
+                "Expected Cloudflare Discover command, got {:?}",
+                cli.command
+            );
+        }
+    }
+
+    #[test]
+    fn test_timeout_sets_both_connect_and_read() {
+        let cli = Cli::parse_from(["tia", "cloudflare", "discover", "--timeout", "20"]);
+
+        if let ProviderCommand::Cloudflare {
+            command: CloudflareCommand::Discover(args),
+        } = cli.command
+        {
+            assert_eq!(args.timeouts.connect(), Some(Duration::from_secs(20)));
+            assert_eq!(args.timeouts.read(), Some(Duration::from_secs(20)));
+        } else {
+            panic!(
+                "Expected Cloudflare Discover command, got {:?}",
+                cli.command
+            );
+        }
+    }
+
+    #[test]
+    fn test_zero_or_negative_timeout_is_rejected() {
+        for flag in ["--timeout", "--connect-timeout", "--read-timeout"] {
+            for value in ["0", "-5"] {
+                let result = Cli::try_parse_from(["tia", "cloudflare", "discover", flag, value]);
+                assert!(result.is_err(), "{} {} was accepted", flag, value);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_timeout_bounds_a_slow_response() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/user/tokens/verify"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({
+                        "success": true,
+                        "result": { "id": "abc123", "status": "active" }
+                    }))
+                    .set_delay(Duration::from_secs(3)),
+            )
+            .mount(&mock_server)
+            .await;
+        let cli = Cli::parse_from(["tia", "cloudflare", "generate", "--timeout", "1"]);
+        let ProviderCommand::Cloudflare {
+            command: CloudflareCommand::Generate(args),
+        } = cli.command
+        else {
+            panic!(
+                "Expected Cloudflare Generate command, got {:?}",
+                cli.command
+            );
+        };
+        let client = tia::CloudflareClient::with_base_url("token".to_string(), mock_server.uri())
+            .unwrap()
+            .with_timeouts(args.timeouts.connect(), args.timeouts.read())
+            .unwrap();
+
+        let started = std::time::Instant::now();
+        let err = client.verify_auth().await.unwrap_err();
+
+        assert!(matches!(err, tia::CloudflareError::Timeout(_)), "{:?}", err);
+        let elapsed = started.elapsed();
+        assert!(
+            (Duration::from_secs(1)..Duration::from_secs(3)).contains(&elapsed),
+            "{:?}",
+            elapsed
+        );
+    }
+
+    #[test]
+    fn test_specific_timeouts_override_timeout() {
+        let cli = Cli::parse_from([
+            "tia",
+            "cloudflare",
+            "discover",
+            "--timeout",
+            "20",
+            "--connect-timeout",
+            "3",
+            "--read-timeout",
+            "120",
+        ]);
+
+        if let ProviderCommand::Cloudflare {
+            command: CloudflareCommand::Discover(args),
+        } = cli.command
+        {
+            assert_eq!(args.timeouts.connect(), Some(Duration::from_secs(3)));
+            assert_eq!(args.timeouts.read(), Some(Duration::from_secs(120)));
+        } else {
+            panic!(
+                "Expected Cloudflare Discover command, got {:?}",
+                cli.command
+            );
+        }
+    }
+
+    #[test]
+    fn test_discover_args_fields_requires_format() {
+        let result = Cli::try_parse_from(["tia", "cloudflare", "discover", "--fields", "name"]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_discover_args_fields_comma_separated() {
+        let cli = Cli::parse_from([
+            "tia",
+            "cloudflare",
+            "discover",
+            "--format",
+            "json",
+            "--fields",
+            "resource_type,metadata.type",
+        ]);
+
+        if let ProviderCommand::Cloudflare {
+            command: CloudflareCommand::Discover(args),
+        } = cli.command
+        {
+            assert_eq!(
+                args.fields,
+                Some(vec![
+                    "resource_type".to_string(),
+                    "metadata.type".to_string()
+                ])
+            );
+        } else {
+            panic!(
+                "Expected Cloudflare Discover command, got {:?}",
+                cli.command
+            );
+        }
+    }
+
+    #[test]
+    fn test_discover_args_watch_with_interval() {
+        let cli = Cli::parse_from([
+            "tia",
+            "cloudflare",
+            "discover",
+            "--watch",
+            "--interval",
+            "60",
+        ]);
+
+        if let ProviderCommand::Cloudflare {
+            command: CloudflareCommand::Discover(args),
+        } = cli.command
+        {
+            assert!(args.watch);
+            assert_eq!(args.interval, 60);
+        } else {
+            panic!(
+                "Expected Cloudflare Discover command, got {:?}",
+                cli.command
+            );
+        }
+    }
+
+    #[test]
+    fn test_discover_args_interval_requires_watch() {
+        let result = Cli::try_parse_from(["tia", "cloudflare", "discover", "--interval", "60"]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_discover_args_format_json() {
+        let cli = Cli::parse_from(["tia", "cloudflare", "discover", "--format", "json"]);
+
+        if let ProviderCommand::Cloudflare {
+            command: CloudflareCommand::Discover(args),
+        } = cli.command
+        {
+            assert_eq!(args.format, Some(OutputFormat::Json));
+        } else {
+            panic!(
+                "Expected Cloudflare Discover command, got {:?}",
+                cli.command
+            );
+        }
+    }
+
+    #[test]
+    fn test_discover_args_last_json_style_flag_wins() {
+        let pretty = |flags: &[&str]| {
+            let argv = ["tia", "cloudflare", "discover"].iter().chain(flags);
+            let cli = Cli::parse_from(argv);
+            if let ProviderCommand::Cloudflare {
+                command: CloudflareCommand::Discover(args),
+            } = cli.command
+            {
+                args.json_style.pretty()
+            } else {
+                panic!(
+                    "Expected Cloudflare Discover command, got {:?}",
+                    cli.command
+                );
+            }
+        };
+
+        assert!(pretty(&[]));
+        assert!(!pretty(&["--compact"]));
+        assert!(pretty(&["--compact", "--pretty"]));
+        assert!(!pretty(&["--pretty", "--compact"]));
+    }
+
+    #[test]
+    fn test_discover_args_format_table() {
+        let cli = Cli::parse_from(["tia", "cloudflare", "discover", "--format", "table"]);
+
+        if let ProviderCommand::Cloudflare {
+            command: CloudflareCommand::Discover(args),
+        } = cli.command
+        {
+            assert_eq!(args.format, Some(OutputFormat::Table));
+        } else {
+            panic!(
+                "Expected Cloudflare Discover command, got {:?}",
+                cli.command
+            );
+        }
+    }
+
+    #[test]
+    fn test_discover_args_format_json_envelope() {
+        let cli = Cli::parse_from(["tia", "cloudflare", "discover", "--format", "json-envelope"]);
+
+        if let ProviderCommand::Cloudflare {
+            command: CloudflareCommand::Discover(args),
+        } = cli.command
+        {
+            assert_eq!(args.format, Some(OutputFormat::JsonEnvelope));
+        } else {
+            panic!(
+                "Expected Cloudflare Discover command, got {:?}",
+                cli.command
+            );
+        }
+    }
+
+    #[test]
+    fn test_expect_min_fails_below_and_passes_at_bound() {
+        let expect = ExpectArgs {
+            expect_min: Some(400),
+            ..Default::default()
+        };
+
+        let err = expect.check(12).unwrap_err();
+        assert!(err.contains("at least 400"));
+        assert!(err.contains("discovered 12"));
+        assert!(expect.check(400).is_ok());
+    }
+
+    #[test]
+    fn test_expect_count_and_max() {
+        let exact = ExpectArgs {
+            expect_count: Some(3),
+            ..Default::default()
+        };
+        assert!(exact.check(3).is_ok());
+        assert!(exact.check(4).is_err());
+
+        let max = ExpectArgs {
+            expect_max: Some(10),
+            ..Default::default()
+        };
+        assert!(max.check(10).is_ok());
+        assert!(max.check(11).unwrap_err().contains("at most 10"));
+        assert!(ExpectArgs::default().check(0).is_ok());
+    }
+
+    #[test]
+    fn test_expect_count_conflicts_with_bounds() {
+        let result = Cli::try_parse_from([
+            "tia",
+            "cloudflare",
+            "discover",
+            "--expect-count=5",
+            "--expect-min=1",
+        ]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_retry_on_parses_list() {
+        let cli = Cli::parse_from(["tia", "cloudflare", "discover", "--retry-on", "409,503"]);
+
+        if let ProviderCommand::Cloudflare {
+            command: CloudflareCommand::Discover(args),
+        } = cli.command
+        {
+            assert_eq!(args.retry.retry_on, Some(vec![409, 503]));
+        } else {
+            panic!(
+                "Expected Cloudflare Discover command, got {:?}",
+                cli.command
+            );
+        }
+    }
+
+    #[test]
+    fn test_zone_cache_defaults_to_one_day() {
+        let cli = Cli::parse_from(["tia", "cloudflare", "discover"]);
+
+        if let ProviderCommand::Cloudflare {
+            command: CloudflareCommand::Discover(args),
+        } = cli.command
+        {
+            assert_eq!(args.zone_cache.ttl(), Some(Duration::from_secs(86_400)));
+        } else {
+            panic!(
+                "Expected Cloudflare Discover command, got {:?}",
+                cli.command
+            );
+        }
+    }
+
+    #[test]
+    fn test_no_cache_disables_zone_cache() {
+        let cli = Cli::parse_from(["tia", "cloudflare", "generate", "--no-cache"]);
+
+        if let ProviderCommand::Cloudflare {
+            command: CloudflareCommand::Generate(args),
+        } = cli.command
+        {
+            assert_eq!(args.zone_cache.ttl(), None);
+        } else {
+            panic!(
+                "Expected Cloudflare Generate command, got {:?}",
+                cli.command
+            );
+        }
+    }
+
+    #[test]
+    fn test_cache_ttl_conflicts_with_no_cache() {
+        let cli = Cli::parse_from(["tia", "cloudflare", "discover", "--cache-ttl", "600"]);
+        if let ProviderCommand::Cloudflare {
+            command: CloudflareCommand::Discover(args),
+        } = cli.command
+        {
+            assert_eq!(args.cache_ttl, Some(600));
+        } else {
+            panic!(
+                "Expected Cloudflare Discover command, got {:?}",
+                cli.command
+            );
+        }
+
+        let result = Cli::try_parse_from([
+            "tia",
+            "cloudflare",
+            "discover",
+            "--cache-ttl",
+            "600",
+            "--no-cache",
+        ]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_exclude_type_is_repeatable() {
+        let cli = Cli::parse_from([
+            "tia",
+            "cloudflare",
+            "discover",
+            "--exclude-type",
+            "cloudflare_ruleset",
+            "--exclude-type=cloudflare_ruleset_rule",
+        ]);
+
+        if let ProviderCommand::Cloudflare {
+            command: CloudflareCommand::Discover(args),
+        } = cli.command
+        {
+            assert!(args.resource_types.is_empty());
+            assert_eq!(
+                args.exclude_types,
+                vec!["cloudflare_ruleset", "cloudflare_ruleset_rule"]
+            );
+        } else {
+            panic!(
+                "Expected Cloudflare Discover command, got {:?}",
+                cli.command
+            );
+        }
+    }
+
+    #[test]
+    fn test_type_is_repeatable() {
+        let cli = Cli::parse_from([
+            "tia",
+            "cloudflare",
+            "discover",
+            "--type",
+            "cloudflare_dns_record",
+            "--type",
+            "cloudflare_ruleset",
+        ]);
+
+        if let ProviderCommand::Cloudflare {
+            command: CloudflareCommand::Discover(args),
+        } = cli.command
+        {
+            assert_eq!(
+                args.resource_types,
+                vec!["cloudflare_dns_record", "cloudflare_ruleset"]
+            );
+        } else {
+            panic!(
+                "Expected Cloudflare Discover command, got {:?}",
+                cli.command
+            );
+        }
+    }
+
+    #[test]
+    fn test_retry_on_rejects_out_of_range_status() {
+        let result =
+            Cli::try_parse_from(["tia", "cloudflare", "discover", "--retry-on", "429,700"]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_ignore_defaults_to_tiaignore() {
+        let cli = Cli::parse_from(["tia", "cloudflare", "discover"]);
+
+        if let ProviderCommand::Cloudflare {
+            command: CloudflareCommand::Discover(args),
+        } = cli.command
+        {
+            assert_eq!(args.ignore, PathBuf::from(".tiaignore"));
+        } else {
+            panic!(
+                "Expected Cloudflare Discover command, got {:?}",
+                cli.command
+            );
+        }
+    }
+
+    #[test]
+    fn test_timeouts_unset_by_default() {
+        let args = TimeoutArgs::default();
+        assert_eq!(args.connect(), None);
+        assert_eq!(args.read(), None);
+    }
+
+    #[test]
+    fn test_generate_args_output_defaults_to_imports_tf() {
+        let cli = Cli::parse_from(["tia", "cloudflare", "generate"]);
+
+        if let ProviderCommand::Cloudflare {
+            command: CloudflareCommand::Generate(args),
+        } = cli.command
+        {
+            assert_eq!(args.output, PathBuf::from("imports.tf"));
+            assert!(!args.force);
+            assert!(!args.with_config);
+            assert_eq!(args.style, ImportStyle::Block);
+        } else {
+            panic!(
+                "Expected Cloudflare Generate command, got {:?}",
+                cli.command
+            );
+        }
+    }
+
+    #[test]
+    fn test_generate_args_output_and_force() {
+        let cli = Cli::parse_from([
+            "tia",
+            "cloudflare",
+            "generate",
+            "--output",
+            "cloudflare/imports.tf",
+            "--force",
+            "--hash-output",
+            "--with-config",
+        ]);
+
+        if let ProviderCommand::Cloudflare {
+            command: CloudflareCommand::Generate(args),
+        } = cli.command
+        {
+            assert_eq!(args.output, PathBuf::from("cloudflare/imports.tf"));
+            assert!(args.force);
+            assert!(args.hash_output);
+            assert!(args.with_config);
+        } else {
+            panic!(
+                "Expected Cloudflare Generate command, got {:?}",
+                cli.command
+            );
+        }
+    }
+
+    #[test]
+    fn test_generate_args_command_style() {
+        let cli = Cli::parse_from(["tia", "cloudflare", "generate", "--style", "command"]);
+
+        if let ProviderCommand::Cloudflare {
+            command: CloudflareCommand::Generate(args),
+        } = cli.command
+        {
+            assert_eq!(args.style, ImportStyle::Command);
+        } else {
+            panic!(
+                "Expected Cloudflare Generate command, got {:?}",
+                cli.command
+            );
+        }
+    }
+
+    #[test]
+    fn test_generate_args_module_conflicts_with_with_config() {
+        let cli = Cli::parse_from(["tia", "cloudflare", "generate", "--module", "cloudflare"]);
+        if let ProviderCommand::Cloudflare {
+            command: CloudflareCommand::Generate(args),
+        } = cli.command
+        {
+            assert_eq!(args.module.as_deref(), Some("cloudflare"));
+        } else {
+            panic!(
+                "Expected Cloudflare Generate command, got {:?}",
+                cli.command
+            );
+        }
+
+        let err = Cli::try_parse_from([
+            "tia",
+            "cloudflare",
+            "generate",
+            "--module",
+            "cloudflare",
+            "--with-config",
+        ])
+        .unwrap_err();
+        assert_eq!(err.kind(), clap::error::ErrorKind::ArgumentConflict);
+    }
+
+    #[test]
+    fn test_discover_args_sort() {
+        let cli = Cli::parse_from(["tia", "cloudflare", "discover", "--sort", "name"]);
+
+        if let ProviderCommand::Cloudflare {
+            command: CloudflareCommand::Discover(args),
+        } = cli.command
+        {
+            assert_eq!(args.sort, Some(SortField::Name));
+            assert_eq!(args.sort.map(SortField::key), Some(SortKey::Name));
+        } else {
+            panic!(
+                "Expected Cloudflare Discover command, got {:?}",
+                cli.command
+            );
+        }
+
+        assert!(Cli::try_parse_from(["tia", "cloudflare", "discover", "--sort", "zone"]).is_err());
+    }
+
+    #[test]
+    fn test_split_by_type_requires_output_dir() {
+        let err =
+            Cli::try_parse_from(["tia", "cloudflare", "generate", "--split-by-type"]).unwrap_err();
+        assert_eq!(err.kind(), clap::error::ErrorKind::MissingRequiredArgument);
+
+        let err = Cli::try_parse_from([
+            "tia",
+            "cloudflare",
+            "generate",
+            "--split-by-type",
+            "--output-dir",
+            "imports",
+            "--output",
+            "imports.tf",
+        ])
+        .unwrap_err();
+        assert_eq!(err.kind(), clap::error::ErrorKind::ArgumentConflict);
+
+        let cli = Cli::parse_from([
+            "tia",
+            "cloudflare",
+            "generate",
+            "--split-by-type",
+            "--output-dir",
+            "imports",
+        ]);
+        if let ProviderCommand::Cloudflare {
+            command: CloudflareCommand::Generate(args),
+        } = cli.command
+        {
+            assert!(args.split_by_type);
+            assert_eq!(args.output_dir, Some(PathBuf::from("imports")));
+        } else {
+            panic!(
+                "Expected Cloudflare Generate command, got {:?}",
+                cli.command
+            );
+        }
+    }
+
+    #[test]
+    fn test_since_json_requires_since() {
+        let err = Cli::try_parse_from(["tia", "cloudflare", "generate", "--since-json", "d.json"])
+            .unwrap_err();
+        assert_eq!(err.kind(), clap::error::ErrorKind::MissingRequiredArgument);
+
+        let cli = Cli::parse_from([
+            "tia",
+            "cloudflare",
+            "discover",
+            "--since",
+            "last.json",
+            "--since-json",
+            "delta.json",
+        ]);
+        if let ProviderCommand::Cloudflare {
+            command: CloudflareCommand::Discover(args),
+        } = cli.command
+        {
+            assert_eq!(args.since.since, Some(PathBuf::from("last.json")));
+            assert_eq!(args.since.since_json, Some(PathBuf::from("delta.json")));
+        } else {
+            panic!(
+                "Expected Cloudflare Discover command, got {:?}",
+                cli.command
+            );
+        }
+    }
+
+    #[test]
+    fn test_diff_args_diff_against_defaults_to_state() {
+        let cli = Cli::parse_from(["tia", "cloudflare", "diff"]);
+
+        if let ProviderCommand::Cloudflare {
+            command: CloudflareCommand::Diff(args),
+        } = cli.command
+        {
+            assert_eq!(args.diff_against, DiffTarget::State);
+        } else {
+            panic!("Expected Cloudflare Diff command, got {:?}", cli.command);
+        }
+    }
+
+    #[test]
+    fn test_diff_args_line_endings_crlf_with_bom() {
+        let cli = Cli::parse_from([
+            "tia",
+            "cloudflare",
+            "diff",
+            "--line-endings",
+            "crlf",
+            "--bom",
+        ]);
+
+        if let ProviderCommand::Cloudflare {
+            command: CloudflareCommand::Diff(args),
+        } = cli.command
+        {
+            assert_eq!(args.encoding.line_endings, LineEndingArg::Crlf);
+            assert!(args.encoding.bom);
 
-#[derive(Subcommand, Debug)]
-pub enum CloudflareCommand {
-    Discover(DiscoverArgs),
-    Generate(GenerateArgs),
-    Diff(DiffArgs),
-}
+            let mut writer = args.encoding.wrap(Vec::new());
+            std::io::Write::write_all(&mut writer, b"a\n").unwrap();
+            assert_eq!(writer.into_inner(), b"\xEF\xBB\xBFa\r\n");
+        } else {
+            panic!("Expected Cloudflare Diff command, got {:?}", cli.command);
+        }
+    }
 
-#[derive(clap::Args, Debug)]
-pub struct DiscoverArgs {
-    #[arg(long, env = "CLOUDFLARE_API_TOKEN", hide_env_values = true)]
-    pub token: Option<String>,
+    #[test]
+    fn test_diff_args_state_module() {
+        let cli = Cli::parse_from([
+            "tia",
+            "cloudflare",
+            "diff",
+            "--state-module",
+            "module.network",
+        ]);
 
-    #[arg(long, env = "CLOUDFLARE_ZONE_ID")]
-    pub zone: Option<String>,
-}
+        if let ProviderCommand::Cloudflare {
+            command: CloudflareCommand::Diff(args),
+        } = cli.command
+        {
+            assert_eq!(args.state_module.as_deref(), Some("module.network"));
+        } else {
+            panic!("Expected Cloudflare Diff command, got {:?}", cli.command);
+        }
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use clap::Parser;
-    use serial_test::serial;
+    #[test]
+    fn test_diff_args_summary_only_with_fail_on_drift() {
+        let cli = Cli::parse_from([
+            "tia",
+            "cloudflare",
+            "diff",
+            "--summary-only",
+            "--fail-on-drift",
+        ]);
+
+        if let ProviderCommand::Cloudflare {
+            command: CloudflareCommand::Diff(args),
+        } = cli.command
+        {
+            assert!(args.summary_only);
+            assert!(args.fail_on_drift);
+        } else {
+            panic!("Expected Cloudflare Diff command, got {:?}", cli.command);
+        }
+    }
 
     #[test]
-    fn test_discover_args_token_from_flag() {
-        let cli = Cli::parse_from(["tia", "cloudflare", "discover", "--token=test_token"]);
+    fn test_diff_args_state_path_with_exit_code_alias() {
+        let cli = Cli::parse_from([
+            "tia",
+            "cloudflare",
+            "diff",
+            "--state",
+            "terraform.tfstate",
+            "--show-state-only",
+            "--exit-code",
+        ]);
 
         if let ProviderCommand::Cloudflare {
-            command: CloudflareCommand::Discover(args),
+            command: CloudflareCommand::Diff(args),
         } = cli.command
         {
-            assert_eq!(args.token, Some("test_token".to_string()));
+            assert_eq!(args.state, Some(PathBuf::from("terraform.tfstate")));
+            assert!(args.show_state_only);
+            assert!(args.fail_on_drift);
         } else {
-            panic!(
-                "Expected Cloudflare Discover command, got {:?}",
-                cli.command
-            );
+            panic!("Expected Cloudflare Diff command, got {:?}", cli.command);
         }
     }
 
     #[test]
-    fn test_discover_args_zone_from_flag() {
-        let cli = Cli::parse_from(["tia", "cloudflare", "discover", "--zone=example.com"]);
+    fn test_diff_args_diff_against_cache() {
+        let cli = Cli::parse_from(["tia", "cloudflare", "diff", "--diff-against", "cache"]);
 
         if let ProviderCommand::Cloudflare {
-            command: CloudflareCommand::Discover(args),
+            command: CloudflareCommand::Diff(args),
         } = cli.command
         {
-            assert_eq!(args.zone, Some("example.com".to_string()));
+            assert_eq!(args.diff_against, DiffTarget::Cache);
         } else {
-            panic!(
-                "Expected Cloudflare Discover command, got {:?}",
-                cli.command
-            );
+            panic!("Expected Cloudflare Diff command, got {:?}", cli.command);
         }
     }
 
     #[test]
-    fn test_discover_args_both_token_and_zone() {
+    fn test_diff_args_format() {
+        let cli = Cli::parse_from(["tia", "cloudflare", "diff", "--state", "tf.tfstate"]);
+        if let ProviderCommand::Cloudflare {
+            command: CloudflareCommand::Diff(args),
+        } = cli.command
+        {
+            assert_eq!(args.format, DiffFormat::Text);
+        } else {
+            panic!("Expected Cloudflare Diff command, got {:?}", cli.command);
+        }
+
         let cli = Cli::parse_from([
             "tia",
             "cloudflare",
-            "discover",
-            "--token=my_token",
-            "--zone=my_zone",
+            "diff",
+            "--state",
+            "tf.tfstate",
+            "--format",
+            "json",
+            "--compact",
         ]);
-
         if let ProviderCommand::Cloudflare {
-            command: CloudflareCommand::Discover(args),
+            command: CloudflareCommand::Diff(args),
         } = cli.command
         {
-            assert_eq!(args.token, Some("my_token".to_string()));
-            assert_eq!(args.zone, Some("my_zone".to_string()));
+            assert_eq!(args.format, DiffFormat::Json);
+            assert!(!args.json_style.pretty());
         } else {
-            panic!(
-                "Expected Cloudflare Discover command, got {:?}",
-                cli.command
-            );
+            panic!("Expected Cloudflare Diff command, got {:?}", cli.command);
         }
     }
 
@@ -121,7 +1645,7 @@ mod tests {
         } = cli.command
         {
             assert!(args.token.is_none());
-            assert!(args.zone.is_none());
+            assert!(args.zone.zones.is_empty());
         } else {
             panic!(
                 "Expected Cloudflare Discover command, got {:?}",
@@ -161,6 +1685,219 @@ mod tests {
         }
     }
 
+    /// Runs `f` with `vars` as the only Cloudflare env vars set.
+    fn with_env<T>(vars: &[(&str, &str)], f: impl FnOnce() -> T) -> T {
+        const VARS: [&str; 3] = ["CLOUDFLARE_API_TOKEN", "CLOUDFLARE_ZONE_ID", ZONES_ENV];
+        let backup: Vec<Option<String>> = VARS.iter().map(|v| std::env::var(v).ok()).collect();
+        unsafe {
+            for var in VARS {
+                std::env::remove_var(var);
+            }
+            for (var, value) in vars {
+                std::env::set_var(var, value);
+            }
+        }
+
+        let result = f();
+
+        unsafe {
+            for (var, value) in VARS.iter().zip(backup) {
+                match value {
+                    Some(value) => std::env::set_var(var, value),
+                    None => std::env::remove_var(var),
+                }
+            }
+        }
+        result
+    }
+
+    /// Parses a discover command line with `vars` as the only Cloudflare env vars set.
+    fn discover_args_with_env(argv: &[&str], vars: &[(&str, &str)]) -> DiscoverArgs {
+        let cli = with_env(vars, || {
+            Cli::parse_from(["tia", "cloudflare", "discover"].iter().chain(argv))
+        });
+        match cli.command {
+            ProviderCommand::Cloudflare {
+                command: CloudflareCommand::Discover(args),
+            } => args,
+            other => panic!("Expected Cloudflare Discover command, got {:?}", other),
+        }
+    }
+
+    /// Like [`discover_args_with_env`], but also applies `file` with `vars` still set.
+    fn configured_discover_args(
+        argv: &[&str],
+        vars: &[(&str, &str)],
+        file: FileConfig,
+    ) -> DiscoverArgs {
+        with_env(vars, || {
+            let mut args = discover_args_with_env(argv, vars);
+            args.apply_config(file).unwrap();
+            args
+        })
+    }
+
+    fn file_config() -> FileConfig {
+        FileConfig {
+            token: Some("file_token".to_string()),
+            zones: Some(vec!["file.example.com".to_string()]),
+            types: Some(vec!["cloudflare_ruleset".to_string()]),
+            format: Some("json-envelope".to_string()),
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_config_file_fills_unset_values() {
+        let mut args = discover_args_with_env(&[], &[]);
+
+        args.apply_config(file_config()).unwrap();
+
+        assert_eq!(args.token.as_deref(), Some("file_token"));
+        assert_eq!(args.zone.zones, vec!["file.example.com"]);
+        assert_eq!(args.resource_types, vec!["cloudflare_ruleset"]);
+        assert_eq!(args.format, Some(OutputFormat::JsonEnvelope));
+    }
+
+    #[test]
+    #[serial]
+    fn test_cli_flags_override_config_file() {
+        let mut args = discover_args_with_env(
+            &[
+                "--token=cli_token",
+                "--zone=cli.example.com",
+                "--type=cloudflare_dns_record",
+                "--format=json",
+            ],
+            &[],
+        );
+
+        args.apply_config(file_config()).unwrap();
+
+        assert_eq!(args.token.as_deref(), Some("cli_token"));
+        assert_eq!(args.zone.zones, vec!["cli.example.com"]);
+        assert_eq!(args.resource_types, vec!["cloudflare_dns_record"]);
+        assert_eq!(args.format, Some(OutputFormat::Json));
+    }
+
+    #[test]
+    #[serial]
+    fn test_env_vars_override_config_file() {
+        let mut args = discover_args_with_env(
+            &[],
+            &[
+                ("CLOUDFLARE_API_TOKEN", "env_token"),
+                ("CLOUDFLARE_ZONE_ID", "env.example.com"),
+            ],
+        );
+
+        args.apply_config(file_config()).unwrap();
+
+        assert_eq!(args.token.as_deref(), Some("env_token"));
+        assert_eq!(args.zone.zones, vec!["env.example.com"]);
+        assert_eq!(args.resource_types, vec!["cloudflare_ruleset"]);
+    }
+
+    #[test]
+    #[serial]
+    fn test_zones_env_var_fans_out_and_replaces_config_file_zones() {
+        let args = configured_discover_args(
+            &[],
+            &[(ZONES_ENV, "a.example.com, b.example.com")],
+            file_config(),
+        );
+
+        assert_eq!(args.zone.zones, vec!["a.example.com", "b.example.com"]);
+    }
+
+    #[test]
+    #[serial]
+    fn test_zone_flag_and_zone_id_env_override_zones_env_var() {
+        let zones_env = (ZONES_ENV, "a.example.com,b.example.com");
+
+        let args = configured_discover_args(
+            &["--zone=cli.example.com"],
+            &[zones_env],
+            FileConfig::default(),
+        );
+        assert_eq!(args.zone.zones, vec!["cli.example.com"]);
+
+        let args = configured_discover_args(
+            &[],
+            &[("CLOUDFLARE_ZONE_ID", "id.example.com"), zones_env],
+            FileConfig::default(),
+        );
+        assert_eq!(args.zone.zones, vec!["id.example.com"]);
+    }
+
+    #[test]
+    #[serial]
+    fn test_all_zones_flag_ignores_zones_env_var() {
+        let args = configured_discover_args(
+            &["--all-zones"],
+            &[(ZONES_ENV, "a.example.com")],
+            FileConfig::default(),
+        );
+
+        assert!(args.zone.zones.is_empty());
+    }
+
+    #[test]
+    #[serial]
+    fn test_all_zones_flag_ignores_config_file_zones() {
+        let mut args = discover_args_with_env(&["--all-zones"], &[]);
+
+        args.apply_config(file_config()).unwrap();
+
+        assert!(args.zone.zones.is_empty());
+    }
+
+    #[test]
+    #[serial]
+    fn test_config_file_rejects_unknown_format() {
+        let mut args = discover_args_with_env(&[], &[]);
+        let file = FileConfig {
+            format: Some("yaml".to_string()),
+            ..FileConfig::default()
+        };
+
+        let err = args.apply_config(file).unwrap_err();
+
+        assert!(err.to_string().contains("unknown format 'yaml'"));
+    }
+
+    #[test]
+    fn test_config_file_formats_match_output_formats() {
+        let names: Vec<String> = OutputFormat::value_variants()
+            .iter()
+            .map(|f| f.to_possible_value().unwrap().get_name().to_string())
+            .collect();
+
+        assert_eq!(names, tia::config::FORMATS);
+    }
+
+    #[test]
+    fn test_explicit_config_path_must_exist() {
+        let cli = Cli::parse_from([
+            "tia",
+            "cloudflare",
+            "discover",
+            "--config",
+            "/nonexistent/tia.toml",
+        ]);
+        if let ProviderCommand::Cloudflare {
+            command: CloudflareCommand::Discover(args),
+        } = cli.command
+        {
+            assert!(args.config.load().is_err());
+        } else {
+            panic!(
+                "Expected Cloudflare Discover command, got {:?}",
+                cli.command
+            );
+        }
+    }
+
     #[test]
     #[serial]
     fn test_zone_from_env_var_fallback() {
@@ -183,7 +1920,7 @@ mod tests {
             command: CloudflareCommand::Discover(args),
         } = cli.command
         {
-            assert_eq!(args.zone, Some("env_zone_id_123".to_string()));
+            assert_eq!(args.zone.zones, vec!["env_zone_id_123".to_string()]);
         } else {
             panic!(
                 "Expected Cloudflare Discover command, got {:?}",
@@ -214,7 +1951,7 @@ mod tests {
             command: CloudflareCommand::Discover(args),
         } = cli.command
         {
-            assert_eq!(args.zone, Some("cli_zone_id".to_string()));
+            assert_eq!(args.zone.zones, vec!["cli_zone_id".to_string()]);
         } else {
             panic!(
                 "Expected Cloudflare Discover command, got {:?}",
@@ -226,10 +1963,283 @@ mod tests {
 
 #[derive(clap::Args, Debug)]
 pub struct GenerateArgs {
-    // TODO: implement
+    #[command(flatten)]
+    pub config: ConfigArgs,
+
+    #[arg(long, env = "CLOUDFLARE_API_TOKEN", hide_env_values = true)]
+    pub token: Option<String>,
+
+    #[command(flatten)]
+    pub zone: ZoneArgs,
+
+    #[command(flatten)]
+    pub timeouts: TimeoutArgs,
+
+    #[command(flatten)]
+    pub retry: RetryArgs,
+
+    #[command(flatten)]
+    pub zone_cache: ZoneCacheArgs,
+
+    /// Only generate imports for this resource type, e.g. `cloudflare_dns_record`;
+    /// repeatable.
+    #[arg(long = "type", value_name = "RESOURCE_TYPE")]
+    pub resource_types: Vec<String>,
+
+    /// Skip this resource type, even if `--type` names it; repeatable.
+    #[arg(long = "exclude-type", value_name = "RESOURCE_TYPE")]
+    pub exclude_types: Vec<String>,
+
+    /// Also include rules the API reports as disabled, e.g. legacy rate limits.
+    #[arg(long)]
+    pub include_disabled: bool,
+
+    /// Also include entries skipped as defaults or unmanageable: zone settings left at
+    /// their default and Cloudflare-managed rulesets.
+    #[arg(long)]
+    pub include_defaults: bool,
+
+    /// Also write a skeleton `resource` block after each import block, filled in from
+    /// the discovered metadata. Only DNS records are supported so far.
+    #[arg(long)]
+    pub with_config: bool,
+
+    /// Write `import {}` blocks, or `terraform import` commands for Terraform versions
+    /// without import blocks.
+    #[arg(long, value_enum, default_value_t)]
+    pub style: ImportStyle,
+
+    /// Import into this module instead of the root module, e.g. `cloudflare` for
+    /// `module.cloudflare.cloudflare_dns_record.www`; nest with `a.module.b`.
+    #[arg(long, value_name = "PATH", conflicts_with = "with_config")]
+    pub module: Option<String>,
+
+    /// File to write the import blocks to.
+    #[arg(long, short, default_value = "imports.tf")]
+    pub output: PathBuf,
+
+    /// Write one file per resource type, e.g. `dns_records.tf`, into `--output-dir`
+    /// instead of a single `--output` file. Types with nothing to import get no file.
+    #[arg(long, requires = "output_dir", conflicts_with_all = ["output", "hash_output"])]
+    pub split_by_type: bool,
+
+    /// Directory for the per-type files of `--split-by-type`; created if missing.
+    #[arg(long, value_name = "DIR", requires = "split_by_type")]
+    pub output_dir: Option<PathBuf>,
+
+    /// Overwrite the output file if it already exists.
+    #[arg(long)]
+    pub force: bool,
+
+    /// Print a `sha256:<hex>` line for the written file and save a `<file>.sha256`
+    /// checksum next to it.
+    #[arg(long)]
+    pub hash_output: bool,
+
+    /// Also write a JSON manifest of the imported resources to this file, for audits
+    /// or a later comparison. Like `--output`, it's only replaced with `--force`.
+    #[arg(long, value_name = "PATH")]
+    pub manifest: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub since: SinceArgs,
+
+    #[command(flatten)]
+    pub encoding: EncodingArgs,
+
+    /// File of glob patterns for resources to leave out; ignored if it doesn't exist.
+    #[arg(long, default_value = DEFAULT_IGNORE_FILE)]
+    pub ignore: PathBuf,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct VerifyArgs {
+    #[command(flatten)]
+    pub config: ConfigArgs,
+
+    #[arg(long, env = "CLOUDFLARE_API_TOKEN", hide_env_values = true)]
+    pub token: Option<String>,
+
+    #[command(flatten)]
+    pub zone: ZoneArgs,
+
+    #[command(flatten)]
+    pub timeouts: TimeoutArgs,
+
+    #[command(flatten)]
+    pub retry: RetryArgs,
+
+    #[command(flatten)]
+    pub zone_cache: ZoneCacheArgs,
+
+    /// Only check this resource type, e.g. `cloudflare_dns_record`; repeatable.
+    #[arg(long = "type", value_name = "RESOURCE_TYPE")]
+    pub resource_types: Vec<String>,
+
+    /// Don't check this resource type, even if `--type` names it; repeatable.
+    #[arg(long = "exclude-type", value_name = "RESOURCE_TYPE")]
+    pub exclude_types: Vec<String>,
 }
 
 #[derive(clap::Args, Debug)]
 pub struct DiffArgs {
-    // TODO: implement
+    #[command(flatten)]
+    pub config: ConfigArgs,
+
+    #[arg(long, env = "CLOUDFLARE_API_TOKEN", hide_env_values = true)]
+    pub token: Option<String>,
+
+    #[command(flatten)]
+    pub zone: ZoneArgs,
+
+    /// What to compare the live discovery against.
+    #[arg(long, value_enum, default_value_t = DiffTarget::State)]
+    pub diff_against: DiffTarget,
+
+    /// Terraform state file to compare against, e.g. `terraform.tfstate`.
+    #[arg(long)]
+    pub state: Option<PathBuf>,
+
+    /// Only compare against state resources under this module path, e.g. `module.network`.
+    #[arg(long)]
+    pub state_module: Option<String>,
+
+    /// Also list state entries that no longer exist live.
+    #[arg(long)]
+    pub show_state_only: bool,
+
+    /// Print only the one-line counts instead of every differing resource.
+    #[arg(long)]
+    pub summary_only: bool,
+
+    /// How to print the comparison. `json` always holds the full comparison, whatever
+    /// `--summary-only` or `--show-state-only` say, and needs `--diff-against state`.
+    #[arg(long, value_enum, default_value_t = DiffFormat::Text)]
+    pub format: DiffFormat,
+
+    #[command(flatten)]
+    pub json_style: JsonStyleArgs,
+
+    /// Exit non-zero when any difference is found.
+    #[arg(long, visible_alias = "exit-code")]
+    pub fail_on_drift: bool,
+
+    #[command(flatten)]
+    pub timeouts: TimeoutArgs,
+
+    #[command(flatten)]
+    pub retry: RetryArgs,
+
+    #[command(flatten)]
+    pub zone_cache: ZoneCacheArgs,
+
+    #[command(flatten)]
+    pub encoding: EncodingArgs,
+
+    /// Also include rules the API reports as disabled, e.g. legacy rate limits.
+    #[arg(long)]
+    pub include_disabled: bool,
+
+    /// Also include entries skipped as defaults or unmanageable: zone settings left at
+    /// their default and Cloudflare-managed rulesets.
+    #[arg(long)]
+    pub include_defaults: bool,
+
+    /// File of glob patterns for resources to leave out; ignored if it doesn't exist.
+    #[arg(long, default_value = DEFAULT_IGNORE_FILE)]
+    pub ignore: PathBuf,
+}
+
+#[derive(clap::Args, Debug, Default)]
+pub struct EncodingArgs {
+    /// Line terminator for text output.
+    #[arg(long, value_enum, default_value_t = LineEndingArg::Lf)]
+    pub line_endings: LineEndingArg,
+
+    /// Prefix text output with a UTF-8 byte order mark.
+    #[arg(long)]
+    pub bom: bool,
+}
+
+impl EncodingArgs {
+    pub fn wrap<W: std::io::Write>(&self, writer: W) -> EncodedWriter<W> {
+        let line_ending = match self.line_endings {
+            LineEndingArg::Lf => LineEnding::Lf,
+            LineEndingArg::Crlf => LineEnding::Crlf,
+        };
+        EncodedWriter::new(writer, line_ending, self.bom)
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Log a count of the discovered resources.
+    #[default]
+    Text,
+    /// A JSON array of resources, for piping into `jq` and other tooling.
+    Json,
+    /// A JSON object carrying `tia_version`, `generated_at` and `zone_id` alongside
+    /// the `resources` array.
+    JsonEnvelope,
+    /// An aligned table of type, name, id and zone, for reading in a terminal.
+    Table,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Human-readable lines.
+    #[default]
+    Text,
+    /// One JSON object per line, for log pipelines.
+    Json,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ImportStyle {
+    /// Terraform 1.5+ `import {}` blocks.
+    #[default]
+    Block,
+    /// One `terraform import <address> <id>` shell command per line.
+    Command,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortField {
+    Type,
+    Name,
+    Id,
+}
+
+impl SortField {
+    pub fn key(self) -> SortKey {
+        match self {
+            SortField::Type => SortKey::Type,
+            SortField::Name => SortKey::Name,
+            SortField::Id => SortKey::Id,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LineEndingArg {
+    #[default]
+    Lf,
+    Crlf,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DiffFormat {
+    /// Resources grouped by type, then a summary line.
+    #[default]
+    Text,
+    /// An object with `to_import`, `orphaned` and `in_sync`, for CI to gate on.
+    Json,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DiffTarget {
+    /// A Terraform state file.
+    State,
+    /// The last cached discovery for the zone.
+    Cache,
 }
@@ -0,0 +1,224 @@
+//! `tia.toml` support for settings that would otherwise be repeated on every run.
+//!
+//! The file is TOML, deserialized with the `toml` crate into [`FileConfig`]; only its
+//! top-level keys are known, so tables and any other key are rejected.
+//!
+//! Zones can also be listed in a plain text file, one per line, for runs over more
+//! zones than fit on a command line, or in the `CLOUDFLARE_ZONES` environment variable.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::error::TiaError;
+
+pub const DEFAULT_CONFIG_FILE: &str = "tia.toml";
+
+/// Values accepted for `format`, matching `discover --format`. Checked when the file is
+/// parsed, so an invalid file fails every command alike, even those that ignore it.
+pub const FORMATS: &[&str] = &["text", "json", "json-envelope", "table"];
+
+/// Comma-separated zones to discover when neither `--zone` nor `CLOUDFLARE_ZONE_ID`
+/// names any; it replaces the config file's `zones` rather than adding to them.
+pub const ZONES_ENV: &str = "CLOUDFLARE_ZONES";
+
+/// Settings read from a config file. Every field is optional; command-line flags and
+/// environment variables take precedence over anything set here.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FileConfig {
+    pub token: Option<String>,
+    pub zones: Option<Vec<String>>,
+    pub types: Option<Vec<String>>,
+    pub format: Option<String>,
+}
+
+impl FileConfig {
+    pub fn parse(contents: &str) -> Result<Self, TiaError> {
+        Self::from_str(contents).map_err(TiaError::Config)
+    }
+
+    /// Loads `path`; a missing file yields an empty config unless `required` is set.
+    pub fn load(path: &Path, required: bool) -> Result<Self, TiaError> {
+        match fs::read_to_string(path) {
+            Ok(contents) => Self::from_str(&contents)
+                .map_err(|e| TiaError::Config(format!("{}: {}", path.display(), e))),
+            Err(e) if e.kind() == io::ErrorKind::NotFound && !required => Ok(Self::default()),
+            Err(e) => Err(TiaError::Config(format!("{}: {}", path.display(), e))),
+        }
+    }
+}
+
+/// Zone names or ids from a zones file: one per line, with blank lines and `#`
+/// comments ignored.
+pub fn parse_zones_file(contents: &str) -> Vec<String> {
+    contents
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default().trim())
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Zone names or ids from a comma-separated list, as in [`ZONES_ENV`]; whitespace
+/// around each entry and empty entries are dropped.
+pub fn parse_zone_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|zone| !zone.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Reads a zones file; unlike `tia.toml` it's never optional, so a missing file is an
+/// error.
+pub fn load_zones_file(path: &Path) -> Result<Vec<String>, TiaError> {
+    fs::read_to_string(path)
+        .map(|contents| parse_zones_file(&contents))
+        .map_err(|e| TiaError::Config(format!("{}: {}", path.display(), e)))
+}
+
+impl FileConfig {
+    fn from_str(contents: &str) -> Result<Self, String> {
+        let config: Self = toml::from_str(contents).map_err(|e| e.to_string())?;
+        if let Some(format) = &config.format
+            && !FORMATS.contains(&format.as_str())
+        {
+            return Err(format!(
+                "unknown format '{}' (valid: {})",
+                format,
+                FORMATS.join(", ")
+            ));
+        }
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_zones_file_skips_blanks_and_comments() {
+        let contents = "# onboarding batch 1\nexample.com\n\n  example.org  # legacy\n\t\n023e105f4ecef8ad9ca31a8372d0c353\n";
+
+        assert_eq!(
+            parse_zones_file(contents),
+            vec![
+                "example.com",
+                "example.org",
+                "023e105f4ecef8ad9ca31a8372d0c353"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_zone_list_trims_and_skips_empty_entries() {
+        assert_eq!(
+            parse_zone_list(" example.com, example.org,,\t"),
+            vec!["example.com", "example.org"]
+        );
+        assert!(parse_zone_list("").is_empty());
+    }
+
+    #[test]
+    fn test_load_zones_file_requires_the_file() {
+        let err = load_zones_file(Path::new("/nonexistent/zones.txt")).unwrap_err();
+        assert!(
+            err.to_string().contains("/nonexistent/zones.txt"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_parse_every_field() {
+        let config = FileConfig::parse(
+            r#"
+            # Shared settings for the production account.
+            token = "cf-token" # inline comment
+            zones = ["example.com", 'example.org']
+            types = [
+                "cloudflare_dns_record", # records first
+                "cloudflare_ruleset",
+            ]
+            format = "json"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config,
+            FileConfig {
+                token: Some("cf-token".to_string()),
+                zones: Some(vec!["example.com".to_string(), "example.org".to_string()]),
+                types: Some(vec![
+                    "cloudflare_dns_record".to_string(),
+                    "cloudflare_ruleset".to_string()
+                ]),
+                format: Some("json".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_empty_file_sets_nothing() {
+        assert_eq!(
+            FileConfig::parse("\n# nothing here\n").unwrap(),
+            FileConfig::default()
+        );
+    }
+
+    #[test]
+    fn test_parse_string_escapes_and_hashes() {
+        let config = FileConfig::parse(r#"token = "a\"b#c\\d""#).unwrap();
+        assert_eq!(config.token.as_deref(), Some("a\"b#c\\d"));
+    }
+
+    #[test]
+    fn test_parse_rejects_unknown_keys() {
+        let err = FileConfig::parse("zone = \"example.com\"").unwrap_err();
+        assert!(err.to_string().contains("unknown field `zone`"));
+    }
+
+    #[test]
+    fn test_parse_rejects_unsupported_syntax() {
+        for contents in [
+            "[cloudflare]\ntoken = \"x\"",
+            "token = \"unterminated",
+            "zones = [\"a\",",
+            "token \"x\"",
+            "zones = [[\"a\"]]",
+            "token = \"a\"\ntoken = \"b\"",
+        ] {
+            assert!(FileConfig::parse(contents).is_err(), "{:?}", contents);
+        }
+    }
+
+    #[test]
+    fn test_parse_rejects_wrong_types() {
+        let err = FileConfig::parse("zones = \"example.com\"").unwrap_err();
+        assert!(err.to_string().contains("invalid type"));
+    }
+
+    #[test]
+    fn test_parse_rejects_unknown_format() {
+        let err = FileConfig::parse("format = \"yaml\"").unwrap_err();
+        assert!(err.to_string().contains("unknown format 'yaml'"), "{}", err);
+    }
+
+    #[test]
+    fn test_load_missing_file() {
+        let path =
+            std::env::temp_dir().join(format!("tia-config-missing-{}.toml", std::process::id()));
+
+        assert_eq!(
+            FileConfig::load(&path, false).unwrap(),
+            FileConfig::default()
+        );
+        assert!(FileConfig::load(&path, true).is_err());
+    }
+}
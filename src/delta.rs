@@ -0,0 +1,215 @@
+//! Change detection between two sets of discovered resources.
+//!
+//! Resources are matched on [`Resource::identity_key`]; a matched pair whose name or
+//! [normalized](Resource::normalized) metadata differs is reported as changed.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::resource::Resource;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ResourceChange {
+    pub previous: Resource,
+    pub current: Resource,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ResourceDelta {
+    pub added: Vec<Resource>,
+    pub removed: Vec<Resource>,
+    pub changed: Vec<ResourceChange>,
+}
+
+impl ResourceDelta {
+    /// Computes what changed going from `previous` to `current`.
+    ///
+    /// Every list is ordered by identity key so repeated runs print identically.
+    pub fn between(previous: &[Resource], current: &[Resource]) -> Self {
+        let previous_by_key: BTreeMap<_, _> =
+            previous.iter().map(|r| (r.identity_key(), r)).collect();
+        let current_by_key: BTreeMap<_, _> =
+            current.iter().map(|r| (r.identity_key(), r)).collect();
+
+        let mut delta = Self::default();
+
+        for (key, resource) in &current_by_key {
+            match previous_by_key.get(key) {
+                None => delta.added.push((*resource).clone()),
+                Some(old)
+                    if old.name != resource.name
+                        || old.normalized().metadata != resource.normalized().metadata =>
+                {
+                    delta.changed.push(ResourceChange {
+                        previous: (*old).clone(),
+                        current: (*resource).clone(),
+                    });
+                }
+                Some(_) => {}
+            }
+        }
+
+        delta.removed = previous_by_key
+            .iter()
+            .filter(|(key, _)| !current_by_key.contains_key(*key))
+            .map(|(_, resource)| (*resource).clone())
+            .collect();
+
+        delta
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "{} added, {} removed, {} changed",
+            self.added.len(),
+            self.removed.len(),
+            self.changed.len()
+        )
+    }
+}
+
+/// Remembers the previous discovery so each new run can be reported as a delta.
+#[derive(Debug, Default)]
+pub struct DeltaTracker {
+    previous: Option<Vec<Resource>>,
+}
+
+impl DeltaTracker {
+    /// Records `current` and returns its delta against the previous observation, or
+    /// `None` for the first one, which only establishes the baseline.
+    pub fn observe(&mut self, current: Vec<Resource>) -> Option<ResourceDelta> {
+        let delta = self
+            .previous
+            .as_deref()
+            .map(|previous| ResourceDelta::between(previous, &current));
+        self.previous = Some(current);
+        delta
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(id: &str, name: &str, metadata: serde_json::Value) -> Resource {
+        Resource {
+            resource_type: "cloudflare_dns_record".to_string(),
+            resource_id: id.to_string(),
+            name: name.to_string(),
+            zone_id: "zone123".to_string(),
+            zone_name: None,
+            account_id: None,
+            metadata,
+        }
+    }
+
+    #[test]
+    fn test_delta_identical_sets_is_empty() {
+        let resources = vec![record("r1", "api.example.com", serde_json::json!({}))];
+        let delta = ResourceDelta::between(&resources, &resources);
+        assert!(delta.is_empty());
+    }
+
+    #[test]
+    fn test_delta_detects_added_and_removed() {
+        let previous = vec![
+            record("r1", "api.example.com", serde_json::json!({})),
+            record("r2", "old.example.com", serde_json::json!({})),
+        ];
+        let current = vec![
+            record("r1", "api.example.com", serde_json::json!({})),
+            record("r3", "new.example.com", serde_json::json!({})),
+        ];
+
+        let delta = ResourceDelta::between(&previous, &current);
+
+        assert_eq!(delta.added.len(), 1);
+        assert_eq!(delta.added[0].resource_id, "r3");
+        assert_eq!(delta.removed.len(), 1);
+        assert_eq!(delta.removed[0].resource_id, "r2");
+        assert!(delta.changed.is_empty());
+    }
+
+    #[test]
+    fn test_delta_detects_metadata_change() {
+        let previous = vec![record("r1", "api", serde_json::json!({"type": "A"}))];
+        let current = vec![record("r1", "api", serde_json::json!({"type": "CNAME"}))];
+
+        let delta = ResourceDelta::between(&previous, &current);
+
+        assert!(delta.added.is_empty());
+        assert!(delta.removed.is_empty());
+        assert_eq!(delta.changed.len(), 1);
+        assert_eq!(delta.changed[0].previous.metadata["type"], "A");
+        assert_eq!(delta.changed[0].current.metadata["type"], "CNAME");
+    }
+
+    #[test]
+    fn test_delta_ignores_metadata_key_order() {
+        let previous = vec![record(
+            "r1",
+            "api",
+            serde_json::from_str(r#"{"type": "A", "proxied": true}"#).unwrap(),
+        )];
+        let current = vec![record(
+            "r1",
+            "api",
+            serde_json::from_str(r#"{"proxied": true, "type": "A"}"#).unwrap(),
+        )];
+
+        assert!(ResourceDelta::between(&previous, &current).is_empty());
+    }
+
+    #[test]
+    fn test_tracker_first_observation_is_baseline() {
+        let mut tracker = DeltaTracker::default();
+        assert!(
+            tracker
+                .observe(vec![record("r1", "a", serde_json::json!({}))])
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_tracker_successive_cycles_report_changes() {
+        let mut tracker = DeltaTracker::default();
+        tracker.observe(vec![
+            record("r1", "api", serde_json::json!({"proxied": true})),
+            record("r2", "www", serde_json::json!({})),
+        ]);
+
+        let second = tracker
+            .observe(vec![
+                record("r1", "api", serde_json::json!({"proxied": false})),
+                record("r3", "cdn", serde_json::json!({})),
+            ])
+            .unwrap();
+        assert_eq!(second.summary(), "1 added, 1 removed, 1 changed");
+        assert_eq!(second.changed[0].current.resource_id, "r1");
+
+        let third = tracker
+            .observe(vec![
+                record("r1", "api", serde_json::json!({"proxied": false})),
+                record("r3", "cdn", serde_json::json!({})),
+            ])
+            .unwrap();
+        assert!(third.is_empty());
+    }
+
+    #[test]
+    fn test_delta_same_id_different_type_is_not_a_match() {
+        let previous = vec![record("shared", "a", serde_json::json!({}))];
+        let mut rule = record("shared", "a", serde_json::json!({}));
+        rule.resource_type = "cloudflare_page_rule".to_string();
+
+        let delta = ResourceDelta::between(&previous, &[rule]);
+
+        assert_eq!(delta.added.len(), 1);
+        assert_eq!(delta.removed.len(), 1);
+    }
+}
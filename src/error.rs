@@ -17,6 +17,9 @@ pub enum TiaError {
 
     #[error("configuration error: {0}")]
     Config(String),
+
+    #[error("state error: {0}")]
+    State(String),
 }
 
 #[cfg(test)]
@@ -0,0 +1,164 @@
+//! `.tiaignore` support for excluding specific resources from output and generation.
+//!
+//! Each non-blank line not starting with `#` is a glob pattern (`*` matches any run of
+//! characters, `?` a single one) tested against a resource's name and its address
+//! `<resource_type>.<name>`.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::error::TiaError;
+use crate::resource::Resource;
+
+pub const DEFAULT_IGNORE_FILE: &str = ".tiaignore";
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IgnoreRules {
+    patterns: Vec<String>,
+}
+
+impl IgnoreRules {
+    pub fn parse(contents: &str) -> Self {
+        let patterns = contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::to_string)
+            .collect();
+        Self { patterns }
+    }
+
+    /// Loads rules from `path`; a missing file yields empty rules.
+    pub fn load(path: &Path) -> Result<Self, TiaError> {
+        match fs::read_to_string(path) {
+            Ok(contents) => Ok(Self::parse(&contents)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    pub fn is_ignored(&self, resource: &Resource) -> bool {
+        let address = format!("{}.{}", resource.resource_type, resource.name);
+        self.patterns
+            .iter()
+            .any(|pattern| glob_match(pattern, &resource.name) || glob_match(pattern, &address))
+    }
+
+    pub fn apply(&self, resources: Vec<Resource>) -> Vec<Resource> {
+        if self.is_empty() {
+            return resources;
+        }
+        resources
+            .into_iter()
+            .filter(|resource| !self.is_ignored(resource))
+            .collect()
+    }
+}
+
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star_p, star_t)) => {
+                    p = star_p + 1;
+                    t = star_t + 1;
+                    backtrack = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(name: &str) -> Resource {
+        Resource {
+            resource_type: "cloudflare_dns_record".to_string(),
+            resource_id: format!("id_{}", name),
+            name: name.to_string(),
+            zone_id: "zone123".to_string(),
+            zone_name: None,
+            account_id: None,
+            metadata: serde_json::json!({}),
+        }
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("*_temp", "build_temp"));
+        assert!(glob_match("api.?.example.com", "api.1.example.com"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("a*b*c", "axxbyyc"));
+        assert!(!glob_match("*_temp", "temp_build"));
+        assert!(!glob_match("api", "api.example.com"));
+    }
+
+    #[test]
+    fn test_parse_skips_comments_and_blanks() {
+        let rules =
+            IgnoreRules::parse("# temporary records\n\n*_temp\n   \n  # indented\nstaging.*\n");
+        assert_eq!(rules.patterns, vec!["*_temp", "staging.*"]);
+    }
+
+    #[test]
+    fn test_apply_excludes_matching_resources() {
+        let rules = IgnoreRules::parse("*_temp\ncloudflare_dns_record.legacy*");
+        let kept = rules.apply(vec![
+            record("api"),
+            record("build_temp"),
+            record("legacy.example.com"),
+            record("www"),
+        ]);
+
+        let names: Vec<&str> = kept.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["api", "www"]);
+    }
+
+    #[test]
+    fn test_address_pattern_is_type_specific() {
+        let rules = IgnoreRules::parse("cloudflare_page_rule.*");
+        assert!(!rules.is_ignored(&record("api")));
+    }
+
+    #[test]
+    fn test_load_missing_file_is_empty() {
+        let rules = IgnoreRules::load(Path::new("/nonexistent/dir/.tiaignore")).unwrap();
+        assert!(rules.is_empty());
+    }
+
+    #[test]
+    fn test_load_reads_file() {
+        let path = std::env::temp_dir().join(format!("tiaignore-{}", std::process::id()));
+        fs::write(&path, "# drop scratch\nscratch*\n").unwrap();
+
+        let rules = IgnoreRules::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert!(rules.is_ignored(&record("scratch1")));
+        assert!(!rules.is_ignored(&record("api")));
+    }
+}
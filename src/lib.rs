@@ -2,13 +2,20 @@
 //!
 //! A library for discovering cloud provider resources and generating Terraform import blocks.
 
+pub mod cache;
+pub mod config;
+pub mod delta;
+pub mod ignore;
+pub mod output;
 pub mod providers;
+#[cfg(feature = "resolve-targets")]
+pub mod resolve;
 pub mod resource;
+pub mod terraform;
 
-mod cache;
 mod error;
-mod output;
-mod terraform;
 
+pub use error::TiaError;
 pub use providers::cloudflare::{CloudflareClient, CloudflareError, ZoneInfo};
-pub use resource::{DiscoverConfig, Resource};
+pub use providers::discover;
+pub use resource::{DiscoverConfig, DiscoverConfigBuilder, Resource};
@@ -0,0 +1,184 @@
+//! Subscriber setup for `--log-format`.
+//!
+//! The JSON format is written by hand rather than with `tracing_subscriber::fmt().json()`:
+//! that formatter serializes event fields straight through `tracing-serde`, with no hook
+//! to redact a field before it's written, so [`is_secret`] couldn't be applied. The
+//! output keeps its shape: one object per line with `timestamp`, `level`, `target` and
+//! `fields`.
+
+use std::fmt;
+use std::time::SystemTime;
+
+use serde_json::{Map, Value};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::EnvFilter;
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields, MakeWriter};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::SubscriberInitExt;
+
+use tia::output::format_timestamp;
+
+use crate::cli::LogFormat;
+
+/// Installs the global subscriber. Logs go to stderr so they never mix with
+/// `--format json` on stdout.
+pub fn init(filter: EnvFilter, format: LogFormat) {
+    match format {
+        LogFormat::Text => tracing_subscriber::fmt()
+            .with_env_filter(filter)
+            .with_writer(std::io::stderr)
+            .init(),
+        LogFormat::Json => json_subscriber(filter, std::io::stderr).init(),
+    }
+}
+
+fn json_subscriber<W>(filter: EnvFilter, writer: W) -> impl Subscriber + Send + Sync + 'static
+where
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(writer)
+        .event_format(JsonFormat)
+        .finish()
+}
+
+/// Writes each event as a single-line JSON object.
+struct JsonFormat;
+
+impl<S, N> FormatEvent<S, N> for JsonFormat
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        _ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let metadata = event.metadata();
+        let mut fields = JsonFields::default();
+        event.record(&mut fields);
+
+        let line = serde_json::json!({
+            "timestamp": format_timestamp(SystemTime::now()),
+            "level": metadata.level().to_string(),
+            "target": metadata.target(),
+            "fields": fields.0,
+        });
+        writeln!(writer, "{}", line)
+    }
+}
+
+/// Whether a field with this name could carry a credential. Nothing logs one today;
+/// this keeps it that way if a field is ever added carelessly.
+fn is_secret(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    name.contains("token") || name.contains("secret") || name == "authorization"
+}
+
+#[derive(Default)]
+struct JsonFields(Map<String, Value>);
+
+impl JsonFields {
+    fn insert(&mut self, field: &Field, value: Value) {
+        let value = if is_secret(field.name()) {
+            Value::from("[REDACTED]")
+        } else {
+            value
+        };
+        self.0.insert(field.name().to_string(), value);
+    }
+}
+
+impl Visit for JsonFields {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.insert(field, Value::String(format!("{:?}", value)));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.insert(field, Value::from(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.insert(field, Value::from(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.insert(field, Value::from(value));
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.insert(field, Value::from(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.insert(field, Value::from(value));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Buffer {
+        fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(bytes)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_json_subscriber_writes_one_object_per_event() {
+        let buffer = Buffer::default();
+        let writer = buffer.clone();
+        let subscriber = json_subscriber(EnvFilter::new("info"), move || writer.clone());
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!(zone_id = "zone456", count = 3, "resources discovered");
+            tracing::debug!("filtered out");
+        });
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 1, "{}", output);
+        let line: Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(line["level"], "INFO");
+        assert_eq!(
+            line["fields"],
+            serde_json::json!({
+                "message": "resources discovered",
+                "zone_id": "zone456",
+                "count": 3
+            })
+        );
+        assert!(line["timestamp"].as_str().unwrap().ends_with('Z'));
+    }
+
+    #[test]
+    fn test_json_fields_redact_credentials() {
+        let buffer = Buffer::default();
+        let writer = buffer.clone();
+        let subscriber = json_subscriber(EnvFilter::new("info"), move || writer.clone());
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!(token = "cf-secret-value", "authenticating");
+        });
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        assert!(!output.contains("cf-secret-value"), "{}", output);
+        assert!(output.contains("[REDACTED]"), "{}", output);
+    }
+}
@@ -1,46 +1,739 @@
 mod cli;
+mod logging;
+
+use std::io::Write;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
 
 use clap::Parser;
-use color_eyre::eyre::Result;
+use color_eyre::eyre::{Result, eyre};
+use regex::Regex;
 use tracing_subscriber::EnvFilter;
 
-use cli::{Cli, CloudflareCommand, ProviderCommand};
-use tia::{DiscoverConfig, providers};
+use cli::{
+    AwsCommand, AwsDiscoverArgs, Cli, CloudflareCommand, DiffArgs, DiffFormat, DiffTarget,
+    DiscoverArgs, EncodingArgs, GenerateArgs, ImportStyle, OutputFormat, ProviderCommand,
+    VerifyArgs,
+};
+use tia::cache::{CacheOptions, DiscoveryCache};
+use tia::delta::{DeltaTracker, ResourceDelta};
+use tia::ignore::IgnoreRules;
+use tia::output::envelope::{Envelope, write_envelope};
+use tia::output::manifest::{Manifest, SavedManifest};
+use tia::providers::{Access, DiscoveryReport};
+use tia::resource::filter_by_name;
+use tia::terraform::drift::StateDiff;
+use tia::terraform::naming::check_module_path;
+use tia::terraform::state::TerraformState;
+use tia::{DiscoverConfig, Resource, output, providers};
 
 #[tokio::main]
 async fn main() -> Result<()> {
     color_eyre::install()?;
 
-    tracing_subscriber::fmt()
-        .with_env_filter(EnvFilter::from_default_env())
-        .with_writer(std::io::stderr)
-        .init();
-
     let cli = Cli::parse();
 
+    let filter = match cli.log_level() {
+        Some(level) => EnvFilter::default().add_directive(level.into()),
+        None => EnvFilter::from_default_env(),
+    };
+    logging::init(filter, cli.log_format);
+    // Settings shared by every Cloudflare command; each fills in the rest.
+    let base = DiscoverConfig {
+        max_concurrency: cli.max_concurrency,
+        api_base_url: cli.api_base,
+        ..Default::default()
+    };
+
     match cli.command {
         ProviderCommand::Cloudflare { command } => match command {
-            CloudflareCommand::Discover(args) => {
-                let provider = providers::get_provider("cloudflare", args.token.clone())?;
-                let config = DiscoverConfig {
-                    zone: args.zone,
-                    token: args.token,
-                    ..Default::default()
-                };
-                let resources = provider.discover(&config).await?;
-                tracing::info!(count = resources.len(), "discovery complete");
+            CloudflareCommand::Discover(args) => run_discover(args, base).await?,
+            CloudflareCommand::Generate(args) => run_generate(args, base).await?,
+            CloudflareCommand::Verify(args) => run_verify(args, base).await?,
+            CloudflareCommand::Diff(mut args) => {
+                args.zone.read_zones_file()?;
+                let file = args.config.load()?;
+                args.apply_config(file);
+                match args.diff_against {
+                    DiffTarget::State => {
+                        diff_against_state(&diff_config(&args, base), &args).await?
+                    }
+                    DiffTarget::Cache => {
+                        diff_against_cache(&diff_config(&args, base), &args).await?
+                    }
+                }
             }
-            CloudflareCommand::Generate(_args) => {
-                let provider = providers::get_provider("cloudflare", None)?;
-                tracing::info!("Cloudflare generate - not yet implemented");
-                let _ = provider; // Suppress unused warning
+        },
+        ProviderCommand::Aws { command } => match command {
+            AwsCommand::Discover(args) => run_aws_discover(args).await?,
+        },
+    }
+
+    Ok(())
+}
+
+async fn run_discover(mut args: DiscoverArgs, base: DiscoverConfig) -> Result<()> {
+    args.zone.read_zones_file()?;
+    let file = args.config.load()?;
+    args.apply_config(file)?;
+    if args.fields.is_some()
+        && !matches!(
+            args.format,
+            Some(OutputFormat::Json | OutputFormat::JsonEnvelope)
+        )
+    {
+        return Err(eyre!(
+            "--fields only applies to --format json or --format json-envelope"
+        ));
+    }
+
+    let config = DiscoverConfig {
+        zones: args.zone.zones,
+        all_zones: args.zone.all_zones,
+        account_id: args.zone.account_id,
+        all_accounts: args.zone.all_accounts,
+        token: args.token,
+        connect_timeout: args.timeouts.connect(),
+        read_timeout: args.timeouts.read(),
+        quiet_skip: args.quiet_skip,
+        strict: args.strict,
+        retry_on: args.retry.retry_on,
+        zone_cache_ttl: args.zone_cache.ttl(),
+        resource_types: type_filter(args.resource_types),
+        exclude_types: args.exclude_types,
+        include_disabled: args.include_disabled,
+        include_defaults: args.include_defaults,
+        fail_fast: args.fail_fast,
+        dry_run: args.dry_run,
+        page_size: args.page_size,
+        concurrency: args.concurrency,
+        zone_concurrency: args.concurrency_across_zones,
+        per_zone_concurrency: args.concurrency_per_zone,
+        progress: args.progress,
+        ..base
+    };
+    // Validate before spending any API calls.
+    if let Some(fields) = &args.fields {
+        output::json::project(&[], fields)?;
+    }
+    let cloudflare = providers::get_provider("cloudflare", None)?;
+    if let Some(types) = &config.resource_types {
+        cloudflare.check_resource_types(types)?;
+    }
+    cloudflare.check_resource_types(&config.exclude_types)?;
+
+    let ignore = IgnoreRules::load(&args.ignore)?;
+    let since = args
+        .since
+        .since
+        .as_deref()
+        .map(SavedManifest::load)
+        .transpose()?;
+
+    let name_filter = args.name_filter.as_ref();
+
+    if args.watch {
+        return watch(
+            &config,
+            &ignore,
+            name_filter,
+            Duration::from_secs(args.interval),
+        )
+        .await;
+    }
+
+    if config.dry_run {
+        // Fails on a missing token or zone like a real run would, but sends nothing.
+        discover(&config).await?;
+        for request in providers::cloudflare::dry_run_requests(&config) {
+            println!("{}", request);
+        }
+        return Ok(());
+    }
+
+    // A zone's cache entry only holds what that zone alone discovers: no narrowed types
+    // and no account-level resources from accounts besides its own.
+    let cacheable = !config.filters_types() && config.account_id.is_none() && !config.all_accounts;
+    let cached = args
+        .cache_ttl
+        .zip(single_zone(&config))
+        .filter(|_| cacheable)
+        .and_then(|(ttl, zone)| {
+            DiscoveryCache::default_location()?.load_fresh(
+                zone,
+                CacheOptions::from(&config),
+                Duration::from_secs(ttl),
+            )
+        });
+    let from_cache = cached.is_some();
+    let report = match cached {
+        Some(entry) => {
+            tracing::info!(
+                zone = %entry.zone,
+                cached_at = entry.cached_at,
+                "using cached discovery; no API requests sent"
+            );
+            DiscoveryReport {
+                resources: entry.resources,
+                ..Default::default()
             }
-            CloudflareCommand::Diff(_args) => {
-                let provider = providers::get_provider("cloudflare", None)?;
-                tracing::info!("Cloudflare diff - not yet implemented");
-                let _ = provider; // Suppress unused warning
+        }
+        None => {
+            providers::get_provider("cloudflare", config.token.clone())?
+                .discover_report(&config)
+                .await?
+        }
+    };
+    output::write_discovery_failures(&report.errors, &mut std::io::stderr().lock())?;
+    let (complete, all_failed) = (report.errors.is_empty(), report.all_failed());
+    let failed_zones: Vec<String> = report
+        .failed_zones()
+        .into_iter()
+        .map(str::to_string)
+        .collect();
+    let api_usage = report.api_usage;
+    let skipped = report.skipped;
+
+    // The cache keeps what the API returned, before `.tiaignore`, `--name-filter` and
+    // enrichment, which can change between runs. A filtered or partial run would show
+    // up as removals in the next `diff --diff-against cache`, and rewriting a cache hit
+    // would keep it fresh forever.
+    let to_cache = single_zone(&config)
+        .filter(|_| cacheable && complete && !from_cache)
+        .map(|zone| (zone, report.resources.clone()));
+
+    let mut resources = ignore.apply(report.resources);
+    if let Some(pattern) = name_filter {
+        resources = filter_by_name(resources, pattern);
+    }
+    output::sort_resources(
+        &mut resources,
+        args.sort.map(|s| s.key()).unwrap_or_default(),
+    );
+    #[cfg(feature = "resolve-targets")]
+    if args.resolve_targets {
+        tia::resolve::enrich_cname_targets(
+            &mut resources,
+            &tia::resolve::SystemResolver,
+            tia::resolve::DEFAULT_LOOKUP_INTERVAL,
+        )
+        .await;
+    }
+    tracing::info!(count = resources.len(), "discovery complete");
+
+    let redacted;
+    let shown = if args.redact_ids {
+        redacted = output::redact::redact_ids(&resources);
+        &redacted
+    } else {
+        &resources
+    };
+
+    let pretty = args.json_style.pretty();
+    match args.format.unwrap_or_default() {
+        OutputFormat::Text => {}
+        OutputFormat::Json => match &args.fields {
+            Some(fields) => {
+                let projected = output::json::project(shown, fields)?;
+                output::json::write_values(&projected, &mut std::io::stdout().lock(), pretty)?;
             }
+            None => output::json::write_json(shown, &mut std::io::stdout().lock(), pretty)?,
         },
+        OutputFormat::JsonEnvelope => {
+            let values = match &args.fields {
+                Some(fields) => output::json::project(shown, fields)?,
+                None => output::json::to_values(shown)?,
+            };
+            let envelope = Envelope::new(
+                output::format_timestamp(SystemTime::now()),
+                single_zone(&config).and(shown.first().map(|r| r.zone_id.as_str())),
+                &values,
+            );
+            write_envelope(&envelope, &mut std::io::stdout().lock(), pretty)?;
+        }
+        OutputFormat::Table => output::table::write_table(shown, &mut std::io::stdout().lock())?,
+    }
+
+    if all_failed {
+        return Err(eyre!("discovery failed for every resource type"));
+    }
+
+    let filtered = config.filters_types();
+    if resources.is_empty() {
+        output::write_empty_notice(&mut std::io::stderr().lock(), filtered)?;
+    }
+    output::write_summary(
+        &output::summarize(&resources),
+        &mut std::io::stderr().lock(),
+    )?;
+    output::write_skipped(&skipped, config.quiet_skip, &mut std::io::stderr().lock())?;
+    output::write_api_usage(&api_usage, &mut std::io::stderr().lock())?;
+    if let Some(previous) = &since {
+        report_since(previous, &resources, args.since.since_json.as_deref())?;
+    }
+
+    if let Some((zone, raw)) = &to_cache {
+        cache_discovery(zone, CacheOptions::from(&config), raw);
+    }
+
+    // The other zones were still printed; a multi-zone run only fails once it's done.
+    if single_zone(&config).is_none() && !failed_zones.is_empty() {
+        return Err(eyre!(
+            "discovery failed for {} zone(s): {}",
+            failed_zones.len(),
+            failed_zones.join(", ")
+        ));
+    }
+
+    args.expect
+        .check(resources.len())
+        .map_err(|msg| eyre!(msg))?;
+
+    Ok(())
+}
+
+async fn run_aws_discover(args: AwsDiscoverArgs) -> Result<()> {
+    let config = DiscoverConfig {
+        zones: args.zones,
+        all_zones: args.all_zones,
+        connect_timeout: args.timeouts.connect(),
+        read_timeout: args.timeouts.read(),
+        ..Default::default()
+    };
+
+    let resources = providers::get_provider("aws", None)?
+        .discover(&config)
+        .await?;
+    tracing::info!(count = resources.len(), "discovery complete");
+
+    let pretty = args.json_style.pretty();
+    match args.format.unwrap_or_default() {
+        OutputFormat::Text => {}
+        OutputFormat::Json => {
+            output::json::write_json(&resources, &mut std::io::stdout().lock(), pretty)?
+        }
+        OutputFormat::JsonEnvelope => {
+            let values = output::json::to_values(&resources)?;
+            let envelope = Envelope::new(
+                output::format_timestamp(SystemTime::now()),
+                single_zone(&config).and(resources.first().map(|r| r.zone_id.as_str())),
+                &values,
+            );
+            write_envelope(&envelope, &mut std::io::stdout().lock(), pretty)?;
+        }
+        OutputFormat::Table => {
+            output::table::write_table(&resources, &mut std::io::stdout().lock())?
+        }
+    }
+
+    if resources.is_empty() {
+        output::write_empty_notice(&mut std::io::stderr().lock(), false)?;
+    }
+
+    Ok(())
+}
+
+async fn run_generate(mut args: GenerateArgs, base: DiscoverConfig) -> Result<()> {
+    args.zone.read_zones_file()?;
+    let file = args.config.load()?;
+    args.apply_config(file);
+
+    let config = DiscoverConfig {
+        zones: args.zone.zones,
+        all_zones: args.zone.all_zones,
+        account_id: args.zone.account_id,
+        all_accounts: args.zone.all_accounts,
+        token: args.token,
+        connect_timeout: args.timeouts.connect(),
+        read_timeout: args.timeouts.read(),
+        retry_on: args.retry.retry_on,
+        zone_cache_ttl: args.zone_cache.ttl(),
+        resource_types: type_filter(args.resource_types),
+        exclude_types: args.exclude_types,
+        include_disabled: args.include_disabled,
+        include_defaults: args.include_defaults,
+        ..base
+    };
+    if args.with_config && args.style == ImportStyle::Command {
+        return Err(eyre!("--with-config only applies to --style block"));
+    }
+    if args.split_by_type && args.style == ImportStyle::Command {
+        return Err(eyre!("--split-by-type only applies to --style block"));
+    }
+    if let Some(module) = &args.module {
+        check_module_path(module)?;
+    }
+    let ignore = IgnoreRules::load(&args.ignore)?;
+    let since = args
+        .since
+        .since
+        .as_deref()
+        .map(SavedManifest::load)
+        .transpose()?;
+
+    let provider = providers::get_provider("cloudflare", config.token.clone())?;
+    let resources = ignore.apply(provider.discover(&config).await?);
+    if resources.is_empty() {
+        output::write_empty_notice(&mut std::io::stderr().lock(), config.filters_types())?;
+        if let Some(previous) = &since {
+            report_since(previous, &resources, args.since.since_json.as_deref())?;
+        }
+        return Ok(());
+    }
+
+    let bytes = match &args.output_dir {
+        Some(dir) => {
+            write_split_by_type(
+                provider.as_ref(),
+                &resources,
+                dir,
+                args.with_config,
+                args.module.as_deref(),
+                &args.encoding,
+                args.force,
+            )?;
+            None
+        }
+        None => {
+            let mut writer = args.encoding.wrap(Vec::new());
+            let (document, kind) = match args.style {
+                ImportStyle::Block => (
+                    provider.generate_document(
+                        &resources,
+                        args.with_config,
+                        args.module.as_deref(),
+                    ),
+                    "import blocks",
+                ),
+                ImportStyle::Command => (
+                    provider.generate_import_commands(&resources, args.module.as_deref()),
+                    "import commands",
+                ),
+            };
+            writer.write_all(document.as_bytes())?;
+            let bytes = writer.into_inner();
+
+            output::write_output_file(&args.output, &bytes, args.force)?;
+            eprintln!(
+                "Wrote {} {} to {}",
+                importable_count(&resources),
+                kind,
+                args.output.display()
+            );
+            Some(bytes)
+        }
+    };
+    if let Some(path) = &args.manifest {
+        let manifest = Manifest::new(
+            output::format_timestamp(SystemTime::now()),
+            single_zone(&config),
+            &resources,
+        );
+        output::write_output_file(path, &manifest.to_bytes()?, args.force)?;
+    }
+    output::write_summary(
+        &output::summarize(&resources),
+        &mut std::io::stderr().lock(),
+    )?;
+    if let Some(previous) = &since {
+        report_since(previous, &resources, args.since.since_json.as_deref())?;
+    }
+
+    if args.hash_output
+        && let Some(bytes) = &bytes
+    {
+        output::integrity::write_checksum(&args.output, bytes)?;
+        println!("{}", output::integrity::integrity_line(bytes));
+    }
+
+    Ok(())
+}
+
+/// Writes each type's import blocks to its own file in `dir`, skipping types with
+/// nothing importable.
+fn write_split_by_type(
+    provider: &dyn providers::Provider,
+    resources: &[Resource],
+    dir: &Path,
+    with_config: bool,
+    module: Option<&str>,
+    encoding: &EncodingArgs,
+    force: bool,
+) -> Result<()> {
+    std::fs::create_dir_all(dir)?;
+    for (resource_type, group) in output::group_by_type(resources) {
+        let document = provider.generate_document(&group, with_config, module);
+        if document.is_empty() {
+            continue;
+        }
+        let mut writer = encoding.wrap(Vec::new());
+        writer.write_all(document.as_bytes())?;
+
+        let path = dir.join(output::type_file_name(resource_type));
+        output::write_output_file(&path, &writer.into_inner(), force)?;
+        eprintln!(
+            "Wrote {} import blocks to {}",
+            importable_count(&group),
+            path.display()
+        );
+    }
+    Ok(())
+}
+
+fn importable_count(resources: &[Resource]) -> usize {
+    resources
+        .iter()
+        .filter(|r| providers::is_importable(r))
+        .count()
+}
+
+/// Prints what changed since `previous` to stderr, and as JSON to `json_path` if given.
+/// Only importable resources are compared, since those are all a manifest records.
+fn report_since(
+    previous: &SavedManifest,
+    resources: &[Resource],
+    json_path: Option<&Path>,
+) -> Result<()> {
+    let current: Vec<Resource> = resources
+        .iter()
+        .filter(|r| providers::is_importable(r))
+        .cloned()
+        .collect();
+    let delta = ResourceDelta::between(&previous.resources, &current);
+
+    let mut stderr = std::io::stderr().lock();
+    writeln!(stderr, "Since {}:", previous.generated_at)?;
+    output::diff::write_delta(&delta, &mut stderr)?;
+    if let Some(path) = json_path {
+        let mut bytes = serde_json::to_vec_pretty(&delta)?;
+        bytes.push(b'\n');
+        std::fs::write(path, bytes)?;
+    }
+    Ok(())
+}
+
+/// An empty `--type` list means every type.
+fn type_filter(types: Vec<String>) -> Option<Vec<String>> {
+    (!types.is_empty()).then_some(types)
+}
+
+/// Re-runs discovery every `interval`, printing a timestamped delta per cycle until
+/// interrupted with Ctrl-C. A failed cycle is logged and retried on the next tick.
+async fn watch(
+    config: &DiscoverConfig,
+    ignore: &IgnoreRules,
+    name_filter: Option<&Regex>,
+    interval: Duration,
+) -> Result<()> {
+    let mut tracker = DeltaTracker::default();
+
+    loop {
+        let cycle = tokio::select! {
+            _ = tokio::signal::ctrl_c() => break,
+            result = discover(config) => result,
+        };
+        let timestamp = output::format_timestamp(SystemTime::now());
+
+        match cycle {
+            Ok(resources) => {
+                let mut resources = ignore.apply(resources);
+                if let Some(pattern) = name_filter {
+                    resources = filter_by_name(resources, pattern);
+                }
+                let count = resources.len();
+                match tracker.observe(resources) {
+                    None => println!("[{}] baseline: {} resources", timestamp, count),
+                    Some(delta) => {
+                        println!("[{}] {}", timestamp, delta.summary());
+                        if !delta.is_empty() {
+                            output::diff::write_delta_changes(
+                                &delta,
+                                &mut std::io::stdout().lock(),
+                            )?;
+                        }
+                    }
+                }
+            }
+            Err(e) => tracing::warn!(error = %e, "discovery cycle failed"),
+        }
+
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => break,
+            _ = tokio::time::sleep(interval) => {}
+        }
+    }
+
+    tracing::info!("watch stopped");
+    Ok(())
+}
+
+async fn run_verify(mut args: VerifyArgs, base: DiscoverConfig) -> Result<()> {
+    args.zone.read_zones_file()?;
+    let file = args.config.load()?;
+    args.apply_config(file);
+
+    let config = DiscoverConfig {
+        zones: args.zone.zones,
+        all_zones: args.zone.all_zones,
+        account_id: args.zone.account_id,
+        all_accounts: args.zone.all_accounts,
+        token: args.token,
+        connect_timeout: args.timeouts.connect(),
+        read_timeout: args.timeouts.read(),
+        retry_on: args.retry.retry_on,
+        zone_cache_ttl: args.zone_cache.ttl(),
+        resource_types: type_filter(args.resource_types),
+        exclude_types: args.exclude_types,
+        ..base
+    };
+
+    let report = providers::cloudflare::CloudflareProvider::new(None)
+        .verify_access(&config)
+        .await?;
+    output::write_access_report(&report, &mut std::io::stdout().lock())?;
+
+    let denied = report
+        .iter()
+        .flat_map(|zone| &zone.checks)
+        .filter(|(_, access)| *access != Access::Ok)
+        .count();
+    if denied > 0 {
+        return Err(eyre!("{} resource type check(s) did not pass", denied));
+    }
+    Ok(())
+}
+
+async fn discover(config: &DiscoverConfig) -> Result<Vec<Resource>> {
+    Ok(tia::discover("cloudflare", config.clone()).await?)
+}
+
+/// The zone `config` is scoped to, when it names exactly one.
+fn single_zone(config: &DiscoverConfig) -> Option<&str> {
+    match config.zones.as_slice() {
+        [zone] if !config.all_zones => Some(zone),
+        _ => None,
+    }
+}
+
+/// Best-effort: a cache write failure never fails the discovery itself.
+fn cache_discovery(zone: &str, options: CacheOptions, resources: &[Resource]) {
+    let Some(cache) = DiscoveryCache::default_location() else {
+        return;
+    };
+    if let Err(e) = cache.store(zone, options, resources) {
+        tracing::warn!(error = %e, "failed to write discovery cache");
+    }
+}
+
+fn diff_config(args: &DiffArgs, base: DiscoverConfig) -> DiscoverConfig {
+    DiscoverConfig {
+        zones: args.zone.zones.clone(),
+        all_zones: args.zone.all_zones,
+        account_id: args.zone.account_id.clone(),
+        all_accounts: args.zone.all_accounts,
+        token: args.token.clone(),
+        connect_timeout: args.timeouts.connect(),
+        read_timeout: args.timeouts.read(),
+        retry_on: args.retry.retry_on.clone(),
+        zone_cache_ttl: args.zone_cache.ttl(),
+        include_disabled: args.include_disabled,
+        include_defaults: args.include_defaults,
+        ..base
+    }
+}
+
+async fn diff_against_state(config: &DiscoverConfig, args: &DiffArgs) -> Result<()> {
+    let path = args
+        .state
+        .as_deref()
+        .ok_or_else(|| eyre!("No state file provided. Use --state <path>"))?;
+    let file = std::fs::File::open(path)
+        .map_err(|e| eyre!("failed to open state file {}: {}", path.display(), e))?;
+    let mut state = TerraformState::from_reader(std::io::BufReader::new(file))?;
+    if let Some(module) = &args.state_module {
+        state = state.scoped_to_module(module);
+    }
+
+    let ignore = IgnoreRules::load(&args.ignore)?;
+    let cloudflare = providers::get_provider("cloudflare", config.token.clone())?;
+    let report = cloudflare.discover_report(config).await?;
+    if let Some(failure) = report.errors.into_iter().next() {
+        return Err(failure.error.into());
+    }
+    let (ignored, current): (Vec<Resource>, Vec<Resource>) = report
+        .resources
+        .into_iter()
+        .partition(|r| ignore.is_ignored(r));
+    if current.is_empty() && !args.summary_only {
+        output::write_empty_notice(&mut std::io::stderr().lock(), false)?;
+    }
+
+    let discovered_types: Vec<&str> = cloudflare
+        .resource_types()
+        .into_iter()
+        .filter(|t| config.wants(t))
+        .collect();
+    let diff = StateDiff::between(&current, &state, &discovered_types, &report.zones)
+        .without_ignored(&ignored);
+
+    let mut stdout = args.encoding.wrap(std::io::stdout().lock());
+    if args.format == DiffFormat::Json {
+        output::diff::write_state_diff_json(&diff, &mut stdout, args.json_style.pretty())?;
+    } else if args.summary_only {
+        writeln!(stdout, "{}", diff.summary())?;
+    } else {
+        output::diff::write_state_diff(&diff, args.show_state_only, &mut stdout)?;
+    }
+    stdout.flush()?;
+
+    if args.fail_on_drift && diff.has_drift() {
+        return Err(eyre!("drift detected: {}", diff.summary()));
+    }
+
+    Ok(())
+}
+
+async fn diff_against_cache(config: &DiscoverConfig, args: &DiffArgs) -> Result<()> {
+    if args.format == DiffFormat::Json {
+        return Err(eyre!("--format json only applies to --diff-against state"));
+    }
+    if config.zones.is_empty() && !config.all_zones {
+        return Err(eyre!(
+            "No zone provided. Set CLOUDFLARE_ZONE_ID or CLOUDFLARE_ZONES, or use --zone flag"
+        ));
+    }
+    let zone = single_zone(config).ok_or_else(|| {
+        eyre!("--diff-against cache compares a single zone; pass exactly one --zone")
+    })?;
+
+    let cache = DiscoveryCache::default_location()
+        .ok_or_else(|| eyre!("could not determine a cache directory"))?;
+    let previous = cache.load(zone, CacheOptions::from(config)).ok_or_else(|| {
+        eyre!(
+            "no cached discovery for '{}' with these options; run `tia cloudflare discover` first, with the same --include-disabled and --include-defaults",
+            zone
+        )
+    })?;
+
+    let ignore = IgnoreRules::load(&args.ignore)?;
+    let current = ignore.apply(discover(config).await?);
+    if current.is_empty() && !args.summary_only {
+        output::write_empty_notice(&mut std::io::stderr().lock(), false)?;
+    }
+
+    // The cache holds unfiltered results, so today's `.tiaignore` applies to both sides.
+    let delta = ResourceDelta::between(&ignore.apply(previous.resources), &current);
+
+    let mut stdout = args.encoding.wrap(std::io::stdout().lock());
+    if args.summary_only {
+        writeln!(stdout, "{}", delta.summary())?;
+    } else {
+        output::diff::write_delta(&delta, &mut stdout)?;
+    }
+    stdout.flush()?;
+
+    if args.fail_on_drift && !delta.is_empty() {
+        return Err(eyre!("drift detected: {}", delta.summary()));
     }
 
     Ok(())
@@ -1,6 +1,458 @@
-// TODO: implement
+//! Rendering of discovery results.
 
+pub mod diff;
+pub mod encoding;
+pub mod envelope;
 pub mod hcl;
+pub mod integrity;
 pub mod json;
+pub mod manifest;
+pub mod redact;
+pub mod shell;
 pub mod table;
 pub mod tree;
+
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::error::TiaError;
+use crate::providers::{ApiUsage, DiscoveryFailure, SkippedTypes, ZoneAccess};
+use crate::resource::Resource;
+
+/// Formats `time` as an RFC 3339 UTC timestamp with second precision.
+pub fn format_timestamp(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let (days, rem) = ((secs / 86_400) as i64, secs % 86_400);
+
+    // Civil-from-days conversion for the proleptic Gregorian calendar.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        rem / 3_600,
+        rem % 3_600 / 60,
+        rem % 60
+    )
+}
+
+/// Explains an empty result. The filter hint is only shown when filters were applied,
+/// since an unfiltered empty result just means the zone has nothing importable.
+pub fn write_empty_notice<W: Write>(writer: &mut W, filtered: bool) -> io::Result<()> {
+    writeln!(writer, "No resources found matching your filters.")?;
+    if filtered {
+        writeln!(
+            writer,
+            "Hint: --type or --exclude-type filters are active; widen or remove them to see more."
+        )?;
+    }
+    Ok(())
+}
+
+/// Counts `resources` per resource type, ordered by type.
+pub fn summarize(resources: &[Resource]) -> BTreeMap<String, usize> {
+    let mut counts = BTreeMap::new();
+    for resource in resources {
+        *counts.entry(resource.resource_type.clone()).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// What [`sort_resources`] orders by first; ties fall back to the other fields so
+/// the order never depends on what the API returned first.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortKey {
+    /// Type, then name, then id.
+    #[default]
+    Type,
+    /// Name, then type, then id.
+    Name,
+    /// Id, then type.
+    Id,
+}
+
+/// Orders `resources` by `key`, so two runs over the same resources print the same.
+pub fn sort_resources(resources: &mut [Resource], key: SortKey) {
+    match key {
+        SortKey::Type => resources.sort_by(|a, b| {
+            (&a.resource_type, &a.name, &a.resource_id).cmp(&(
+                &b.resource_type,
+                &b.name,
+                &b.resource_id,
+            ))
+        }),
+        SortKey::Name => resources.sort_by(|a, b| {
+            (&a.name, &a.resource_type, &a.resource_id).cmp(&(
+                &b.name,
+                &b.resource_type,
+                &b.resource_id,
+            ))
+        }),
+        SortKey::Id => resources.sort_by(|a, b| {
+            (&a.resource_id, &a.resource_type).cmp(&(&b.resource_id, &b.resource_type))
+        }),
+    }
+}
+
+/// Groups `resources` by type, ordered by type, keeping their order within a type.
+pub fn group_by_type(resources: &[Resource]) -> BTreeMap<&str, Vec<Resource>> {
+    let mut groups: BTreeMap<&str, Vec<Resource>> = BTreeMap::new();
+    for resource in resources {
+        groups
+            .entry(resource.resource_type.as_str())
+            .or_default()
+            .push(resource.clone());
+    }
+    groups
+}
+
+/// The file a type's imports go to with `--split-by-type`: the type without its
+/// provider prefix, pluralized, e.g. `dns_records.tf` for `cloudflare_dns_record`.
+pub fn type_file_name(resource_type: &str) -> String {
+    let stem = resource_type
+        .split_once('_')
+        .map_or(resource_type, |(_, rest)| rest);
+    format!("{}s.tf", stem)
+}
+
+/// Writes `summary` as one `type: count, type: count` line; nothing when it's empty.
+pub fn write_summary<W: Write>(
+    summary: &BTreeMap<String, usize>,
+    writer: &mut W,
+) -> io::Result<()> {
+    if summary.is_empty() {
+        return Ok(());
+    }
+    let parts: Vec<String> = summary
+        .iter()
+        .map(|(resource_type, count)| format!("{}: {}", resource_type, count))
+        .collect();
+    writeln!(writer, "{}", parts.join(", "))
+}
+
+/// Writes a `warning: skipped <type>: <reason>` line per skipped type, unless `quiet`,
+/// then the total; nothing when no type was skipped.
+pub fn write_skipped<W: Write>(
+    skipped: &SkippedTypes,
+    quiet: bool,
+    writer: &mut W,
+) -> io::Result<()> {
+    if !quiet {
+        for (resource_type, reason) in skipped.iter() {
+            writeln!(writer, "warning: skipped {}: {}", resource_type, reason)?;
+        }
+    }
+    match skipped.summary() {
+        Some(summary) => writeln!(writer, "{}", summary),
+        None => Ok(()),
+    }
+}
+
+/// Writes an `API requests: n` line, noting the attempts when retries sent more;
+/// nothing when no requests were counted.
+pub fn write_api_usage<W: Write>(usage: &ApiUsage, writer: &mut W) -> io::Result<()> {
+    if usage.attempts == 0 {
+        return Ok(());
+    }
+    if usage.attempts == usage.requests {
+        return writeln!(writer, "API requests: {}", usage.requests);
+    }
+    writeln!(
+        writer,
+        "API requests: {} ({} attempts including retries)",
+        usage.requests, usage.attempts
+    )
+}
+
+/// Writes a `warning:` line for each resource type that failed, so partial results
+/// aren't mistaken for complete ones.
+pub fn write_discovery_failures<W: Write>(
+    failures: &[DiscoveryFailure],
+    writer: &mut W,
+) -> io::Result<()> {
+    for failure in failures {
+        writeln!(writer, "warning: could not discover {}", failure)?;
+    }
+    Ok(())
+}
+
+/// Writes one `resource_type: access` line per check, under a line naming each zone.
+pub fn write_access_report<W: Write>(report: &[ZoneAccess], writer: &mut W) -> io::Result<()> {
+    for zone in report {
+        writeln!(writer, "{}", zone.zone)?;
+        for (resource_type, access) in &zone.checks {
+            writeln!(writer, "  {}: {}", resource_type, access)?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes `bytes` to `path`, refusing to replace an existing file unless `force` is set.
+pub fn write_output_file(path: &Path, bytes: &[u8], force: bool) -> Result<(), TiaError> {
+    if force {
+        fs::write(path, bytes)?;
+        return Ok(());
+    }
+
+    let mut file = match OpenOptions::new().write(true).create_new(true).open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+            return Err(TiaError::Config(format!(
+                "{} already exists; pass --force to overwrite it",
+                path.display()
+            )));
+        }
+        Err(e) => return Err(e.into()),
+    };
+    file.write_all(bytes)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_type_file_name_drops_provider_prefix() {
+        assert_eq!(type_file_name("cloudflare_dns_record"), "dns_records.tf");
+        assert_eq!(type_file_name("cloudflare_page_rule"), "page_rules.tf");
+        assert_eq!(type_file_name("aws_route53_record"), "route53_records.tf");
+    }
+
+    #[test]
+    fn test_format_timestamp_epoch() {
+        assert_eq!(format_timestamp(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+    }
+
+    #[test]
+    fn test_format_timestamp_known_instants() {
+        let at = |secs| UNIX_EPOCH + Duration::from_secs(secs);
+        assert_eq!(format_timestamp(at(1_700_000_000)), "2023-11-14T22:13:20Z");
+        assert_eq!(format_timestamp(at(951_782_400)), "2000-02-29T00:00:00Z");
+    }
+
+    #[test]
+    fn test_empty_notice_filtered_includes_hint() {
+        let mut out = Vec::new();
+        write_empty_notice(&mut out, true).unwrap();
+        let out = String::from_utf8(out).unwrap();
+
+        assert!(out.contains("No resources found matching your filters"));
+        assert!(out.contains("--type"));
+    }
+
+    #[test]
+    fn test_empty_notice_unfiltered_omits_hint() {
+        let mut out = Vec::new();
+        write_empty_notice(&mut out, false).unwrap();
+        let out = String::from_utf8(out).unwrap();
+
+        assert!(out.contains("No resources found matching your filters"));
+        assert!(!out.contains("Hint"));
+    }
+
+    fn sorted_ids(key: SortKey) -> Vec<String> {
+        let resource = |resource_type: &str, id: &str, name: &str| Resource {
+            resource_type: resource_type.to_string(),
+            resource_id: id.to_string(),
+            name: name.to_string(),
+            zone_id: "zone456".to_string(),
+            zone_name: None,
+            account_id: None,
+            metadata: serde_json::json!({}),
+        };
+        let mut resources = vec![
+            resource("cloudflare_page_rule", "c", "example.com/*"),
+            resource("cloudflare_dns_record", "b", "www.example.com"),
+            resource("cloudflare_dns_record", "d", "api.example.com"),
+            resource("cloudflare_dns_record", "a", "www.example.com"),
+        ];
+
+        sort_resources(&mut resources, key);
+
+        resources.into_iter().map(|r| r.resource_id).collect()
+    }
+
+    #[test]
+    fn test_sort_resources_by_type_then_name_by_default() {
+        assert_eq!(sorted_ids(SortKey::default()), vec!["d", "a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_sort_resources_by_name() {
+        assert_eq!(sorted_ids(SortKey::Name), vec!["d", "c", "a", "b"]);
+    }
+
+    #[test]
+    fn test_sort_resources_by_id() {
+        assert_eq!(sorted_ids(SortKey::Id), vec!["a", "b", "c", "d"]);
+    }
+
+    #[test]
+    fn test_summarize_counts_per_type_sorted() {
+        let resource = |resource_type: &str, id: &str| Resource {
+            resource_type: resource_type.to_string(),
+            resource_id: id.to_string(),
+            name: id.to_string(),
+            zone_id: "zone456".to_string(),
+            zone_name: None,
+            account_id: None,
+            metadata: serde_json::json!({}),
+        };
+        let resources = vec![
+            resource("cloudflare_ruleset", "rs1"),
+            resource("cloudflare_dns_record", "rec1"),
+            resource("cloudflare_page_rule", "pr1"),
+            resource("cloudflare_dns_record", "rec2"),
+            resource("cloudflare_dns_record", "rec3"),
+        ];
+
+        let summary = summarize(&resources);
+
+        assert_eq!(
+            summary.into_iter().collect::<Vec<_>>(),
+            vec![
+                ("cloudflare_dns_record".to_string(), 3),
+                ("cloudflare_page_rule".to_string(), 1),
+                ("cloudflare_ruleset".to_string(), 1),
+            ]
+        );
+
+        let mut out = Vec::new();
+        write_summary(&summarize(&resources), &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "cloudflare_dns_record: 3, cloudflare_page_rule: 1, cloudflare_ruleset: 1\n"
+        );
+    }
+
+    #[test]
+    fn test_write_summary_empty_writes_nothing() {
+        let mut out = Vec::new();
+        write_summary(&BTreeMap::new(), &mut out).unwrap();
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn test_write_skipped_warns_per_type_unless_quiet() {
+        let mut skipped = SkippedTypes::default();
+        skipped.record("cloudflare_healthcheck", "Authentication error");
+        let write = |quiet| {
+            let mut out = Vec::new();
+            write_skipped(&skipped, quiet, &mut out).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+
+        assert_eq!(
+            write(false),
+            "warning: skipped cloudflare_healthcheck: Authentication error\n\
+             skipped 1 resource type(s): cloudflare_healthcheck\n"
+        );
+        assert_eq!(
+            write(true),
+            "skipped 1 resource type(s): cloudflare_healthcheck\n"
+        );
+
+        let mut out = Vec::new();
+        write_skipped(&SkippedTypes::default(), false, &mut out).unwrap();
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn test_write_api_usage_mentions_retries_only_when_there_were_some() {
+        let write = |requests, attempts| {
+            let mut out = Vec::new();
+            write_api_usage(&ApiUsage { requests, attempts }, &mut out).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+
+        assert_eq!(write(0, 0), "");
+        assert_eq!(write(12, 12), "API requests: 12\n");
+        assert_eq!(
+            write(12, 15),
+            "API requests: 12 (15 attempts including retries)\n"
+        );
+    }
+
+    #[test]
+    fn test_write_access_report_lists_each_zone() {
+        use crate::providers::Access;
+
+        let report = vec![ZoneAccess {
+            zone: "example.com".to_string(),
+            checks: vec![
+                ("cloudflare_dns_record".to_string(), Access::Ok),
+                (
+                    "cloudflare_page_rule".to_string(),
+                    Access::Forbidden("Authentication error".to_string()),
+                ),
+                (
+                    "cloudflare_ruleset".to_string(),
+                    Access::Failed("network error: timed out".to_string()),
+                ),
+            ],
+        }];
+        let mut out = Vec::new();
+        write_access_report(&report, &mut out).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "example.com\n\
+             \x20 cloudflare_dns_record: ok\n\
+             \x20 cloudflare_page_rule: forbidden\n\
+             \x20 cloudflare_ruleset: error (network error: timed out)\n"
+        );
+    }
+
+    #[test]
+    fn test_write_discovery_failures_warns_per_type() {
+        use crate::providers::ProviderError;
+
+        let failures = vec![DiscoveryFailure {
+            resource_type: "cloudflare_page_rule".to_string(),
+            zone: "example.com".to_string(),
+            error: ProviderError::PermissionDenied("Authentication error".to_string()),
+        }];
+        let mut out = Vec::new();
+        write_discovery_failures(&failures, &mut out).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "warning: could not discover cloudflare_page_rule in example.com: \
+             permission denied: Authentication error\n"
+        );
+    }
+
+    #[test]
+    fn test_write_output_file_refuses_existing_without_force() {
+        let path = std::env::temp_dir().join(format!("tia-output-{}.tf", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        write_output_file(&path, b"first", false).unwrap();
+        let err = write_output_file(&path, b"second", false).unwrap_err();
+        assert!(err.to_string().contains("--force"));
+        assert_eq!(fs::read(&path).unwrap(), b"first");
+
+        write_output_file(&path, b"second", true).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"second");
+
+        fs::remove_file(&path).unwrap();
+    }
+}
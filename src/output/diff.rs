@@ -0,0 +1,273 @@
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+use serde::Serialize;
+
+use crate::delta::ResourceDelta;
+use crate::error::TiaError;
+use crate::resource::Resource;
+use crate::terraform::drift::StateDiff;
+
+/// Writes a human-readable change report, one line per resource, followed by a summary.
+pub fn write_delta<W: Write>(delta: &ResourceDelta, writer: &mut W) -> io::Result<()> {
+    write_delta_changes(delta, writer)?;
+    writeln!(writer, "{}", delta.summary())
+}
+
+/// Like [`write_delta`] without the summary line, for callers that print their own.
+pub fn write_delta_changes<W: Write>(delta: &ResourceDelta, writer: &mut W) -> io::Result<()> {
+    for resource in &delta.added {
+        writeln!(
+            writer,
+            "+ {} {} ({})",
+            resource.resource_type, resource.name, resource.resource_id
+        )?;
+    }
+    for resource in &delta.removed {
+        writeln!(
+            writer,
+            "- {} {} ({})",
+            resource.resource_type, resource.name, resource.resource_id
+        )?;
+    }
+    for change in &delta.changed {
+        writeln!(
+            writer,
+            "~ {} {} ({})",
+            change.current.resource_type, change.current.name, change.current.resource_id
+        )?;
+        if change.previous.name != change.current.name {
+            writeln!(
+                writer,
+                "    name: {} -> {}",
+                change.previous.name, change.current.name
+            )?;
+        }
+        for (key, before, after) in Resource::metadata_diff(&change.previous, &change.current) {
+            writeln!(
+                writer,
+                "    {}: {} -> {}",
+                key,
+                describe(before.as_ref()),
+                describe(after.as_ref())
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes a state comparison grouped by resource type: `+` for import candidates and,
+/// with `show_state_only`, `-` for state entries with no live counterpart. Ends with
+/// the summary line.
+pub fn write_state_diff<W: Write>(
+    diff: &StateDiff,
+    show_state_only: bool,
+    writer: &mut W,
+) -> io::Result<()> {
+    let mut groups: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for resource in &diff.to_import {
+        groups
+            .entry(resource.resource_type.as_str())
+            .or_default()
+            .push(format!("  + {} ({})", resource.name, resource.resource_id));
+    }
+    if show_state_only {
+        for (resource_type, id) in &diff.state_only {
+            groups
+                .entry(resource_type.as_str())
+                .or_default()
+                .push(format!("  - {}", id));
+        }
+    }
+
+    for (resource_type, lines) in groups {
+        writeln!(writer, "{}", resource_type)?;
+        for line in lines {
+            writeln!(writer, "{}", line)?;
+        }
+    }
+
+    writeln!(writer, "{}", diff.summary())
+}
+
+/// The `--format json` shape of a [`StateDiff`].
+#[derive(Debug, Serialize)]
+struct StateDiffJson<'a> {
+    to_import: &'a [Resource],
+    orphaned: Vec<OrphanedEntry<'a>>,
+    in_sync: usize,
+}
+
+/// A state entry with no live counterpart; the state only records its type and id.
+#[derive(Debug, Serialize)]
+struct OrphanedEntry<'a> {
+    resource_type: &'a str,
+    resource_id: &'a str,
+}
+
+/// Writes a state comparison as a JSON object with the `to_import` resources, the
+/// `orphaned` state entries and the `in_sync` count, for CI to act on. Unlike the text
+/// report, orphaned entries are always included.
+pub fn write_state_diff_json<W: Write>(
+    diff: &StateDiff,
+    writer: &mut W,
+    pretty: bool,
+) -> Result<(), TiaError> {
+    let json = StateDiffJson {
+        to_import: &diff.to_import,
+        orphaned: diff
+            .state_only
+            .iter()
+            .map(|(resource_type, resource_id)| OrphanedEntry {
+                resource_type,
+                resource_id,
+            })
+            .collect(),
+        in_sync: diff.matched,
+    };
+    super::json::write_serialized(&json, writer, pretty)
+}
+
+fn describe(value: Option<&serde_json::Value>) -> String {
+    value
+        .map(|v| v.to_string())
+        .unwrap_or_else(|| "(absent)".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(id: &str, name: &str) -> Resource {
+        Resource {
+            resource_type: "cloudflare_dns_record".to_string(),
+            resource_id: id.to_string(),
+            name: name.to_string(),
+            zone_id: "zone123".to_string(),
+            zone_name: None,
+            account_id: None,
+            metadata: serde_json::json!({}),
+        }
+    }
+
+    #[test]
+    fn test_write_delta_lists_each_category() {
+        let previous = vec![record("r1", "gone.example.com"), record("r2", "a")];
+        let current = vec![record("r2", "b"), record("r3", "new.example.com")];
+        let delta = ResourceDelta::between(&previous, &current);
+
+        let mut out = Vec::new();
+        write_delta(&delta, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+
+        assert!(out.contains("+ cloudflare_dns_record new.example.com (r3)"));
+        assert!(out.contains("- cloudflare_dns_record gone.example.com (r1)"));
+        assert!(out.contains("~ cloudflare_dns_record b (r2)"));
+        assert!(out.ends_with("1 added, 1 removed, 1 changed\n"));
+    }
+
+    #[test]
+    fn test_write_delta_changes_omits_the_summary() {
+        let delta = ResourceDelta::between(&[], &[record("r1", "new.example.com")]);
+
+        let mut out = Vec::new();
+        write_delta_changes(&delta, &mut out).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "+ cloudflare_dns_record new.example.com (r1)\n"
+        );
+    }
+
+    #[test]
+    fn test_write_state_diff_json_compact() {
+        let diff = StateDiff {
+            to_import: vec![record("r1", "api.example.com")],
+            state_only: vec![("cloudflare_ruleset".to_string(), "rs_old".to_string())],
+            matched: 2,
+        };
+
+        let mut out = Vec::new();
+        write_state_diff_json(&diff, &mut out, false).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            concat!(
+                r#"{"to_import":[{"resource_type":"cloudflare_dns_record","resource_id":"r1","#,
+                r#""name":"api.example.com","zone_id":"zone123","metadata":{}}],"#,
+                r#""orphaned":[{"resource_type":"cloudflare_ruleset","resource_id":"rs_old"}],"#,
+                r#""in_sync":2}"#,
+                "\n"
+            )
+        );
+    }
+
+    #[test]
+    fn test_write_state_diff_groups_by_type() {
+        let diff = StateDiff {
+            to_import: vec![
+                record("r2", "www.example.com"),
+                Resource {
+                    resource_type: "cloudflare_ruleset".to_string(),
+                    ..record("rs1", "Redirect rules")
+                },
+                record("r1", "api.example.com"),
+            ],
+            state_only: vec![("cloudflare_dns_record".to_string(), "old".to_string())],
+            matched: 3,
+        };
+
+        let mut out = Vec::new();
+        write_state_diff(&diff, true, &mut out).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "cloudflare_dns_record\n\
+             \x20 + www.example.com (r2)\n\
+             \x20 + api.example.com (r1)\n\
+             \x20 - old\n\
+             cloudflare_ruleset\n\
+             \x20 + Redirect rules (rs1)\n\
+             import: 3, state-only: 1, matched: 3\n"
+        );
+    }
+
+    #[test]
+    fn test_write_state_diff_hides_state_only_by_default() {
+        let diff = StateDiff {
+            to_import: vec![],
+            state_only: vec![("cloudflare_dns_record".to_string(), "old".to_string())],
+            matched: 1,
+        };
+
+        let mut out = Vec::new();
+        write_state_diff(&diff, false, &mut out).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "import: 0, state-only: 1, matched: 1\n"
+        );
+    }
+
+    #[test]
+    fn test_write_delta_details_changed_metadata() {
+        let previous = vec![Resource {
+            metadata: serde_json::json!({"proxied": true}),
+            ..record("r1", "api")
+        }];
+        let current = vec![Resource {
+            metadata: serde_json::json!({"proxied": false, "ttl": 300}),
+            ..record("r1", "api")
+        }];
+        let delta = ResourceDelta::between(&previous, &current);
+
+        let mut out = Vec::new();
+        write_delta(&delta, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+
+        assert!(out.contains("~ cloudflare_dns_record api (r1)\n"));
+        assert!(out.contains("    proxied: true -> false\n"));
+        assert!(out.contains("    ttl: (absent) -> 300\n"));
+    }
+}
@@ -0,0 +1,112 @@
+//! Line-ending and BOM control for text output, mainly for Windows editors.
+
+use std::io::{self, Write};
+
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    Crlf,
+}
+
+/// Wraps a writer, translating `\n` to the configured line ending and optionally
+/// prefixing a UTF-8 byte order mark before the first byte written.
+#[derive(Debug)]
+pub struct EncodedWriter<W: Write> {
+    inner: W,
+    line_ending: LineEnding,
+    bom_pending: bool,
+    last_was_cr: bool,
+}
+
+impl<W: Write> EncodedWriter<W> {
+    pub fn new(inner: W, line_ending: LineEnding, bom: bool) -> Self {
+        Self {
+            inner,
+            line_ending,
+            bom_pending: bom,
+            last_was_cr: false,
+        }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> Write for EncodedWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        if self.bom_pending {
+            self.inner.write_all(UTF8_BOM)?;
+            self.bom_pending = false;
+        }
+
+        match self.line_ending {
+            LineEnding::Lf => self.inner.write_all(buf)?,
+            LineEnding::Crlf => {
+                let mut start = 0;
+                for (i, &byte) in buf.iter().enumerate() {
+                    // Existing `\r\n` pairs are passed through rather than doubled.
+                    if byte == b'\n' && !self.last_was_cr {
+                        self.inner.write_all(&buf[start..i])?;
+                        self.inner.write_all(b"\r\n")?;
+                        start = i + 1;
+                    }
+                    self.last_was_cr = byte == b'\r';
+                }
+                self.inner.write_all(&buf[start..])?;
+            }
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(input: &str, line_ending: LineEnding, bom: bool) -> Vec<u8> {
+        let mut writer = EncodedWriter::new(Vec::new(), line_ending, bom);
+        writer.write_all(input.as_bytes()).unwrap();
+        writer.into_inner()
+    }
+
+    #[test]
+    fn test_crlf_mode_terminates_lines_with_crlf() {
+        let out = encode("import {\n  id = \"a\"\n}\n", LineEnding::Crlf, false);
+        assert_eq!(out, b"import {\r\n  id = \"a\"\r\n}\r\n");
+    }
+
+    #[test]
+    fn test_lf_mode_leaves_output_untouched() {
+        let out = encode("import {\n}\n", LineEnding::Lf, false);
+        assert_eq!(out, b"import {\n}\n");
+        assert!(!out.windows(2).any(|w| w == b"\r\n"));
+    }
+
+    #[test]
+    fn test_crlf_mode_does_not_double_existing_crlf() {
+        let mut writer = EncodedWriter::new(Vec::new(), LineEnding::Crlf, false);
+        writer.write_all(b"a\r").unwrap();
+        writer.write_all(b"\nb\n").unwrap();
+        assert_eq!(writer.into_inner(), b"a\r\nb\r\n");
+    }
+
+    #[test]
+    fn test_bom_written_once_at_start() {
+        let mut writer = EncodedWriter::new(Vec::new(), LineEnding::Lf, true);
+        writer.write_all(b"a\n").unwrap();
+        writer.write_all(b"b\n").unwrap();
+        assert_eq!(writer.into_inner(), b"\xEF\xBB\xBFa\nb\n");
+    }
+}
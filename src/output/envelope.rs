@@ -0,0 +1,86 @@
+//! JSON output wrapped in an envelope that records where the data came from.
+
+use std::io::Write;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use super::json::write_serialized;
+use crate::error::TiaError;
+
+#[derive(Debug, Serialize)]
+pub struct Envelope<'a> {
+    pub tia_version: &'a str,
+    pub generated_at: String,
+    pub zone_id: Option<&'a str>,
+    pub resources: &'a [Value],
+}
+
+impl<'a> Envelope<'a> {
+    /// Wraps already-serialized resources (see [`super::json::to_values`]) stamped with
+    /// this build's version.
+    pub fn new(generated_at: String, zone_id: Option<&'a str>, resources: &'a [Value]) -> Self {
+        Self {
+            tia_version: env!("CARGO_PKG_VERSION"),
+            generated_at,
+            zone_id,
+            resources,
+        }
+    }
+}
+
+pub fn write_envelope<W: Write>(
+    envelope: &Envelope<'_>,
+    writer: &mut W,
+    pretty: bool,
+) -> Result<(), TiaError> {
+    write_serialized(envelope, writer, pretty)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::json::to_values;
+    use crate::resource::Resource;
+
+    #[test]
+    fn test_envelope_has_metadata_keys_and_resources() {
+        let resources = to_values(&[Resource {
+            resource_type: "cloudflare_dns_record".to_string(),
+            resource_id: "abc123".to_string(),
+            name: "api.example.com".to_string(),
+            zone_id: "zone456".to_string(),
+            zone_name: None,
+            account_id: None,
+            metadata: serde_json::json!({"type": "A"}),
+        }])
+        .unwrap();
+        let envelope = Envelope::new(
+            "2026-01-01T00:00:00Z".to_string(),
+            Some("zone456"),
+            &resources,
+        );
+
+        let mut out = Vec::new();
+        write_envelope(&envelope, &mut out, true).unwrap();
+        let parsed: Value = serde_json::from_slice(&out).unwrap();
+
+        assert_eq!(parsed["tia_version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(parsed["generated_at"], "2026-01-01T00:00:00Z");
+        assert_eq!(parsed["zone_id"], "zone456");
+        assert_eq!(parsed["resources"].as_array().unwrap().len(), 1);
+        assert_eq!(parsed["resources"][0]["resource_id"], "abc123");
+    }
+
+    #[test]
+    fn test_envelope_without_zone_is_null() {
+        let envelope = Envelope::new("2026-01-01T00:00:00Z".to_string(), None, &[]);
+
+        let mut out = Vec::new();
+        write_envelope(&envelope, &mut out, true).unwrap();
+        let parsed: Value = serde_json::from_slice(&out).unwrap();
+
+        assert!(parsed["zone_id"].is_null());
+        assert_eq!(parsed["resources"], serde_json::json!([]));
+    }
+}
@@ -1,4 +1,398 @@
+use crate::providers;
+use crate::resource::Resource;
+use crate::terraform::naming::{address, assign_identifiers};
+
 /// Generates Terraform import blocks for discovered resources.
 #[allow(dead_code)]
 #[derive(Debug)]
 pub struct HclFormatter;
+
+/// An `import` block for `resource_type.identifier`, or for that address inside
+/// `module` (as in `module.<module>.`) when one is given.
+pub fn import_block(
+    module: Option<&str>,
+    resource_type: &str,
+    identifier: &str,
+    id: &str,
+) -> String {
+    format!(
+        "import {{\n  to = {}\n  id = {}\n}}",
+        address(module, resource_type, identifier),
+        quote(id)
+    )
+}
+
+/// Renders `value` as an HCL string literal, escaping quotes, backslashes, control
+/// characters and template sequences.
+pub fn quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            '$' | '%' if chars.peek() == Some(&'{') => {
+                quoted.push(c);
+                quoted.push(c);
+            }
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// A skeleton `resource` block for `resource` at address `resource_type.identifier`,
+/// populated from its metadata. `None` for types without enough metadata yet.
+///
+/// Fields that aren't discovered are left as `# TODO` comments to fill in before
+/// `terraform plan` will be clean.
+pub fn resource_block(resource: &Resource, identifier: &str) -> Option<String> {
+    let mut body = BlockBody::default();
+    match resource.resource_type.as_str() {
+        "cloudflare_dns_record" => {
+            let metadata = &resource.metadata;
+            let record_type = metadata["type"].as_str().unwrap_or_default();
+
+            body.attribute("zone_id", quote(&resource.zone_id));
+            body.attribute("name", quote(&resource.name));
+            body.attribute("type", quote(record_type));
+            match metadata["content"].as_str() {
+                Some(content) => body.attribute("content", quote(content)),
+                None => body.todo("content wasn't returned by the API"),
+            }
+            match metadata["ttl"].as_u64() {
+                Some(ttl) => body.attribute("ttl", ttl.to_string()),
+                None => body.todo("ttl wasn't returned by the API; 1 means automatic"),
+            }
+            if let Some(proxied) = metadata["proxied"].as_bool() {
+                body.attribute("proxied", proxied.to_string());
+            }
+            if matches!(record_type, "MX" | "SRV" | "URI") {
+                body.todo("priority isn't discovered yet");
+            }
+            body.todo("comment and tags aren't discovered yet");
+        }
+        _ => return None,
+    }
+
+    Some(format!(
+        "resource {} {} {{\n{}}}",
+        quote(&resource.resource_type),
+        quote(identifier),
+        body.render()
+    ))
+}
+
+/// Attribute lines with `=` aligned the way `terraform fmt` does, followed by TODOs.
+#[derive(Debug, Default)]
+struct BlockBody {
+    attributes: Vec<(&'static str, String)>,
+    todos: Vec<&'static str>,
+}
+
+impl BlockBody {
+    fn attribute(&mut self, key: &'static str, value: String) {
+        self.attributes.push((key, value));
+    }
+
+    fn todo(&mut self, note: &'static str) {
+        self.todos.push(note);
+    }
+
+    fn render(&self) -> String {
+        let width = self
+            .attributes
+            .iter()
+            .map(|(key, _)| key.len())
+            .max()
+            .unwrap_or(0);
+        let mut out = String::new();
+        for (key, value) in &self.attributes {
+            out.push_str(&format!("  {:width$} = {}\n", key, value, width = width));
+        }
+        for note in &self.todos {
+            out.push_str(&format!("  # TODO: {}\n", note));
+        }
+        out
+    }
+}
+
+/// Joins import blocks into one document, separated by blank lines.
+pub fn join_blocks(blocks: Vec<String>) -> String {
+    if blocks.is_empty() {
+        return String::new();
+    }
+    format!("{}\n", blocks.join("\n\n"))
+}
+
+/// Renders import blocks for `resources` in the order given, skipping resources that
+/// can't be imported on their own.
+///
+/// Identifiers that collide after sanitizing get `_2`, `_3`, ... suffixes in input
+/// order, so rendering the same list twice produces identical output.
+pub fn render_imports(resources: &[Resource]) -> String {
+    let blocks = assign_identifiers(resources.iter().filter(|r| providers::is_importable(r)))
+        .iter()
+        .map(|named| {
+            import_block(
+                None,
+                &named.resource_type,
+                &named.name,
+                &providers::import_id(named),
+            )
+        })
+        .collect();
+    join_blocks(blocks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(resource_type: &str, id: &str, name: &str) -> Resource {
+        Resource {
+            resource_type: resource_type.to_string(),
+            resource_id: id.to_string(),
+            name: name.to_string(),
+            zone_id: "zone456".to_string(),
+            zone_name: None,
+            account_id: None,
+            metadata: serde_json::json!({}),
+        }
+    }
+
+    fn addresses(document: &str) -> Vec<&str> {
+        document
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix("to = "))
+            .collect()
+    }
+
+    #[test]
+    fn test_render_imports_suffixes_collisions_in_input_order() {
+        let resources = vec![
+            record("cloudflare_dns_record", "rec1", "www"),
+            record("cloudflare_dns_record", "rec2", "www."),
+            record("cloudflare_page_rule", "pr1", "example.com/*"),
+            record("cloudflare_page_rule", "pr2", "example.com/*"),
+            record("cloudflare_page_rule", "pr3", "example.com/*"),
+        ];
+
+        let document = render_imports(&resources);
+
+        assert_eq!(
+            addresses(&document),
+            vec![
+                "cloudflare_dns_record.www",
+                "cloudflare_dns_record.www_",
+                "cloudflare_page_rule.example_com__",
+                "cloudflare_page_rule.example_com___2",
+                "cloudflare_page_rule.example_com___3",
+            ]
+        );
+        assert!(document.contains("id = \"zone456/pr2\""));
+        assert_eq!(render_imports(&resources), document);
+    }
+
+    #[test]
+    fn test_render_imports_same_sanitized_name_collides() {
+        let resources = vec![
+            record("cloudflare_dns_record", "rec1", "api-v1"),
+            record("cloudflare_dns_record", "rec2", "api.v1"),
+        ];
+
+        assert_eq!(
+            addresses(&render_imports(&resources)),
+            vec![
+                "cloudflare_dns_record.api_v1",
+                "cloudflare_dns_record.api_v1_2"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_imports_skips_ruleset_rules() {
+        let resources = vec![
+            record("cloudflare_ruleset", "rs1", "Custom firewall"),
+            record("cloudflare_ruleset_rule", "rule1", "rule1"),
+        ];
+
+        assert_eq!(
+            addresses(&render_imports(&resources)),
+            vec!["cloudflare_ruleset.Custom_firewall"]
+        );
+    }
+
+    /// Checks `document` against the subset of HCL this module emits: labelled blocks,
+    /// `key = value` attributes with string, number, bool or reference values, and `#`
+    /// comments. Returns the number of top-level blocks.
+    fn parse_hcl(document: &str) -> Result<usize, String> {
+        fn is_ident(s: &str) -> bool {
+            !s.is_empty()
+                && !s.starts_with(|c: char| c.is_ascii_digit())
+                && s.chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        }
+        // Consumes one string literal from the start of `s`, returning the rest.
+        fn string_literal(s: &str) -> Option<&str> {
+            let body = s.strip_prefix('"')?;
+            let mut chars = body.char_indices().peekable();
+            while let Some((i, c)) = chars.next() {
+                match c {
+                    '\\' => {
+                        chars.next()?;
+                    }
+                    '"' => return Some(&body[i + 1..]),
+                    // `$${` and `%%{` are escapes; a lone `${` or `%{` starts a template.
+                    '$' | '%' => match chars.peek() {
+                        Some(&(_, next)) if next == c => {
+                            chars.next();
+                        }
+                        Some((_, '{')) => return None,
+                        _ => {}
+                    },
+                    _ => {}
+                }
+            }
+            None
+        }
+        fn is_value(s: &str) -> bool {
+            string_literal(s) == Some("")
+                || s.parse::<i64>().is_ok()
+                || s == "true"
+                || s == "false"
+                || s.split('.').all(is_ident)
+        }
+
+        let (mut depth, mut blocks) = (0usize, 0usize);
+        for (n, raw) in document.lines().enumerate() {
+            let line = raw.trim();
+            let err = || format!("line {}: {:?}", n + 1, raw);
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if line == "}" {
+                depth = depth.checked_sub(1).ok_or_else(err)?;
+            } else if let Some(header) = line.strip_suffix('{') {
+                let header = header.trim_end();
+                let (kind, mut labels) = header.split_once(' ').unwrap_or((header, ""));
+                if !is_ident(kind) {
+                    return Err(err());
+                }
+                while !labels.is_empty() {
+                    labels = string_literal(labels).ok_or_else(err)?.trim_start();
+                }
+                if depth == 0 {
+                    blocks += 1;
+                }
+                depth += 1;
+            } else {
+                let (key, value) = line.split_once('=').ok_or_else(err)?;
+                if depth == 0 || !is_ident(key.trim()) || !is_value(value.trim()) {
+                    return Err(err());
+                }
+            }
+        }
+        if depth != 0 {
+            return Err("unclosed block".to_string());
+        }
+        Ok(blocks)
+    }
+
+    fn dns_record(metadata: serde_json::Value) -> Resource {
+        Resource {
+            metadata,
+            ..record("cloudflare_dns_record", "rec1", "api.example.com")
+        }
+    }
+
+    #[test]
+    fn test_quote_escapes_special_characters() {
+        assert_eq!(quote("plain"), "\"plain\"");
+        assert_eq!(
+            quote("v=spf1 \"a\" \\ ${var} %{if}"),
+            "\"v=spf1 \\\"a\\\" \\\\ $${var} %%{if}\""
+        );
+        assert_eq!(quote("$5 and 100%"), "\"$5 and 100%\"");
+    }
+
+    #[test]
+    fn test_resource_block_dns_record_from_metadata() {
+        let resource = dns_record(serde_json::json!({
+            "type": "A",
+            "content": "192.0.2.10",
+            "ttl": 1,
+            "proxied": true
+        }));
+
+        assert_eq!(
+            resource_block(&resource, "api_example_com").unwrap(),
+            "resource \"cloudflare_dns_record\" \"api_example_com\" {\n\
+             \x20 zone_id = \"zone456\"\n\
+             \x20 name    = \"api.example.com\"\n\
+             \x20 type    = \"A\"\n\
+             \x20 content = \"192.0.2.10\"\n\
+             \x20 ttl     = 1\n\
+             \x20 proxied = true\n\
+             \x20 # TODO: comment and tags aren't discovered yet\n\
+             }"
+        );
+    }
+
+    #[test]
+    fn test_resource_block_missing_fields_become_todos() {
+        let resource = dns_record(serde_json::json!({"type": "MX"}));
+        let block = resource_block(&resource, "mx").unwrap();
+
+        assert!(block.contains("# TODO: content wasn't returned by the API\n"));
+        assert!(block.contains("# TODO: ttl wasn't returned by the API"));
+        assert!(block.contains("# TODO: priority isn't discovered yet\n"));
+        assert!(!block.contains("proxied"));
+    }
+
+    #[test]
+    fn test_resource_block_unsupported_type_is_none() {
+        let rule = record("cloudflare_page_rule", "pr1", "example.com/*");
+        assert!(resource_block(&rule, "example_com__").is_none());
+    }
+
+    #[test]
+    fn test_generated_document_parses() {
+        let resource = dns_record(serde_json::json!({
+            "type": "TXT",
+            "content": "v=spf1 include:_spf.example.com ${not_a_template} \"quoted\" ~all",
+            "ttl": 3600,
+            "proxied": false
+        }));
+        let document = join_blocks(vec![
+            import_block(
+                None,
+                "cloudflare_dns_record",
+                "api_example_com",
+                "zone456/rec1",
+            ),
+            resource_block(&resource, "api_example_com").unwrap(),
+            import_block(None, "cloudflare_dns_record", "mx", "zone456/rec2"),
+            resource_block(&dns_record(serde_json::json!({"type": "MX"})), "mx").unwrap(),
+        ]);
+
+        assert_eq!(parse_hcl(&document), Ok(4));
+    }
+
+    #[test]
+    fn test_parse_hcl_rejects_malformed_documents() {
+        assert!(parse_hcl("resource \"a\" \"b\" {\n  name = \"x\"\n").is_err());
+        assert!(parse_hcl("resource \"a\" \"b\" {\n  name = \"x\n}\n").is_err());
+        assert!(parse_hcl("resource \"a\" \"b\" {\n  name = \"${x}\"\n}\n").is_err());
+    }
+
+    #[test]
+    fn test_render_imports_empty() {
+        assert_eq!(render_imports(&[]), "");
+    }
+}
@@ -0,0 +1,86 @@
+//! SHA-256 integrity lines so reviewers can detect tampering with committed imports.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Formats the `sha256:<hex>` line printed by `--hash-output`.
+pub fn integrity_line(bytes: &[u8]) -> String {
+    format!("sha256:{}", sha256_hex(bytes))
+}
+
+/// Path of the `<file>.sha256` sibling written next to `path`.
+pub fn checksum_path(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".sha256");
+    path.with_file_name(file_name)
+}
+
+/// Writes a `sha256sum`-compatible checksum of `bytes`, already written to `path`, to
+/// the sibling [`checksum_path`], and returns the hex digest.
+pub fn write_checksum(path: &Path, bytes: &[u8]) -> io::Result<String> {
+    let digest = sha256_hex(bytes);
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    fs::write(checksum_path(path), format!("{}  {}\n", digest, file_name))?;
+
+    Ok(digest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sha256_hex_known_vector() {
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_integrity_line_prefix() {
+        let line = integrity_line(b"");
+        assert_eq!(
+            line,
+            "sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[test]
+    fn test_checksum_path_is_sibling() {
+        assert_eq!(
+            checksum_path(Path::new("out/imports.tf")),
+            PathBuf::from("out/imports.tf.sha256")
+        );
+    }
+
+    #[test]
+    fn test_write_checksum_matches_file_bytes() {
+        let dir = std::env::temp_dir().join(format!("tia-integrity-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("imports.tf");
+        let content = b"import {\n  to = cloudflare_dns_record.api\n  id = \"abc\"\n}\n";
+
+        fs::write(&path, content).unwrap();
+        let digest = write_checksum(&path, content).unwrap();
+
+        let written = fs::read(&path).unwrap();
+        assert_eq!(written, content);
+        assert_eq!(digest, sha256_hex(&written));
+
+        let sidecar = fs::read_to_string(checksum_path(&path)).unwrap();
+        assert_eq!(sidecar, format!("{}  imports.tf\n", digest));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
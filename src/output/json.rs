@@ -1,4 +1,279 @@
+use std::io::Write;
+
+use serde_json::{Map, Value};
+
+use crate::error::TiaError;
+use crate::resource::Resource;
+
 /// Serializes discovered resources to JSON format.
 #[allow(dead_code)]
 #[derive(Debug)]
 pub struct JsonFormatter;
+
+/// Top-level `Resource` fields accepted by [`project`].
+pub const PROJECTABLE_FIELDS: &[&str] = &[
+    "resource_type",
+    "resource_id",
+    "name",
+    "zone_id",
+    "zone_name",
+    "account_id",
+    "metadata",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Field {
+    TopLevel(String),
+    Metadata(String),
+}
+
+fn parse_field(raw: &str) -> Result<Field, TiaError> {
+    let raw = raw.trim();
+    if let Some(key) = raw.strip_prefix("metadata.") {
+        if key.is_empty() {
+            return Err(TiaError::Config(
+                "empty metadata key in --fields".to_string(),
+            ));
+        }
+        return Ok(Field::Metadata(key.to_string()));
+    }
+    if PROJECTABLE_FIELDS.contains(&raw) {
+        return Ok(Field::TopLevel(raw.to_string()));
+    }
+    Err(TiaError::Config(format!(
+        "unknown field '{}' in --fields (valid: {}, metadata.<key>)",
+        raw,
+        PROJECTABLE_FIELDS.join(", ")
+    )))
+}
+
+/// Serializes each resource with its `Serialize` impl; the shared basis of every JSON
+/// renderer.
+pub fn to_values(resources: &[Resource]) -> Result<Vec<Value>, TiaError> {
+    resources
+        .iter()
+        .map(|resource| serde_json::to_value(resource).map_err(|e| TiaError::Config(e.to_string())))
+        .collect()
+}
+
+/// Writes `resources` as a JSON array followed by a newline; indented when `pretty`,
+/// otherwise on a single line.
+pub fn write_json<W: Write>(
+    resources: &[Resource],
+    writer: &mut W,
+    pretty: bool,
+) -> Result<(), TiaError> {
+    write_values(&to_values(resources)?, writer, pretty)
+}
+
+/// Like [`write_json`], for resources already serialized (e.g. by [`project`]).
+pub fn write_values<W: Write>(
+    values: &[Value],
+    writer: &mut W,
+    pretty: bool,
+) -> Result<(), TiaError> {
+    write_serialized(values, writer, pretty)
+}
+
+/// Writes `value` as JSON followed by a newline, with [`serde_json::to_writer_pretty`]
+/// when `pretty` and [`serde_json::to_writer`] otherwise.
+pub fn write_serialized<W: Write, T: serde::Serialize + ?Sized>(
+    value: &T,
+    writer: &mut W,
+    pretty: bool,
+) -> Result<(), TiaError> {
+    let written = if pretty {
+        serde_json::to_writer_pretty(&mut *writer, value)
+    } else {
+        serde_json::to_writer(&mut *writer, value)
+    };
+    written.map_err(|e| TiaError::Config(e.to_string()))?;
+    writeln!(writer)?;
+    Ok(())
+}
+
+/// Projects each serialized resource down to the requested fields.
+///
+/// `metadata.<key>` selects a single metadata entry, nested under `metadata` in the
+/// output; keys a resource doesn't have, and unset `zone_name` or `account_id`, are
+/// omitted. All field names are validated
+/// before anything is projected.
+pub fn project(resources: &[Resource], fields: &[String]) -> Result<Vec<Value>, TiaError> {
+    let fields = fields
+        .iter()
+        .map(|f| parse_field(f))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let projected = to_values(resources)?
+        .into_iter()
+        .map(|full| {
+            let mut projected = Map::new();
+
+            for field in &fields {
+                match field {
+                    Field::TopLevel(name) => {
+                        // Optional fields a resource doesn't carry are left out, as
+                        // when it's serialized in full.
+                        if let Some(value) = full.get(name) {
+                            projected.insert(name.clone(), value.clone());
+                        }
+                    }
+                    Field::Metadata(key) => {
+                        let Some(value) = full["metadata"].get(key) else {
+                            continue;
+                        };
+                        let metadata = projected
+                            .entry("metadata")
+                            .or_insert_with(|| Value::Object(Map::new()));
+                        if let Value::Object(map) = metadata {
+                            map.insert(key.clone(), value.clone());
+                        }
+                    }
+                }
+            }
+
+            Value::Object(projected)
+        })
+        .collect();
+    Ok(projected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Resource {
+        Resource {
+            resource_type: "cloudflare_dns_record".to_string(),
+            resource_id: "abc123".to_string(),
+            name: "api.example.com".to_string(),
+            zone_id: "zone456".to_string(),
+            zone_name: None,
+            account_id: None,
+            metadata: serde_json::json!({"type": "A", "proxied": true}),
+        }
+    }
+
+    #[test]
+    fn test_write_json_is_array_of_resources() {
+        let mut out = Vec::new();
+        write_json(&[sample()], &mut out, true).unwrap();
+
+        let parsed: Value = serde_json::from_slice(&out).unwrap();
+        let items = parsed.as_array().unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0]["resource_type"], "cloudflare_dns_record");
+        assert_eq!(items[0]["resource_id"], "abc123");
+        assert!(out.ends_with(b"]\n"));
+    }
+
+    #[test]
+    fn test_write_json_empty_is_empty_array() {
+        let mut out = Vec::new();
+        write_json(&[], &mut out, true).unwrap();
+        assert_eq!(out, b"[]\n");
+    }
+
+    #[test]
+    fn test_write_json_compact_is_a_single_line() {
+        let mut out = Vec::new();
+        write_json(&[sample(), sample()], &mut out, false).unwrap();
+
+        let text = String::from_utf8(out).unwrap();
+        let line = text.strip_suffix('\n').unwrap();
+        assert!(!line.contains('\n'));
+        let parsed: Value = serde_json::from_str(line).unwrap();
+        assert_eq!(parsed.as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_write_json_pretty_spans_several_lines() {
+        let mut out = Vec::new();
+        write_json(&[sample()], &mut out, true).unwrap();
+
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.trim_end().contains('\n'));
+        assert!(text.contains("  \"resource_type\""));
+    }
+
+    #[test]
+    fn test_project_two_fields_omits_others() {
+        let fields = vec!["resource_type".to_string(), "resource_id".to_string()];
+        let projected = project(&[sample()], &fields).unwrap();
+
+        assert_eq!(
+            projected,
+            vec![serde_json::json!({
+                "resource_type": "cloudflare_dns_record",
+                "resource_id": "abc123"
+            })]
+        );
+    }
+
+    #[test]
+    fn test_project_metadata_path() {
+        let fields = vec!["name".to_string(), "metadata.type".to_string()];
+        let projected = project(&[sample()], &fields).unwrap();
+
+        assert_eq!(
+            projected,
+            vec![serde_json::json!({
+                "name": "api.example.com",
+                "metadata": {"type": "A"}
+            })]
+        );
+    }
+
+    #[test]
+    fn test_project_missing_metadata_key_is_omitted() {
+        let fields = vec!["resource_id".to_string(), "metadata.ttl".to_string()];
+        let projected = project(&[sample()], &fields).unwrap();
+
+        assert_eq!(
+            projected,
+            vec![serde_json::json!({"resource_id": "abc123"})]
+        );
+    }
+
+    #[test]
+    fn test_project_zone_name_and_account_id() {
+        let annotated = Resource {
+            zone_name: Some("example.com".to_string()),
+            account_id: Some("acct789".to_string()),
+            ..sample()
+        };
+        let fields = vec![
+            "resource_id".to_string(),
+            "zone_name".to_string(),
+            "account_id".to_string(),
+        ];
+        let projected = project(&[annotated, sample()], &fields).unwrap();
+
+        assert_eq!(
+            projected,
+            vec![
+                serde_json::json!({
+                    "resource_id": "abc123",
+                    "zone_name": "example.com",
+                    "account_id": "acct789"
+                }),
+                serde_json::json!({"resource_id": "abc123"}),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_project_unknown_field_errors() {
+        let fields = vec!["resource_type".to_string(), "owner".to_string()];
+        let err = project(&[sample()], &fields).unwrap_err();
+
+        assert!(matches!(err, TiaError::Config(_)));
+        assert!(err.to_string().contains("unknown field 'owner'"));
+    }
+
+    #[test]
+    fn test_project_unknown_field_errors_even_without_resources() {
+        let fields = vec!["bogus".to_string()];
+        assert!(project(&[], &fields).is_err());
+    }
+}
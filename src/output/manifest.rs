@@ -0,0 +1,145 @@
+//! A JSON record of what `generate` wrote, kept next to the import file for audits.
+
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::TiaError;
+use crate::providers::is_importable;
+use crate::resource::Resource;
+
+#[derive(Debug, Serialize)]
+pub struct Manifest<'a> {
+    pub generated_at: String,
+    pub zone: Option<&'a str>,
+    pub count: usize,
+    pub resources: Vec<&'a Resource>,
+}
+
+impl<'a> Manifest<'a> {
+    /// Records the resources that got an import block; the others are left out so
+    /// `count` always matches the generated file.
+    pub fn new(generated_at: String, zone: Option<&'a str>, resources: &'a [Resource]) -> Self {
+        let resources: Vec<&Resource> = resources.iter().filter(|r| is_importable(r)).collect();
+        Self {
+            generated_at,
+            zone,
+            count: resources.len(),
+            resources,
+        }
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, TiaError> {
+        let mut out = Vec::new();
+        write_manifest(self, &mut out)?;
+        Ok(out)
+    }
+}
+
+/// A manifest read back from disk, e.g. to compare a new run against with `--since`.
+#[derive(Debug, Deserialize)]
+pub struct SavedManifest {
+    pub generated_at: String,
+    #[serde(default)]
+    pub zone: Option<String>,
+    pub resources: Vec<Resource>,
+}
+
+impl SavedManifest {
+    pub fn load(path: &Path) -> Result<Self, TiaError> {
+        Self::from_slice(&fs::read(path)?).map_err(|e| match e {
+            TiaError::Config(message) => {
+                TiaError::Config(format!("{}: {}", path.display(), message))
+            }
+            other => other,
+        })
+    }
+
+    pub fn from_slice(bytes: &[u8]) -> Result<Self, TiaError> {
+        serde_json::from_slice(bytes)
+            .map_err(|e| TiaError::Config(format!("invalid manifest: {}", e)))
+    }
+}
+
+pub fn write_manifest<W: Write>(manifest: &Manifest<'_>, writer: &mut W) -> Result<(), TiaError> {
+    serde_json::to_writer_pretty(&mut *writer, manifest)
+        .map_err(|e| TiaError::Config(e.to_string()))?;
+    writeln!(writer)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    fn resource(resource_type: &str, id: &str) -> Resource {
+        Resource {
+            resource_type: resource_type.to_string(),
+            resource_id: id.to_string(),
+            name: format!("{}.example.com", id),
+            zone_id: "zone456".to_string(),
+            zone_name: None,
+            account_id: None,
+            metadata: serde_json::json!({}),
+        }
+    }
+
+    #[test]
+    fn test_saved_manifests_delta_reports_added_and_removed() {
+        use crate::delta::ResourceDelta;
+
+        let round_trip = |resources: &[Resource]| {
+            let manifest = Manifest::new("2026-01-01T00:00:00Z".to_string(), None, resources);
+            SavedManifest::from_slice(&manifest.to_bytes().unwrap()).unwrap()
+        };
+        let last_week = round_trip(&[
+            resource("cloudflare_dns_record", "api"),
+            resource("cloudflare_dns_record", "old"),
+        ]);
+        let this_week = round_trip(&[
+            resource("cloudflare_dns_record", "api"),
+            resource("cloudflare_dns_record", "new"),
+        ]);
+
+        let delta = ResourceDelta::between(&last_week.resources, &this_week.resources);
+
+        assert_eq!(delta.added, vec![resource("cloudflare_dns_record", "new")]);
+        assert_eq!(
+            delta.removed,
+            vec![resource("cloudflare_dns_record", "old")]
+        );
+        assert!(delta.changed.is_empty());
+        assert_eq!(last_week.generated_at, "2026-01-01T00:00:00Z");
+    }
+
+    #[test]
+    fn test_saved_manifest_rejects_invalid_json() {
+        let err = SavedManifest::from_slice(b"{\"count\": 1}").unwrap_err();
+        assert!(err.to_string().contains("invalid manifest"));
+    }
+
+    #[test]
+    fn test_manifest_lists_importable_resources() {
+        let resources = vec![
+            resource("cloudflare_dns_record", "api"),
+            resource("cloudflare_ruleset_rule", "rule"),
+            resource("cloudflare_dns_record", "www"),
+        ];
+        let manifest = Manifest::new(
+            "2026-01-01T00:00:00Z".to_string(),
+            Some("zone456"),
+            &resources,
+        );
+
+        let parsed: Value = serde_json::from_slice(&manifest.to_bytes().unwrap()).unwrap();
+
+        assert_eq!(parsed["generated_at"], "2026-01-01T00:00:00Z");
+        assert_eq!(parsed["zone"], "zone456");
+        assert_eq!(parsed["count"], 2);
+        let back: Vec<Resource> = serde_json::from_value(parsed["resources"].clone()).unwrap();
+        assert_eq!(back, vec![resources[0].clone(), resources[2].clone()]);
+    }
+}
@@ -0,0 +1,167 @@
+//! Stable stand-ins for ids, so discovery output can be shared without exposing them.
+
+use serde_json::Value;
+
+use crate::output::integrity::sha256_hex;
+use crate::resource::Resource;
+
+/// Hex digits of the SHA-256 digest kept for each redacted id.
+const REDACTED_ID_LEN: usize = 8;
+
+/// The first [`REDACTED_ID_LEN`] hex digits of `id`'s SHA-256, so the same id always
+/// redacts to the same value.
+pub fn redact_id(id: &str) -> String {
+    let mut hash = sha256_hex(id.as_bytes());
+    hash.truncate(REDACTED_ID_LEN);
+    hash
+}
+
+/// Metadata keys holding an id without an `_id` suffix: load balancer pools.
+const ID_KEYS: &[&str] = &["fallback_pool"];
+
+/// Metadata keys holding a list of ids without an `_ids` suffix.
+const ID_LIST_KEYS: &[&str] = &["default_pools"];
+
+/// Clones `resources` with `resource_id`, `zone_id`, `account_id` and every id in
+/// `metadata` replaced by [`redact_id`]. Metadata ids are `*_id` strings and `*_ids`
+/// string lists, e.g. a ruleset's `rule_ids`, plus load balancer pools, at any depth.
+/// Empty ids stay empty.
+pub fn redact_ids(resources: &[Resource]) -> Vec<Resource> {
+    resources
+        .iter()
+        .map(|resource| {
+            let mut redacted = resource.clone();
+            redacted.resource_id = redact(&resource.resource_id);
+            redacted.zone_id = redact(&resource.zone_id);
+            redacted.account_id = resource.account_id.as_deref().map(redact);
+            redact_metadata(&mut redacted.metadata);
+            redacted
+        })
+        .collect()
+}
+
+fn redact(id: &str) -> String {
+    if id.is_empty() {
+        String::new()
+    } else {
+        redact_id(id)
+    }
+}
+
+fn redact_metadata(value: &mut Value) {
+    match value {
+        Value::Object(fields) => {
+            for (key, value) in fields.iter_mut() {
+                if key.ends_with("_id") || ID_KEYS.contains(&key.as_str()) {
+                    if let Value::String(id) = value {
+                        *id = redact(id);
+                        continue;
+                    }
+                } else if (key.ends_with("_ids") || ID_LIST_KEYS.contains(&key.as_str()))
+                    && let Value::Array(ids) = value
+                {
+                    for id in ids.iter_mut() {
+                        if let Value::String(id) = id {
+                            *id = redact(id);
+                        }
+                    }
+                    continue;
+                }
+                redact_metadata(value);
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_metadata),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_id_is_stable_and_hides_the_original() {
+        let id = "023e105f4ecef8ad9ca31a8372d0c353";
+
+        assert_eq!(redact_id(id), redact_id(id));
+        assert_eq!(redact_id(id).len(), 8);
+        assert_ne!(redact_id(id), id);
+        assert!(!id.contains(&redact_id(id)));
+        assert_ne!(redact_id(id), redact_id("rec_www"));
+    }
+
+    #[test]
+    fn test_redact_ids_covers_zone_and_metadata_ids() {
+        let resources = vec![Resource {
+            resource_type: "cloudflare_list".to_string(),
+            resource_id: "list1".to_string(),
+            name: "blocked_ips".to_string(),
+            zone_id: "zone456".to_string(),
+            zone_name: Some("example.com".to_string()),
+            account_id: Some("acc789".to_string()),
+            metadata: serde_json::json!({ "account_id": "acc789", "kind": "ip" }),
+        }];
+
+        let redacted = redact_ids(&resources);
+
+        assert_eq!(redacted[0].resource_id, redact_id("list1"));
+        assert_eq!(redacted[0].zone_id, redact_id("zone456"));
+        assert_eq!(redacted[0].name, "blocked_ips");
+        assert_eq!(redacted[0].zone_name.as_deref(), Some("example.com"));
+        assert_eq!(redacted[0].account_id, Some(redact_id("acc789")));
+        assert_eq!(
+            redacted[0].metadata,
+            serde_json::json!({ "account_id": redact_id("acc789"), "kind": "ip" })
+        );
+    }
+
+    #[test]
+    fn test_redact_ids_covers_pool_and_rule_id_lists() {
+        let resource = |resource_type: &str, metadata: Value| Resource {
+            resource_type: resource_type.to_string(),
+            resource_id: "res1".to_string(),
+            name: "name".to_string(),
+            zone_id: "zone456".to_string(),
+            zone_name: None,
+            account_id: None,
+            metadata,
+        };
+        let resources = vec![
+            resource(
+                "cloudflare_load_balancer",
+                serde_json::json!({
+                    "default_pools": ["pool_a", "pool_b"],
+                    "fallback_pool": "pool_c",
+                    "proxied": true
+                }),
+            ),
+            resource(
+                "cloudflare_ruleset",
+                serde_json::json!({
+                    "phase": "http_request_firewall_custom",
+                    "rule_ids": ["rule_1", "rule_2"],
+                    "rules": [{ "ruleset_id": "rs_nested", "action": "block" }]
+                }),
+            ),
+        ];
+
+        let redacted = redact_ids(&resources);
+
+        assert_eq!(
+            redacted[0].metadata,
+            serde_json::json!({
+                "default_pools": [redact_id("pool_a"), redact_id("pool_b")],
+                "fallback_pool": redact_id("pool_c"),
+                "proxied": true
+            })
+        );
+        assert_eq!(
+            redacted[1].metadata,
+            serde_json::json!({
+                "phase": "http_request_firewall_custom",
+                "rule_ids": [redact_id("rule_1"), redact_id("rule_2")],
+                "rules": [{ "ruleset_id": redact_id("rs_nested"), "action": "block" }]
+            })
+        );
+    }
+}
@@ -0,0 +1,75 @@
+//! `terraform import` shell commands, for Terraform versions without import blocks.
+
+use crate::terraform::naming::address;
+
+/// A `terraform import` command for `resource_type.identifier`, inside `module` when
+/// one is given, with import id `id`, both quoted for a POSIX shell.
+pub fn import_command(
+    module: Option<&str>,
+    resource_type: &str,
+    identifier: &str,
+    id: &str,
+) -> String {
+    format!(
+        "terraform import {} {}",
+        quote(&address(module, resource_type, identifier)),
+        quote(id)
+    )
+}
+
+/// Quotes `value` as a single shell word. Values made only of characters no shell
+/// treats specially are left bare; anything else is single-quoted, with embedded
+/// single quotes written as `'\''`.
+pub fn quote(value: &str) -> String {
+    let is_safe = |c: char| c.is_ascii_alphanumeric() || "_-./:@%+=,".contains(c);
+    if !value.is_empty() && value.chars().all(is_safe) {
+        return value.to_string();
+    }
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_import_command_plain_id_is_unquoted() {
+        assert_eq!(
+            import_command(
+                None,
+                "cloudflare_dns_record",
+                "www",
+                "023e105f4ecef8ad9ca31a8372d0c353/rec1"
+            ),
+            "terraform import cloudflare_dns_record.www 023e105f4ecef8ad9ca31a8372d0c353/rec1"
+        );
+    }
+
+    #[test]
+    fn test_import_command_quotes_shell_metacharacters() {
+        assert_eq!(
+            import_command(
+                None,
+                "aws_route53_record",
+                "wildcard",
+                "Z1_*.example.com_CNAME"
+            ),
+            "terraform import aws_route53_record.wildcard 'Z1_*.example.com_CNAME'"
+        );
+        assert_eq!(quote("it's $HOME"), r"'it'\''s $HOME'");
+        assert_eq!(quote(""), "''");
+    }
+
+    #[test]
+    fn test_import_command_inside_module() {
+        assert_eq!(
+            import_command(
+                Some("cloudflare"),
+                "cloudflare_dns_record",
+                "www",
+                "zone/rec1"
+            ),
+            "terraform import module.cloudflare.cloudflare_dns_record.www zone/rec1"
+        );
+    }
+}
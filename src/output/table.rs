@@ -1,4 +1,126 @@
-/// Renders discovered resources in a formatted table.
-#[allow(dead_code)]
-#[derive(Debug)]
-pub struct TableFormatter;
+//! An aligned table of discovered resources, for reading in a terminal.
+
+use std::io::{self, Write};
+
+use crate::resource::Resource;
+
+/// Ids longer than this are cut short, so a single long rule id doesn't push every
+/// other row off the screen.
+pub const MAX_ID_WIDTH: usize = 40;
+
+const HEADERS: [&str; 4] = ["TYPE", "NAME", "ID", "ZONE"];
+
+/// Writes `resources` as a table with a `TYPE`, `NAME`, `ID` and `ZONE` column, each
+/// as wide as its longest value. An empty list prints a single line saying so.
+pub fn write_table<W: Write>(resources: &[Resource], writer: &mut W) -> io::Result<()> {
+    if resources.is_empty() {
+        return writeln!(writer, "No resources found.");
+    }
+
+    let rows: Vec<[String; 4]> = resources
+        .iter()
+        .map(|r| {
+            [
+                r.resource_type.clone(),
+                r.name.clone(),
+                truncate(&r.resource_id, MAX_ID_WIDTH),
+                r.zone_id.clone(),
+            ]
+        })
+        .collect();
+
+    let mut widths = HEADERS.map(|h| h.chars().count());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    write_row(writer, &HEADERS, &widths)?;
+    for row in &rows {
+        write_row(writer, row, &widths)?;
+    }
+    Ok(())
+}
+
+/// Pads every cell but the last, so lines carry no trailing whitespace.
+fn write_row<W: Write, S: AsRef<str>>(
+    writer: &mut W,
+    cells: &[S; 4],
+    widths: &[usize; 4],
+) -> io::Result<()> {
+    let (last, rest) = cells.split_last().expect("a row has four cells");
+    for (cell, width) in rest.iter().zip(widths) {
+        write!(writer, "{:<width$}  ", cell.as_ref(), width = *width)?;
+    }
+    writeln!(writer, "{}", last.as_ref())
+}
+
+/// Shortens `value` to `max` characters, marking the cut with a trailing `…`.
+fn truncate(value: &str, max: usize) -> String {
+    if value.chars().count() <= max {
+        return value.to_string();
+    }
+    let mut short: String = value.chars().take(max - 1).collect();
+    short.push('…');
+    short
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resource(resource_type: &str, name: &str, id: &str) -> Resource {
+        Resource {
+            resource_type: resource_type.to_string(),
+            resource_id: id.to_string(),
+            name: name.to_string(),
+            zone_id: "zone123".to_string(),
+            zone_name: None,
+            account_id: None,
+            metadata: serde_json::json!({}),
+        }
+    }
+
+    fn render(resources: &[Resource]) -> String {
+        let mut out = Vec::new();
+        write_table(resources, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_write_table_aligns_columns() {
+        let resources = vec![
+            resource("cloudflare_dns_record", "www.example.com", "rec1"),
+            resource("cloudflare_page_rule", "example.com/*", "pagerule_42"),
+        ];
+
+        assert_eq!(
+            render(&resources),
+            "\
+TYPE                   NAME             ID           ZONE
+cloudflare_dns_record  www.example.com  rec1         zone123
+cloudflare_page_rule   example.com/*    pagerule_42  zone123
+"
+        );
+    }
+
+    #[test]
+    fn test_write_table_truncates_long_ids() {
+        let id = "a".repeat(MAX_ID_WIDTH + 10);
+        let output = render(&[resource("cloudflare_ruleset_rule", "block", &id)]);
+
+        let row = output.lines().nth(1).unwrap();
+        let expected_id = format!("{}…", "a".repeat(MAX_ID_WIDTH - 1));
+        assert!(
+            row.contains(&format!("  {}  zone123", expected_id)),
+            "{}",
+            row
+        );
+    }
+
+    #[test]
+    fn test_write_table_empty() {
+        assert_eq!(render(&[]), "No resources found.\n");
+    }
+}
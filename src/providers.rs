@@ -1,9 +1,16 @@
+pub mod aws;
 pub mod cloudflare;
 
+use std::fmt;
+
 use async_trait::async_trait;
 use thiserror::Error;
 
+use crate::error::TiaError;
+use crate::output::hcl::join_blocks;
+use crate::output::shell::import_command;
 use crate::resource::{DiscoverConfig, Resource};
+use crate::terraform::naming::assign_identifiers;
 
 #[derive(Debug, Error)]
 pub enum ProviderError {
@@ -16,6 +23,18 @@ pub enum ProviderError {
     Auth(String),
     #[error("cloudflare error: {0}")]
     Cloudflare(String),
+    #[error("aws error: {0}")]
+    Aws(String),
+    #[error("rate limited, retry after {retry_after}s")]
+    RateLimited { retry_after: u64 },
+    /// A named object, e.g. a zone, that doesn't exist or isn't visible to the token.
+    #[error("{kind} not found: '{name}'")]
+    NotFound { kind: String, name: String },
+    /// The token is valid but lacks a permission the request needs.
+    #[error("permission denied: {0}")]
+    PermissionDenied(String),
+    #[error("unknown resource type '{name}' (valid types: {valid})")]
+    UnknownResourceType { name: String, valid: String },
 }
 
 #[async_trait]
@@ -23,13 +42,298 @@ pub enum ProviderError {
 pub trait Provider: Send + Sync {
     fn name(&self) -> &str;
     async fn discover(&self, config: &DiscoverConfig) -> Result<Vec<Resource>, ProviderError>;
-    fn generate_import(&self, resource: &Resource) -> String;
+
+    /// Like [`discover`](Self::discover), but a resource type that fails is recorded in
+    /// the report and the others are still collected. Failures before any type is
+    /// fetched, e.g. an invalid token, are still returned as errors.
+    async fn discover_report(
+        &self,
+        config: &DiscoverConfig,
+    ) -> Result<DiscoveryReport, ProviderError> {
+        let resources = self.discover(config).await?;
+        Ok(DiscoveryReport {
+            resources,
+            ..Default::default()
+        })
+    }
+
+    /// The import block for `resource`, addressed inside `module` when one is given.
+    fn generate_import(&self, resource: &Resource, module: Option<&str>) -> String;
     fn resource_types(&self) -> Vec<&str>;
+
+    /// Rejects any of `types` this provider doesn't discover, listing the valid ones.
+    fn check_resource_types(&self, types: &[String]) -> Result<(), ProviderError> {
+        let valid = self.resource_types();
+        match types.iter().find(|t| !valid.contains(&t.as_str())) {
+            Some(unknown) => Err(ProviderError::UnknownResourceType {
+                name: unknown.clone(),
+                valid: valid.join(", "),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// The id Terraform expects when importing `resource`; providers override this
+    /// for types whose import id is composite.
+    fn import_id(&self, resource: &Resource) -> String {
+        resource.resource_id.clone()
+    }
+
+    /// A skeleton `resource` block for `resource` addressed by `identifier`, or `None`
+    /// when the provider can't describe its type yet.
+    fn generate_config(&self, _resource: &Resource, _identifier: &str) -> Option<String> {
+        None
+    }
+
+    /// Renders import blocks for all `resources` as one document.
+    ///
+    /// Resources that can't be imported on their own are left out. The rest are ordered
+    /// by type, name and id, and each name is sanitized into a Terraform identifier that
+    /// is unique within its type before being handed to
+    /// [`generate_import`](Self::generate_import), which leaves it unchanged. The output
+    /// is stable for a given set of resources regardless of input order.
+    fn generate_imports(&self, resources: &[Resource]) -> String {
+        self.generate_document(resources, false, None)
+    }
+
+    /// Like [`generate_imports`](Self::generate_imports), but with `with_config` each
+    /// import block is followed by the resource's
+    /// [`generate_config`](Self::generate_config) block, where there is one, and every
+    /// import targets `module` when one is given.
+    fn generate_document(
+        &self,
+        resources: &[Resource],
+        with_config: bool,
+        module: Option<&str>,
+    ) -> String {
+        let ordered = importable_in_order(resources);
+
+        let mut blocks = Vec::new();
+        // NOTE: Config blocks need the original name, so keep it alongside the identifier.
+        for (resource, named) in ordered
+            .iter()
+            .zip(assign_identifiers(ordered.iter().copied()))
+        {
+            blocks.push(self.generate_import(&named, module));
+            if with_config && let Some(config) = self.generate_config(resource, &named.name) {
+                blocks.push(config);
+            }
+        }
+        join_blocks(blocks)
+    }
+
+    /// Renders one `terraform import` shell command per line, for Terraform versions
+    /// without import blocks. Resources are ordered and named as in
+    /// [`generate_imports`](Self::generate_imports), inside `module` when one is given.
+    fn generate_import_commands(&self, resources: &[Resource], module: Option<&str>) -> String {
+        assign_identifiers(importable_in_order(resources))
+            .iter()
+            .map(|named| {
+                format!(
+                    "{}\n",
+                    import_command(
+                        module,
+                        &named.resource_type,
+                        &named.name,
+                        &self.import_id(named),
+                    )
+                )
+            })
+            .collect()
+    }
+}
+
+/// The importable `resources`, ordered by type, name and id.
+fn importable_in_order(resources: &[Resource]) -> Vec<&Resource> {
+    let mut ordered: Vec<&Resource> = resources.iter().filter(|r| is_importable(r)).collect();
+    ordered.sort_by(|a, b| {
+        (&a.resource_type, &a.name, &a.resource_id).cmp(&(
+            &b.resource_type,
+            &b.name,
+            &b.resource_id,
+        ))
+    });
+    ordered
+}
+
+/// Looks up the provider called `provider` and runs discovery with `config`.
+///
+/// ```no_run
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), tia::TiaError> {
+/// let config = tia::DiscoverConfig {
+///     token: std::env::var("CLOUDFLARE_API_TOKEN").ok(),
+///     zones: vec!["example.com".to_string()],
+///     ..Default::default()
+/// };
+///
+/// for resource in tia::discover("cloudflare", config).await? {
+///     println!("{} {}", resource.resource_type, resource.resource_id);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub async fn discover(provider: &str, config: DiscoverConfig) -> Result<Vec<Resource>, TiaError> {
+    let provider = get_provider(provider, config.token.clone())?;
+    Ok(provider.discover(&config).await?)
+}
+
+/// The import id for `resource` under the conventions of the provider owning its type.
+pub fn import_id(resource: &Resource) -> String {
+    if resource.resource_type.starts_with("cloudflare_") {
+        return cloudflare::import_id(resource);
+    }
+    resource.resource_id.clone()
+}
+
+/// Whether `resource` has its own import block, as opposed to being nested in a parent
+/// resource.
+pub fn is_importable(resource: &Resource) -> bool {
+    if resource.resource_type.starts_with("cloudflare_") {
+        return cloudflare::is_importable(resource);
+    }
+    true
+}
+
+/// Resource types skipped during discovery, e.g. for missing token permissions, each
+/// with the reason the API gave.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SkippedTypes {
+    skipped: Vec<(String, String)>,
+}
+
+impl SkippedTypes {
+    pub fn record(&mut self, resource_type: &str, reason: &str) {
+        self.skipped
+            .push((resource_type.to_string(), reason.to_string()));
+    }
+
+    /// Each skipped type with its reason, in the order they were recorded.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.skipped
+            .iter()
+            .map(|(resource_type, reason)| (resource_type.as_str(), reason.as_str()))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.skipped.is_empty()
+    }
+
+    pub fn count(&self) -> usize {
+        self.skipped.len()
+    }
+
+    pub fn summary(&self) -> Option<String> {
+        if self.skipped.is_empty() {
+            return None;
+        }
+        let types: Vec<&str> = self
+            .iter()
+            .map(|(resource_type, _)| resource_type)
+            .collect();
+        Some(format!(
+            "skipped {} resource type(s): {}",
+            self.skipped.len(),
+            types.join(", ")
+        ))
+    }
+}
+
+/// A resource type that failed in one zone during
+/// [`discover_report`](Provider::discover_report). A zone that couldn't be looked up at
+/// all is reported with `resource_type` set to `zone`.
+#[derive(Debug)]
+pub struct DiscoveryFailure {
+    pub resource_type: String,
+    pub zone: String,
+    pub error: ProviderError,
+}
+
+impl fmt::Display for DiscoveryFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} in {}: {}", self.resource_type, self.zone, self.error)
+    }
+}
+
+/// What a discovery run found, alongside the resource types that failed without
+/// stopping the rest.
+#[derive(Debug, Default)]
+pub struct DiscoveryReport {
+    pub resources: Vec<Resource>,
+    /// Sorted by resource type, then zone.
+    pub errors: Vec<DiscoveryFailure>,
+    /// How many resource types were fetched without error, counted once per zone.
+    /// Types skipped for a missing entitlement don't count.
+    pub succeeded: usize,
+    /// API requests the run made; zero for providers that don't count them.
+    pub api_usage: ApiUsage,
+    /// Ids of the zones discovered, whether or not they had any resources; empty for
+    /// providers that don't report them.
+    pub zones: Vec<String>,
+    /// Resource types left out rather than failed, e.g. for a 403 on a paid feature;
+    /// recorded once however many zones skipped them.
+    pub skipped: SkippedTypes,
+}
+
+/// API requests made during a run, to compare against the provider's rate limits.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ApiUsage {
+    /// Distinct requests, however often each was retried.
+    pub requests: u64,
+    /// Every request sent, retries included.
+    pub attempts: u64,
+}
+
+impl DiscoveryReport {
+    /// Whether something failed and nothing succeeded.
+    pub fn all_failed(&self) -> bool {
+        !self.errors.is_empty() && self.succeeded == 0
+    }
+
+    /// Zones with at least one failure, whether the zone itself or one of its types,
+    /// sorted and listed once each.
+    pub fn failed_zones(&self) -> Vec<&str> {
+        let mut zones: Vec<&str> = self.errors.iter().map(|e| e.zone.as_str()).collect();
+        zones.sort_unstable();
+        zones.dedup();
+        zones
+    }
+}
+
+/// Whether a token may read one resource type, as found by probing its endpoint.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Access {
+    Ok,
+    /// The API refused the token, with its reason.
+    Forbidden(String),
+    /// The probe failed some other way, e.g. a network error, so access is unknown.
+    Failed(String),
+}
+
+impl fmt::Display for Access {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Access::Ok => write!(f, "ok"),
+            Access::Forbidden(_) => write!(f, "forbidden"),
+            Access::Failed(reason) => write!(f, "error ({})", reason),
+        }
+    }
+}
+
+/// The [`Access`] a token has to each resource type in one zone.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ZoneAccess {
+    pub zone: String,
+    pub checks: Vec<(String, Access)>,
 }
 
 pub fn get_provider(name: &str, token: Option<String>) -> Result<Box<dyn Provider>, ProviderError> {
     match name {
         "cloudflare" => Ok(Box::new(cloudflare::CloudflareProvider::new(token))),
+        // AWS takes its credentials from the standard environment variables, not a token.
+        "aws" => Ok(Box::new(aws::AwsProvider::new(
+            aws::AwsCredentials::from_env(),
+        ))),
         other => Err(ProviderError::UnknownProvider(other.to_string())),
     }
 }
@@ -44,6 +348,13 @@ mod tests {
         assert_eq!(provider.name(), "cloudflare");
     }
 
+    #[test]
+    fn test_get_provider_aws() {
+        let provider = get_provider("aws", None).unwrap();
+        assert_eq!(provider.name(), "aws");
+        assert_eq!(provider.resource_types(), vec!["aws_route53_record"]);
+    }
+
     #[test]
     fn test_get_provider_unknown() {
         let result = get_provider("unknown", None);
@@ -54,6 +365,28 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_skipped_types_summary_lists_each_type() {
+        let mut skipped = SkippedTypes::default();
+        skipped.record("cloudflare_page_rule", "forbidden");
+        skipped.record("cloudflare_ruleset", "forbidden");
+
+        assert_eq!(skipped.count(), 2);
+        assert_eq!(
+            skipped.summary().unwrap(),
+            "skipped 2 resource type(s): cloudflare_page_rule, cloudflare_ruleset"
+        );
+        assert_eq!(
+            skipped.iter().next(),
+            Some(("cloudflare_page_rule", "forbidden"))
+        );
+    }
+
+    #[test]
+    fn test_skipped_types_no_summary_when_nothing_skipped() {
+        assert!(SkippedTypes::default().summary().is_none());
+    }
+
     #[test]
     fn test_cloudflare_resource_types() {
         let provider = cloudflare::CloudflareProvider::new(None);
@@ -61,10 +394,73 @@ mod tests {
         assert!(types.contains(&"cloudflare_dns_record"));
         assert!(types.contains(&"cloudflare_page_rule"));
         assert!(types.contains(&"cloudflare_ruleset"));
+        assert!(types.contains(&"cloudflare_load_balancer"));
+        assert!(types.contains(&"cloudflare_load_balancer_pool"));
         assert!(!types.contains(&"cloudflare_firewall_rule"));
         assert!(!types.contains(&"cloudflare_waf_rule"));
     }
 
+    #[test]
+    fn test_check_resource_types_accepts_known_types() {
+        let provider = cloudflare::CloudflareProvider::new(None);
+        let types = vec![
+            "cloudflare_dns_record".to_string(),
+            "cloudflare_ruleset".to_string(),
+        ];
+
+        assert!(provider.check_resource_types(&types).is_ok());
+    }
+
+    #[test]
+    fn test_check_resource_types_lists_valid_types() {
+        let provider = cloudflare::CloudflareProvider::new(None);
+        let err = provider
+            .check_resource_types(&["cloudflare_record".to_string()])
+            .unwrap_err();
+
+        let message = err.to_string();
+        assert!(message.contains("unknown resource type 'cloudflare_record'"));
+        assert!(message.contains("cloudflare_dns_record, cloudflare_page_rule"));
+    }
+
+    #[tokio::test]
+    async fn test_discover_unknown_resource_type_fails_before_auth() {
+        let config = DiscoverConfig {
+            token: Some("test_token".to_string()),
+            api_base_url: Some("http://127.0.0.1:9".to_string()),
+            resource_types: Some(vec!["cloudflare_worker".to_string()]),
+            ..Default::default()
+        };
+
+        let err = discover("cloudflare", config).await.unwrap_err();
+
+        assert!(matches!(
+            err,
+            TiaError::Provider(ProviderError::UnknownResourceType { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_discover_unknown_provider_is_tia_error() {
+        let err = discover("gcp", DiscoverConfig::default())
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            TiaError::Provider(ProviderError::UnknownProvider(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_discover_without_token_is_auth_error() {
+        let err = discover("cloudflare", DiscoverConfig::default())
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, TiaError::Provider(ProviderError::Auth(_))));
+    }
+
     #[tokio::test]
     async fn test_cloudflare_discover_no_token_error() {
         let provider = cloudflare::CloudflareProvider::new(None);
@@ -87,11 +483,131 @@ mod tests {
             resource_id: "abc123".to_string(),
             name: "example".to_string(),
             zone_id: "zone456".to_string(),
+            zone_name: None,
+            account_id: None,
             metadata: serde_json::json!({}),
         };
-        let import = provider.generate_import(&resource);
+        let import = provider.generate_import(&resource, None);
         assert!(import.contains("import {"));
         assert!(import.contains("cloudflare_record"));
         assert!(import.contains("abc123"));
     }
+
+    #[test]
+    fn test_cloudflare_generate_import_sanitizes_name() {
+        let provider = cloudflare::CloudflareProvider::new(None);
+        let import = provider.generate_import(&dns_record("rec1", "api.example.com"), None);
+
+        assert!(import.contains("to = cloudflare_dns_record.api_example_com\n"));
+        assert!(import.contains("id = \"zone456/rec1\""));
+    }
+
+    #[test]
+    fn test_cloudflare_generate_import_wildcard_page_rule() {
+        let provider = cloudflare::CloudflareProvider::new(None);
+        let mut rule = dns_record("pr1", "*example.com/images/*");
+        rule.resource_type = "cloudflare_page_rule".to_string();
+
+        let import = provider.generate_import(&rule, None);
+
+        assert!(import.contains("to = cloudflare_page_rule._example_com_images__\n"));
+    }
+
+    #[test]
+    fn test_cloudflare_generate_import_workers_route_pattern() {
+        let provider = cloudflare::CloudflareProvider::new(None);
+        let mut route = dns_record("route1", "example.com/api/*");
+        route.resource_type = "cloudflare_workers_route".to_string();
+
+        let import = provider.generate_import(&route, None);
+
+        assert!(import.contains("to = cloudflare_workers_route.example_com_api__\n"));
+        assert!(import.contains("id = \"zone456/route1\""));
+    }
+
+    #[test]
+    fn test_cloudflare_generate_import_load_balancer_pool_is_account_qualified() {
+        let provider = cloudflare::CloudflareProvider::new(None);
+        let mut pool = dns_record("pool1", "primary-dc-1");
+        pool.resource_type = "cloudflare_load_balancer_pool".to_string();
+        pool.metadata = serde_json::json!({"account_id": "acct789"});
+
+        let import = provider.generate_import(&pool, None);
+
+        assert!(import.contains("to = cloudflare_load_balancer_pool.primary_dc_1\n"));
+        assert!(import.contains("id = \"acct789/pool1\""));
+    }
+
+    fn dns_record(id: &str, name: &str) -> Resource {
+        Resource {
+            resource_type: "cloudflare_dns_record".to_string(),
+            resource_id: id.to_string(),
+            name: name.to_string(),
+            zone_id: "zone456".to_string(),
+            zone_name: None,
+            account_id: None,
+            metadata: serde_json::json!({}),
+        }
+    }
+
+    #[test]
+    fn test_generate_imports_colliding_names_are_unique_and_consistent() {
+        let provider = cloudflare::CloudflareProvider::new(None);
+        let resources = vec![
+            dns_record("rec3", "www."),
+            dns_record("rec1", "www"),
+            dns_record("rec2", "www-"),
+            dns_record("rec4", "api.example.com"),
+        ];
+
+        let document = provider.generate_imports(&resources);
+
+        let addresses: Vec<&str> = document
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix("to = "))
+            .collect();
+        assert_eq!(
+            addresses,
+            vec![
+                "cloudflare_dns_record.api_example_com",
+                "cloudflare_dns_record.www",
+                "cloudflare_dns_record.www_",
+                "cloudflare_dns_record.www__2",
+            ]
+        );
+        assert_eq!(document.matches("import {").count(), resources.len());
+        assert!(document.contains("id = \"zone456/rec4\""));
+
+        let mut reversed = resources.clone();
+        reversed.reverse();
+        assert_eq!(provider.generate_imports(&reversed), document);
+    }
+
+    #[test]
+    fn test_generate_imports_empty_name_falls_back_to_id() {
+        let provider = cloudflare::CloudflareProvider::new(None);
+        let document = provider.generate_imports(&[dns_record("0abc", "")]);
+        assert!(document.contains("to = cloudflare_dns_record._0abc"));
+    }
+
+    #[test]
+    fn test_generate_import_commands_one_line_per_resource() {
+        let provider = cloudflare::CloudflareProvider::new(None);
+        let resources = vec![
+            dns_record("rec2", "www"),
+            dns_record("rec1", "api.example.com"),
+        ];
+
+        assert_eq!(
+            provider.generate_import_commands(&resources, None),
+            "terraform import cloudflare_dns_record.api_example_com zone456/rec1\n\
+             terraform import cloudflare_dns_record.www zone456/rec2\n"
+        );
+    }
+
+    #[test]
+    fn test_generate_imports_empty_input() {
+        let provider = cloudflare::CloudflareProvider::new(None);
+        assert_eq!(provider.generate_imports(&[]), "");
+    }
 }
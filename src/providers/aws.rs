@@ -0,0 +1,564 @@
+//! Route53 discovery for the `aws` provider.
+//!
+//! Requests are signed with AWS Signature Version 4. Responses are read with a small
+//! scan for named elements rather than a full XML parser, which is enough for the flat
+//! list shapes Route53 returns.
+
+use std::fmt;
+use std::time::SystemTime;
+
+use async_trait::async_trait;
+use reqwest::Url;
+use sha2::{Digest, Sha256};
+
+use super::cloudflare::DEFAULT_REQUEST_TIMEOUT;
+use super::{Provider, ProviderError};
+use crate::output::format_timestamp;
+use crate::output::integrity::sha256_hex;
+use crate::resource::{DiscoverConfig, Resource};
+use crate::terraform;
+
+pub const ROUTE53_API_BASE: &str = "https://route53.amazonaws.com/2013-04-01";
+
+/// Route53 is a global service, signed as if it lived in `us-east-1`.
+const ROUTE53_REGION: &str = "us-east-1";
+const ROUTE53_SERVICE: &str = "route53";
+
+const RECORD_TYPE: &str = "aws_route53_record";
+
+/// Static credentials, as read from the standard `AWS_*` environment variables.
+#[derive(Clone)]
+pub struct AwsCredentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    /// Set for temporary credentials, e.g. from an assumed role.
+    pub session_token: Option<String>,
+}
+
+impl AwsCredentials {
+    /// Reads `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY` and `AWS_SESSION_TOKEN`;
+    /// `None` unless both keys are set.
+    pub fn from_env() -> Option<Self> {
+        let var = |name| std::env::var(name).ok().filter(|v: &String| !v.is_empty());
+        Some(Self {
+            access_key_id: var("AWS_ACCESS_KEY_ID")?,
+            secret_access_key: var("AWS_SECRET_ACCESS_KEY")?,
+            session_token: var("AWS_SESSION_TOKEN"),
+        })
+    }
+}
+
+impl fmt::Debug for AwsCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AwsCredentials")
+            .field("access_key_id", &self.access_key_id)
+            .field("secret_access_key", &"[REDACTED]")
+            .field(
+                "session_token",
+                &self.session_token.as_ref().map(|_| "[REDACTED]"),
+            )
+            .finish()
+    }
+}
+
+#[derive(Debug)]
+pub struct AwsProvider {
+    credentials: Option<AwsCredentials>,
+}
+
+impl AwsProvider {
+    pub fn new(credentials: Option<AwsCredentials>) -> Self {
+        Self { credentials }
+    }
+
+    /// Builds a client for `config` without sending anything; fails when there are no
+    /// credentials or hosted zones to work with.
+    fn client(&self, config: &DiscoverConfig) -> Result<Route53Client, ProviderError> {
+        let credentials = self.credentials.clone().ok_or_else(|| {
+            ProviderError::Auth(
+                "No AWS credentials provided. Set AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY"
+                    .to_string(),
+            )
+        })?;
+
+        if config.zones.is_empty() && !config.all_zones {
+            return Err(ProviderError::Aws(
+                "No hosted zone provided. Use --zone or --all-zones".to_string(),
+            ));
+        }
+
+        let mut builder = reqwest::Client::builder()
+            .timeout(config.read_timeout.unwrap_or(DEFAULT_REQUEST_TIMEOUT));
+        if let Some(connect_timeout) = config.connect_timeout {
+            builder = builder.connect_timeout(connect_timeout);
+        }
+        let http = builder
+            .build()
+            .map_err(|e| ProviderError::Aws(format!("failed to build HTTP client: {}", e)))?;
+
+        Ok(Route53Client {
+            http,
+            base_url: config
+                .api_base_url
+                .clone()
+                .unwrap_or_else(|| ROUTE53_API_BASE.to_string()),
+            credentials,
+        })
+    }
+}
+
+#[async_trait]
+impl Provider for AwsProvider {
+    fn name(&self) -> &str {
+        "aws"
+    }
+
+    async fn discover(&self, config: &DiscoverConfig) -> Result<Vec<Resource>, ProviderError> {
+        let client = self.client(config)?;
+        if config.dry_run || !config.wants(RECORD_TYPE) {
+            return Ok(Vec::new());
+        }
+
+        let zones = client.hosted_zones().await?;
+        let selected: Vec<&HostedZone> = if config.all_zones {
+            zones.iter().collect()
+        } else {
+            config
+                .zones
+                .iter()
+                .map(|wanted| {
+                    zones
+                        .iter()
+                        .find(|zone| zone.matches(wanted))
+                        .ok_or_else(|| ProviderError::NotFound {
+                            kind: "hosted zone".to_string(),
+                            name: wanted.clone(),
+                        })
+                })
+                .collect::<Result<_, _>>()?
+        };
+
+        let mut resources = Vec::new();
+        for zone in selected {
+            resources.extend(client.record_sets(zone).await?);
+        }
+        Ok(resources)
+    }
+
+    fn generate_import(&self, resource: &Resource, module: Option<&str>) -> String {
+        terraform::import_block(resource, module)
+    }
+
+    fn resource_types(&self) -> Vec<&str> {
+        vec![RECORD_TYPE]
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct HostedZone {
+    /// Without the `/hostedzone/` prefix the API puts on it.
+    id: String,
+    /// Without the trailing dot.
+    name: String,
+}
+
+impl HostedZone {
+    fn matches(&self, wanted: &str) -> bool {
+        self.id == wanted || self.name == wanted.trim_end_matches('.')
+    }
+}
+
+struct Route53Client {
+    http: reqwest::Client,
+    base_url: String,
+    credentials: AwsCredentials,
+}
+
+impl Route53Client {
+    /// Every hosted zone visible to the credentials, following `NextMarker`.
+    async fn hosted_zones(&self) -> Result<Vec<HostedZone>, ProviderError> {
+        let mut zones = Vec::new();
+        let mut marker: Option<String> = None;
+
+        loop {
+            let mut url = format!("{}/hostedzone", self.base_url);
+            if let Some(marker) = &marker {
+                url.push_str(&format!("?marker={}", urlencoding::encode(marker)));
+            }
+            let body = self.get(&url).await?;
+
+            for zone in elements(&body, "HostedZone") {
+                let id = element(zone, "Id").unwrap_or_default();
+                zones.push(HostedZone {
+                    id: id.trim_start_matches("/hostedzone/").to_string(),
+                    name: dns_name(element(zone, "Name").unwrap_or_default()),
+                });
+            }
+
+            marker = element(&body, "NextMarker").map(unescape);
+            if !is_truncated(&body) || marker.is_none() {
+                break;
+            }
+        }
+
+        Ok(zones)
+    }
+
+    /// The record sets in `zone` as `aws_route53_record` resources, following the
+    /// `NextRecord*` position the API returns while the listing is truncated.
+    async fn record_sets(&self, zone: &HostedZone) -> Result<Vec<Resource>, ProviderError> {
+        let mut resources = Vec::new();
+        let mut query = String::new();
+
+        loop {
+            let url = format!("{}/hostedzone/{}/rrset{}", self.base_url, zone.id, query);
+            let body = self.get(&url).await?;
+
+            for record_set in elements(&body, "ResourceRecordSet") {
+                resources.push(record_set_resource(zone, record_set));
+            }
+
+            if !is_truncated(&body) {
+                break;
+            }
+            let Some(next_name) = element(&body, "NextRecordName") else {
+                break;
+            };
+            query = format!("?name={}", urlencoding::encode(&unescape(next_name)));
+            for (param, tag) in [
+                ("type", "NextRecordType"),
+                ("identifier", "NextRecordIdentifier"),
+            ] {
+                if let Some(value) = element(&body, tag) {
+                    query.push_str(&format!(
+                        "&{}={}",
+                        param,
+                        urlencoding::encode(&unescape(value))
+                    ));
+                }
+            }
+        }
+
+        Ok(resources)
+    }
+
+    async fn get(&self, url: &str) -> Result<String, ProviderError> {
+        let parsed = Url::parse(url)
+            .map_err(|e| ProviderError::Aws(format!("invalid URL {}: {}", url, e)))?;
+        let amz_date = amz_date(SystemTime::now());
+
+        let mut request = self
+            .http
+            .get(parsed.clone())
+            .header("x-amz-date", &amz_date)
+            .header(
+                "authorization",
+                authorization(&self.credentials, &parsed, &amz_date),
+            );
+        if let Some(session_token) = &self.credentials.session_token {
+            request = request.header("x-amz-security-token", session_token);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| ProviderError::Aws(format!("request failed: {}", e)))?;
+        let status = response.status().as_u16();
+        let body = response
+            .text()
+            .await
+            .map_err(|e| ProviderError::Aws(format!("failed to read response: {}", e)))?;
+
+        if (200..300).contains(&status) {
+            return Ok(body);
+        }
+
+        let message = element(&body, "Message")
+            .map(unescape)
+            .unwrap_or_else(|| format!("HTTP {}", status));
+        Err(match (status, element(&body, "Code")) {
+            (403, _) => ProviderError::PermissionDenied(message),
+            (_, Some("Throttling")) | (429, _) => ProviderError::RateLimited { retry_after: 1 },
+            _ => ProviderError::Aws(format!("{} (HTTP {})", message, status)),
+        })
+    }
+}
+
+fn record_set_resource(zone: &HostedZone, record_set: &str) -> Resource {
+    let name = dns_name(element(record_set, "Name").unwrap_or_default());
+    let record_type = element(record_set, "Type").unwrap_or_default().to_string();
+    let set_identifier = element(record_set, "SetIdentifier").map(unescape);
+
+    // Terraform's import id for a record set: ZONEID_NAME_TYPE[_SET-IDENTIFIER].
+    let mut resource_id = format!("{}_{}_{}", zone.id, name, record_type);
+    if let Some(set_identifier) = &set_identifier {
+        resource_id.push('_');
+        resource_id.push_str(set_identifier);
+    }
+
+    let mut metadata = serde_json::json!({
+        "type": record_type,
+        "records": elements(record_set, "Value").into_iter().map(unescape).collect::<Vec<_>>(),
+    });
+    if let Some(ttl) = element(record_set, "TTL").and_then(|ttl| ttl.parse::<u64>().ok()) {
+        metadata["ttl"] = ttl.into();
+    }
+    if let Some(alias) = element(record_set, "AliasTarget") {
+        metadata["alias_target"] = dns_name(element(alias, "DNSName").unwrap_or_default()).into();
+    }
+    if let Some(set_identifier) = set_identifier {
+        metadata["set_identifier"] = set_identifier.into();
+    }
+
+    Resource {
+        resource_type: RECORD_TYPE.to_string(),
+        resource_id,
+        name,
+        zone_id: zone.id.clone(),
+        zone_name: None,
+        account_id: None,
+        metadata,
+    }
+}
+
+fn is_truncated(body: &str) -> bool {
+    element(body, "IsTruncated") == Some("true")
+}
+
+/// The contents of each `<tag>` element in `xml`, in document order. Elements with the
+/// same tag must not nest, which holds for the Route53 list responses.
+fn elements<'a>(xml: &'a str, tag: &str) -> Vec<&'a str> {
+    let (open, close) = (format!("<{}>", tag), format!("</{}>", tag));
+    let mut found = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find(&open) {
+        let after_open = &rest[start + open.len()..];
+        let Some(end) = after_open.find(&close) else {
+            break;
+        };
+        found.push(&after_open[..end]);
+        rest = &after_open[end + close.len()..];
+    }
+    found
+}
+
+fn element<'a>(xml: &'a str, tag: &str) -> Option<&'a str> {
+    elements(xml, tag).into_iter().next()
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// A DNS name as Route53 returns it, made readable: the trailing dot is dropped and
+/// octal escapes such as `\052` for `*` are decoded.
+fn dns_name(raw: &str) -> String {
+    let raw = unescape(raw);
+    let mut name = String::with_capacity(raw.len());
+    let mut chars = raw.trim_end_matches('.').chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            let digits: String = chars.clone().take(3).collect();
+            if digits.len() == 3
+                && let Ok(code) = u8::from_str_radix(&digits, 8)
+            {
+                name.push(char::from(code));
+                chars.nth(2);
+                continue;
+            }
+        }
+        name.push(c);
+    }
+    name
+}
+
+/// `time` in the `YYYYMMDDTHHMMSSZ` form SigV4 signs.
+fn amz_date(time: SystemTime) -> String {
+    format_timestamp(time).replace(['-', ':'], "")
+}
+
+/// The SigV4 `Authorization` header for a body-less GET of `url` at `amz_date`.
+fn authorization(credentials: &AwsCredentials, url: &Url, amz_date: &str) -> String {
+    let host = match url.port() {
+        Some(port) => format!("{}:{}", url.host_str().unwrap_or_default(), port),
+        None => url.host_str().unwrap_or_default().to_string(),
+    };
+
+    let mut query: Vec<(String, String)> = url
+        .query_pairs()
+        .map(|(k, v)| {
+            (
+                urlencoding::encode(&k).into_owned(),
+                urlencoding::encode(&v).into_owned(),
+            )
+        })
+        .collect();
+    query.sort();
+    let canonical_query = query
+        .iter()
+        .map(|(k, v)| format!("{}={}", k, v))
+        .collect::<Vec<_>>()
+        .join("&");
+
+    let mut headers = vec![("host", host), ("x-amz-date", amz_date.to_string())];
+    if let Some(session_token) = &credentials.session_token {
+        headers.push(("x-amz-security-token", session_token.clone()));
+    }
+    let canonical_headers: String = headers
+        .iter()
+        .map(|(name, value)| format!("{}:{}\n", name, value.trim()))
+        .collect();
+    let signed_headers = headers
+        .iter()
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>()
+        .join(";");
+
+    let canonical_request = format!(
+        "GET\n{}\n{}\n{}\n{}\n{}",
+        url.path(),
+        canonical_query,
+        canonical_headers,
+        signed_headers,
+        sha256_hex(b"")
+    );
+
+    let date = &amz_date[..8];
+    let scope = format!(
+        "{}/{}/{}/aws4_request",
+        date, ROUTE53_REGION, ROUTE53_SERVICE
+    );
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        sha256_hex(canonical_request.as_bytes())
+    );
+    let signing_key = signing_key(&credentials.secret_access_key, date);
+    let signature: String = hmac_sha256(&signing_key, string_to_sign.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+
+    format!(
+        "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+        credentials.access_key_id, scope, signed_headers, signature
+    )
+}
+
+fn signing_key(secret_access_key: &str, date: &str) -> [u8; 32] {
+    let key = hmac_sha256(
+        format!("AWS4{}", secret_access_key).as_bytes(),
+        date.as_bytes(),
+    );
+    let key = hmac_sha256(&key, ROUTE53_REGION.as_bytes());
+    let key = hmac_sha256(&key, ROUTE53_SERVICE.as_bytes());
+    hmac_sha256(&key, b"aws4_request")
+}
+
+/// HMAC-SHA256 per RFC 2104.
+fn hmac_sha256(key: &[u8], data: &[u8]) -> [u8; 32] {
+    let mut block = [0u8; 64];
+    if key.len() > block.len() {
+        block[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+
+    let mut inner = Sha256::new();
+    inner.update(block.map(|b| b ^ 0x36));
+    inner.update(data);
+    let mut outer = Sha256::new();
+    outer.update(block.map(|b| b ^ 0x5c));
+    outer.update(inner.finalize());
+    outer.finalize().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    #[test]
+    fn test_hmac_sha256_rfc4231_case_2() {
+        assert_eq!(
+            hex(&hmac_sha256(b"Jefe", b"what do ya want for nothing?")),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_amz_date_format() {
+        let at = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_700_000_000);
+        assert_eq!(amz_date(at), "20231114T221320Z");
+    }
+
+    #[test]
+    fn test_authorization_scope_and_signed_headers() {
+        let credentials = AwsCredentials {
+            access_key_id: "AKIDEXAMPLE".to_string(),
+            secret_access_key: "secret".to_string(),
+            session_token: Some("session".to_string()),
+        };
+        let url = Url::parse("https://route53.amazonaws.com/2013-04-01/hostedzone").unwrap();
+        let header = authorization(&credentials, &url, "20231114T221320Z");
+
+        assert!(header.starts_with(
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20231114/us-east-1/route53/aws4_request, \
+             SignedHeaders=host;x-amz-date;x-amz-security-token, Signature="
+        ));
+        assert_eq!(header.rsplit('=').next().unwrap().len(), 64);
+    }
+
+    #[test]
+    fn test_dns_name_drops_trailing_dot_and_decodes_escapes() {
+        assert_eq!(dns_name("\\052.example.com."), "*.example.com");
+        assert_eq!(dns_name("a&amp;b.example.com."), "a&b.example.com");
+    }
+
+    #[test]
+    fn test_record_set_resource_import_id_includes_set_identifier() {
+        let zone = HostedZone {
+            id: "Z1".to_string(),
+            name: "example.com".to_string(),
+        };
+        let resource = record_set_resource(
+            &zone,
+            "<Name>api.example.com.</Name><Type>A</Type><SetIdentifier>eu</SetIdentifier>\
+             <TTL>300</TTL><ResourceRecords><ResourceRecord><Value>192.0.2.1</Value>\
+             </ResourceRecord></ResourceRecords>",
+        );
+
+        assert_eq!(resource.resource_id, "Z1_api.example.com_A_eu");
+        assert_eq!(resource.name, "api.example.com");
+        assert_eq!(
+            resource.metadata,
+            serde_json::json!({
+                "type": "A",
+                "records": ["192.0.2.1"],
+                "ttl": 300,
+                "set_identifier": "eu"
+            })
+        );
+    }
+
+    #[test]
+    fn test_credentials_debug_redacts_secrets() {
+        let credentials = AwsCredentials {
+            access_key_id: "AKIDEXAMPLE".to_string(),
+            secret_access_key: "very-secret".to_string(),
+            session_token: Some("session-secret".to_string()),
+        };
+        let debug = format!("{:?}", credentials);
+        assert!(debug.contains("AKIDEXAMPLE"));
+        assert!(!debug.contains("very-secret"));
+        assert!(!debug.contains("session-secret"));
+    }
+}
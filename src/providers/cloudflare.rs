@@ -2,13 +2,410 @@ mod client;
 mod error;
 mod types;
 
-pub use client::CloudflareClient;
+pub use client::{
+    API_BASE_URL_ENV, CLOUDFLARE_API_BASE, CloudflareClient, DEFAULT_MAX_CONCURRENCY,
+    DEFAULT_MAX_PAGES, DEFAULT_REQUEST_TIMEOUT, DEFAULT_RETRY_ON, PageProgress, RequestOptions,
+    default_base_url,
+};
 pub use error::CloudflareError;
-pub use types::{PagedResponse, PaginationStrategy, ZoneInfo, is_zone_id};
+pub use types::{
+    Account, PageToken, PagedResponse, PaginationStrategy, RULESET_RULE_TYPE, ZoneInfo, is_zone_id,
+};
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 
 use async_trait::async_trait;
+use futures::future;
+use futures::stream::{self, Stream, StreamExt, TryStreamExt};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+use super::{
+    Access, ApiUsage, DiscoverConfig, DiscoveryFailure, DiscoveryReport, Provider, ProviderError,
+    Resource, ZoneAccess,
+};
+use crate::cache::ZoneCache;
+use crate::output::hcl::resource_block;
+use crate::terraform;
+
+/// Import ids follow the Cloudflare Terraform provider's formats, which are
+/// zone-qualified for zone-scoped types.
+pub fn import_id(resource: &Resource) -> String {
+    match resource.resource_type.as_str() {
+        "cloudflare_dns_record"
+        | "cloudflare_page_rule"
+        | "cloudflare_rate_limit"
+        | "cloudflare_workers_route"
+        | "cloudflare_custom_ssl"
+        | "cloudflare_zone_setting"
+        | "cloudflare_healthcheck"
+        | "cloudflare_spectrum_application"
+        | "cloudflare_custom_hostname"
+        | "cloudflare_load_balancer" => {
+            format!("{}/{}", resource.zone_id, resource.resource_id)
+        }
+        "cloudflare_ruleset" => format!("zones/{}/{}", resource.zone_id, resource.resource_id),
+        "cloudflare_load_balancer_pool"
+        | "cloudflare_list"
+        | "cloudflare_workers_script"
+        | "cloudflare_pages_project"
+        | "cloudflare_r2_bucket"
+        | "cloudflare_turnstile_widget" => match resource.metadata["account_id"].as_str() {
+            Some(account_id) => format!("{}/{}", account_id, resource.resource_id),
+            None => resource.resource_id.clone(),
+        },
+        _ => resource.resource_id.clone(),
+    }
+}
+
+/// Whether `resource` can be imported on its own; ruleset rules only exist inside
+/// their parent `cloudflare_ruleset`.
+pub fn is_importable(resource: &Resource) -> bool {
+    resource.resource_type != RULESET_RULE_TYPE
+}
+
+/// How many resource families are fetched at once when `DiscoverConfig::concurrency`
+/// isn't set.
+pub const DEFAULT_DISCOVERY_CONCURRENCY: usize = 4;
+
+/// An independent group of API calls made during discovery.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Family {
+    DnsRecords,
+    PageRules,
+    Rulesets,
+    RateLimits,
+    WorkerRoutes,
+    CustomCertificates,
+    OriginCertificates,
+    ZoneSettings,
+    LoadBalancers,
+    HealthChecks,
+    SpectrumApps,
+    CustomHostnames,
+    LoadBalancerPools,
+    Lists,
+    WorkerScripts,
+    PagesProjects,
+    R2Buckets,
+    TurnstileWidgets,
+}
+
+impl Family {
+    const ALL: [Family; 18] = [
+        Family::DnsRecords,
+        Family::PageRules,
+        Family::Rulesets,
+        Family::RateLimits,
+        Family::WorkerRoutes,
+        Family::CustomCertificates,
+        Family::OriginCertificates,
+        Family::ZoneSettings,
+        Family::LoadBalancers,
+        Family::HealthChecks,
+        Family::SpectrumApps,
+        Family::CustomHostnames,
+        Family::LoadBalancerPools,
+        Family::Lists,
+        Family::WorkerScripts,
+        Family::PagesProjects,
+        Family::R2Buckets,
+        Family::TurnstileWidgets,
+    ];
+
+    /// Every resource type the family produces, the primary one first.
+    fn resource_types(self) -> &'static [&'static str] {
+        match self {
+            Family::DnsRecords => &["cloudflare_dns_record"],
+            Family::PageRules => &["cloudflare_page_rule"],
+            Family::Rulesets => &["cloudflare_ruleset", RULESET_RULE_TYPE],
+            Family::RateLimits => &["cloudflare_rate_limit"],
+            Family::WorkerRoutes => &["cloudflare_workers_route"],
+            Family::CustomCertificates => &["cloudflare_custom_ssl"],
+            Family::OriginCertificates => &["cloudflare_origin_ca_certificate"],
+            Family::ZoneSettings => &["cloudflare_zone_setting"],
+            Family::LoadBalancers => &["cloudflare_load_balancer"],
+            Family::HealthChecks => &["cloudflare_healthcheck"],
+            Family::SpectrumApps => &["cloudflare_spectrum_application"],
+            Family::CustomHostnames => &["cloudflare_custom_hostname"],
+            Family::LoadBalancerPools => &["cloudflare_load_balancer_pool"],
+            Family::Lists => &["cloudflare_list"],
+            Family::WorkerScripts => &["cloudflare_workers_script"],
+            Family::PagesProjects => &["cloudflare_pages_project"],
+            Family::R2Buckets => &["cloudflare_r2_bucket"],
+            Family::TurnstileWidgets => &["cloudflare_turnstile_widget"],
+        }
+    }
+
+    fn resource_type(self) -> &'static str {
+        self.resource_types()[0]
+    }
+
+    /// Load balancing, health checks, Spectrum, custom hostnames and custom certificates
+    /// are paid add-ons and legacy rate limiting is closed to zones that never used it, while origin
+    /// certificates, zone settings, Workers routes and scripts, lists, Pages projects, R2
+    /// buckets and Turnstile widgets need extra token permissions; a 403 for any of them
+    /// skips the type rather than failing discovery.
+    fn is_optional(self) -> bool {
+        matches!(
+            self,
+            Family::RateLimits
+                | Family::WorkerRoutes
+                | Family::CustomCertificates
+                | Family::OriginCertificates
+                | Family::ZoneSettings
+                | Family::LoadBalancers
+                | Family::HealthChecks
+                | Family::SpectrumApps
+                | Family::CustomHostnames
+                | Family::LoadBalancerPools
+                | Family::Lists
+                | Family::WorkerScripts
+                | Family::PagesProjects
+                | Family::R2Buckets
+                | Family::TurnstileWidgets
+        )
+    }
+
+    /// Whether the family lists account-scoped resources, which are the same for
+    /// every zone in the account.
+    fn is_account_level(self) -> bool {
+        matches!(
+            self,
+            Family::LoadBalancerPools
+                | Family::Lists
+                | Family::WorkerScripts
+                | Family::PagesProjects
+                | Family::R2Buckets
+                | Family::TurnstileWidgets
+        )
+    }
 
-use super::{DiscoverConfig, Provider, ProviderError, Resource};
+    /// The endpoints the family requests, relative to the API base; `{ruleset_id}`
+    /// stands for each ruleset the list returns.
+    fn paths(self, zone_id: &str, account_id: &str) -> Vec<String> {
+        match self {
+            Family::DnsRecords => vec![format!("/zones/{}/dns_records", zone_id)],
+            Family::PageRules => vec![format!("/zones/{}/pagerules", zone_id)],
+            Family::Rulesets => vec![
+                format!("/zones/{}/rulesets", zone_id),
+                format!("/zones/{}/rulesets/{{ruleset_id}}", zone_id),
+            ],
+            Family::RateLimits => vec![format!("/zones/{}/rate_limits", zone_id)],
+            Family::WorkerRoutes => vec![format!("/zones/{}/workers/routes", zone_id)],
+            Family::CustomCertificates => {
+                vec![format!("/zones/{}/custom_certificates", zone_id)]
+            }
+            Family::OriginCertificates => vec![format!("/certificates?zone_id={}", zone_id)],
+            Family::ZoneSettings => vec![format!("/zones/{}/settings", zone_id)],
+            Family::LoadBalancers => vec![format!("/zones/{}/load_balancers", zone_id)],
+            Family::HealthChecks => vec![format!("/zones/{}/healthchecks", zone_id)],
+            Family::SpectrumApps => vec![format!("/zones/{}/spectrum/apps", zone_id)],
+            Family::CustomHostnames => vec![format!("/zones/{}/custom_hostnames", zone_id)],
+            Family::LoadBalancerPools => {
+                vec![format!("/accounts/{}/load_balancers/pools", account_id)]
+            }
+            Family::Lists => vec![format!("/accounts/{}/rules/lists", account_id)],
+            Family::WorkerScripts => vec![format!("/accounts/{}/workers/scripts", account_id)],
+            Family::PagesProjects => vec![format!("/accounts/{}/pages/projects", account_id)],
+            Family::R2Buckets => vec![format!("/accounts/{}/r2/buckets", account_id)],
+            Family::TurnstileWidgets => {
+                vec![format!("/accounts/{}/challenges/widgets", account_id)]
+            }
+        }
+    }
+
+    async fn discover(
+        self,
+        client: &CloudflareClient,
+        zone_info: &ZoneInfo,
+        include_disabled: bool,
+        include_defaults: bool,
+    ) -> Result<Vec<Resource>, CloudflareError> {
+        let zone_id = zone_info.zone_id.as_str();
+        let resources = match self {
+            Family::DnsRecords => client
+                .discover_dns_records(zone_id)
+                .await?
+                .into_iter()
+                .map(|record| record.into_resource(zone_id))
+                .collect(),
+            Family::PageRules => client
+                .discover_page_rules(zone_id)
+                .await?
+                .into_iter()
+                .map(|rule| rule.into_resource(zone_id))
+                .collect(),
+            Family::Rulesets => {
+                let mut resources = Vec::new();
+                // NOTE: The list endpoint omits rules, so each ruleset is fetched individually.
+                for mut ruleset in client
+                    .discover_rulesets(zone_id, types::DISCOVERABLE_PHASES, include_defaults)
+                    .await?
+                {
+                    ruleset.rules = client.discover_ruleset_rules(zone_id, &ruleset.id).await?;
+                    tracing::debug!(ruleset_id = %ruleset.id, count = ruleset.rules.len(), "ruleset rules discovered");
+                    resources.extend(ruleset.into_resources(zone_id));
+                }
+                resources
+            }
+            Family::RateLimits => client
+                .discover_rate_limits(zone_id)
+                .await?
+                .into_iter()
+                .filter(|rule| include_disabled || !rule.disabled)
+                .map(|rule| rule.into_resource(zone_id))
+                .collect(),
+            Family::WorkerRoutes => client
+                .discover_worker_routes(zone_id)
+                .await?
+                .into_iter()
+                .map(|route| route.into_resource(zone_id))
+                .collect(),
+            Family::CustomCertificates => client
+                .discover_custom_certificates(zone_id)
+                .await?
+                .into_iter()
+                .map(|certificate| certificate.into_resource(zone_id))
+                .collect(),
+            Family::OriginCertificates => client
+                .discover_origin_certificates(zone_id)
+                .await?
+                .into_iter()
+                .map(|certificate| certificate.into_resource(zone_id))
+                .collect(),
+            Family::ZoneSettings => client
+                .discover_zone_settings(zone_id)
+                .await?
+                .into_iter()
+                .filter(|setting| include_defaults || setting.is_customized())
+                .map(|setting| setting.into_resource(zone_id))
+                .collect(),
+            Family::LoadBalancers => client
+                .discover_load_balancers(zone_id)
+                .await?
+                .into_iter()
+                .map(|lb| lb.into_resource(zone_id))
+                .collect(),
+            Family::HealthChecks => client
+                .discover_health_checks(zone_id)
+                .await?
+                .into_iter()
+                .map(|health_check| health_check.into_resource(zone_id))
+                .collect(),
+            Family::SpectrumApps => client
+                .discover_spectrum_apps(zone_id)
+                .await?
+                .into_iter()
+                .map(|app| app.into_resource(zone_id))
+                .collect(),
+            Family::CustomHostnames => client
+                .discover_custom_hostnames(zone_id)
+                .await?
+                .into_iter()
+                .map(|hostname| hostname.into_resource(zone_id))
+                .collect(),
+            Family::LoadBalancerPools => client
+                .discover_load_balancer_pools(&zone_info.account_id)
+                .await?
+                .into_iter()
+                .map(|pool| pool.into_resource(zone_id, &zone_info.account_id))
+                .collect(),
+            Family::Lists => client
+                .discover_account_lists(&zone_info.account_id)
+                .await?
+                .into_iter()
+                .map(|list| list.into_resource(zone_id, &zone_info.account_id))
+                .collect(),
+            Family::WorkerScripts => client
+                .discover_worker_scripts(&zone_info.account_id)
+                .await?
+                .into_iter()
+                .map(|script| script.into_resource(zone_id, &zone_info.account_id))
+                .collect(),
+            Family::PagesProjects => client
+                .discover_pages_projects(&zone_info.account_id)
+                .await?
+                .into_iter()
+                .map(|project| project.into_resource(zone_id, &zone_info.account_id))
+                .collect(),
+            Family::R2Buckets => client
+                .discover_r2_buckets(&zone_info.account_id)
+                .await?
+                .into_iter()
+                .map(|bucket| bucket.into_resource(zone_id, &zone_info.account_id))
+                .collect(),
+            Family::TurnstileWidgets => client
+                .discover_turnstile_widgets(&zone_info.account_id)
+                .await?
+                .into_iter()
+                .map(|widget| widget.into_resource(zone_id, &zone_info.account_id))
+                .collect(),
+        };
+        Ok(resources)
+    }
+}
+
+/// The requests discovery with `config` would send, as `GET <url>` lines in order.
+///
+/// Ids only known after a response are shown as placeholders, e.g.
+/// `{zone_id of example.com}`. Zone lookups are listed even if the zone cache would
+/// answer them.
+pub fn dry_run_requests(config: &DiscoverConfig) -> Vec<String> {
+    let base_url = config.api_base_url.clone().unwrap_or_else(default_base_url);
+    let mut paths = vec!["/user/tokens/verify".to_string()];
+    let account_families = account_families(config);
+    let zones: Vec<(String, String)> = if config.all_zones {
+        paths.push("/zones".to_string());
+        vec![("{zone_id}".to_string(), "{account_id}".to_string())]
+    } else {
+        config
+            .zones
+            .iter()
+            .map(|zone| {
+                if is_zone_id(zone) {
+                    paths.push(format!("/zones/{}", zone));
+                    (zone.clone(), format!("{{account_id of {}}}", zone))
+                } else {
+                    paths.push(format!("/zones?name={}", urlencoding::encode(zone)));
+                    (
+                        format!("{{zone_id of {}}}", zone),
+                        format!("{{account_id of {}}}", zone),
+                    )
+                }
+            })
+            .collect()
+    };
+    if config.all_accounts && !account_families.is_empty() {
+        paths.push("/accounts".to_string());
+    }
+    for (zone_id, account_id) in &zones {
+        let account_id = config.account_id.as_ref().unwrap_or(account_id);
+        for family in Family::ALL
+            .into_iter()
+            .filter(|f| f.resource_types().iter().any(|t| config.wants(t)))
+        {
+            paths.extend(family.paths(zone_id, account_id));
+        }
+    }
+    // Accounts without a discovered zone only get their account-level families.
+    let other_account = if config.all_accounts {
+        Some("{account_id of each other account}")
+    } else {
+        config.account_id.as_deref().filter(|_| zones.is_empty())
+    };
+    if let Some(account_id) = other_account {
+        for family in &account_families {
+            paths.extend(family.paths("", account_id));
+        }
+    }
+
+    paths
+        .into_iter()
+        .map(|path| format!("GET {}{}", base_url, path))
+        .collect()
+}
 
 pub struct CloudflareProvider {
     token: Option<String>,
@@ -20,13 +417,99 @@ impl CloudflareProvider {
     }
 }
 
-#[async_trait]
-impl Provider for CloudflareProvider {
-    fn name(&self) -> &str {
-        "cloudflare"
+impl CloudflareProvider {
+    /// Streams the DNS records of every zone `config` names, page by page as they
+    /// arrive, instead of collecting them all first like
+    /// [`discover`](Provider::discover). Zones are streamed one after another and the
+    /// stream ends at the first error.
+    ///
+    /// Only DNS records are streamed so far; `resource_types` is ignored.
+    pub fn discover_stream(
+        &self,
+        config: &DiscoverConfig,
+    ) -> impl Stream<Item = Result<Resource, ProviderError>> + use<> {
+        let setup = self.client(config).map(|client| (client, config.clone()));
+
+        stream::once(async move {
+            let (client, config) = setup?;
+            let zone_infos = connect(&client, &config).await?;
+            let records = stream::iter(zone_infos).flat_map(move |zone_info| {
+                client
+                    .stream_dns_records(&zone_info.zone_id)
+                    .map(move |record| {
+                        Ok(zone_info.annotate(record?.into_resource(&zone_info.zone_id)))
+                    })
+            });
+            Ok::<_, ProviderError>(records)
+        })
+        .try_flatten()
     }
 
-    async fn discover(&self, config: &DiscoverConfig) -> Result<Vec<Resource>, ProviderError> {
+    /// Probes each resource type's list endpoint in every configured zone and reports
+    /// whether the token may read it. Only auth and zone lookup failures are errors;
+    /// a failed probe is reported as [`Access::Failed`].
+    pub async fn verify_access(
+        &self,
+        config: &DiscoverConfig,
+    ) -> Result<Vec<ZoneAccess>, ProviderError> {
+        if let Some(types) = &config.resource_types {
+            self.check_resource_types(types)?;
+        }
+        self.check_resource_types(&config.exclude_types)?;
+        let client = self.client(config)?;
+        let zone_infos = connect(&client, config).await?;
+
+        let mut by_account: HashMap<(Family, String), Access> = HashMap::new();
+        let mut report = Vec::new();
+        for zone_info in zone_infos {
+            let mut checks = Vec::new();
+            for family in Family::ALL {
+                let types: Vec<&str> = family
+                    .resource_types()
+                    .iter()
+                    .copied()
+                    .filter(|t| config.wants(t))
+                    .collect();
+                if types.is_empty() {
+                    continue;
+                }
+
+                let key = (family, zone_info.account_id.clone());
+                let access = match by_account.get(&key) {
+                    Some(access) => access.clone(),
+                    None => {
+                        let path =
+                            family.paths(&zone_info.zone_id, &zone_info.account_id)[0].clone();
+                        let access = match client.probe(&path).await {
+                            Ok(()) => Access::Ok,
+                            Err(CloudflareError::Api {
+                                status: 401 | 403,
+                                message,
+                            }) => Access::Forbidden(message),
+                            Err(e) => Access::Failed(e.to_string()),
+                        };
+                        tracing::debug!(path, %access, "endpoint probed");
+                        // Account-level endpoints answer the same for every zone in the account.
+                        if family.is_account_level() {
+                            by_account.insert(key, access.clone());
+                        }
+                        access
+                    }
+                };
+                checks.extend(types.into_iter().map(|t| (t.to_string(), access.clone())));
+            }
+            report.push(ZoneAccess {
+                zone: zone_info.name,
+                checks,
+            });
+        }
+
+        Ok(report)
+    }
+
+    /// Builds a client for `config` without sending anything; fails when there's no
+    /// token, or neither a zone nor an account to work with.
+    fn client(&self, config: &DiscoverConfig) -> Result<CloudflareClient, ProviderError> {
         let token = self
             .token
             .clone()
@@ -38,81 +521,381 @@ impl Provider for CloudflareProvider {
                 )
             })?;
 
-        let client =
-            CloudflareClient::new(token).map_err(|e| ProviderError::Cloudflare(e.to_string()))?;
+        if config.zones.is_empty()
+            && !config.all_zones
+            && config.account_id.is_none()
+            && !config.all_accounts
+        {
+            return Err(ProviderError::Cloudflare(
+                "No zone provided. Set CLOUDFLARE_ZONE_ID or CLOUDFLARE_ZONES, or use --zone flag"
+                    .to_string(),
+            ));
+        }
 
-        client
-            .verify_auth()
-            .await
-            .map_err(|e| ProviderError::Cloudflare(e.to_string()))?;
+        let client = match &config.api_base_url {
+            Some(base_url) => CloudflareClient::with_base_url(token, base_url.clone()),
+            None => CloudflareClient::new(token),
+        }
+        .and_then(|c| c.with_timeouts(config.connect_timeout, config.read_timeout))
+        .and_then(|c| match config.page_size {
+            Some(page_size) => c.with_page_size(page_size),
+            None => Ok(c),
+        })
+        .and_then(|c| match config.max_concurrency {
+            Some(max_concurrency) => c.with_max_concurrency(max_concurrency),
+            None => Ok(c),
+        })
+        .map(|c| match &config.retry_on {
+            Some(statuses) => c.with_retry_on(statuses.clone()),
+            None => c,
+        })
+        .map(
+            |c| match (config.zone_cache_ttl, ZoneCache::default_location()) {
+                (Some(ttl), Some(cache)) => c.with_zone_cache(cache, ttl),
+                _ => c,
+            },
+        )
+        .map(|c| {
+            if !config.progress {
+                return c;
+            }
+            // Stderr keeps progress out of JSON written to stdout.
+            c.with_progress(|p| eprintln!("{}: page {}, {} items so far", p.path, p.pages, p.items))
+        })?;
+        Ok(client)
+    }
+}
 
-        tracing::info!("Cloudflare authentication verified");
+/// Verifies the token and resolves the zones `config` names, or lists every zone for
+/// `all_zones`, failing on the first zone that can't be looked up.
+async fn connect(
+    client: &CloudflareClient,
+    config: &DiscoverConfig,
+) -> Result<Vec<ZoneInfo>, ProviderError> {
+    let (zones, failures) = connect_each(client, config).await?;
+    match failures.into_iter().next() {
+        Some((_, error)) => Err(error),
+        None => Ok(zones),
+    }
+}
 
-        let zone = config.zone.as_ref().ok_or_else(|| {
-            ProviderError::Cloudflare(
-                "No zone provided. Set CLOUDFLARE_ZONE_ID or use --zone flag".to_string(),
-            )
-        })?;
+/// The account-level families `config` asks for.
+fn account_families(config: &DiscoverConfig) -> Vec<Family> {
+    Family::ALL
+        .into_iter()
+        .filter(|f| f.is_account_level() && f.resource_types().iter().any(|t| config.wants(t)))
+        .collect()
+}
 
-        let zone_info = client
-            .lookup_zone(zone)
-            .await
-            .map_err(|e| ProviderError::Cloudflare(e.to_string()))?;
+/// The accounts whose account-level resources to discover besides the discovered
+/// zones' own: every account the token can access with `all_accounts`, otherwise the
+/// configured `account_id`, if any. A failed account listing only warns, leaving the
+/// zones' accounts.
+async fn discovery_accounts(client: &CloudflareClient, config: &DiscoverConfig) -> Vec<String> {
+    if !config.all_accounts {
+        return config.account_id.iter().cloned().collect();
+    }
+    match client.accounts().await {
+        Ok(accounts) => {
+            tracing::info!(count = accounts.len(), "accounts listed");
+            accounts.into_iter().map(|account| account.id).collect()
+        }
+        Err(e) => {
+            tracing::warn!(error = %e, "account listing failed; only the zones' accounts are discovered");
+            Vec::new()
+        }
+    }
+}
 
-        tracing::info!(
-            zone_id = %zone_info.zone_id,
-            account_id = %zone_info.account_id,
-            "Zone lookup successful"
-        );
+/// Like [`connect`], but zones that can't be looked up are returned by name, in
+/// `config.zones` order, instead of failing the others. Lookups run up to
+/// `config.concurrency` at a time. A configured `account_id` replaces the account each
+/// lookup returned.
+async fn connect_each(
+    client: &CloudflareClient,
+    config: &DiscoverConfig,
+) -> Result<(Vec<ZoneInfo>, Vec<(String, ProviderError)>), ProviderError> {
+    client.verify_auth().await?;
 
-        let dns_records = client
-            .discover_dns_records(&zone_info.zone_id)
-            .await
-            .map_err(|e| ProviderError::Cloudflare(e.to_string()))?;
+    tracing::info!("Cloudflare authentication verified");
 
-        let mut resources: Vec<Resource> = dns_records
-            .into_iter()
-            .map(|record| record.into_resource(&zone_info.zone_id))
-            .collect();
+    let mut zones = Vec::new();
+    let mut failures = Vec::new();
+    if config.all_zones {
+        zones = client.zones().await?;
+        tracing::info!(count = zones.len(), "zones listed");
+        // A valid token with no zone permissions lists nothing rather than failing.
+        if zones.is_empty() {
+            return Err(ProviderError::PermissionDenied(
+                "the token can't access any zone; check that its permissions include Zone Read for the zones to discover"
+                    .to_string(),
+            ));
+        }
+    } else {
+        let limit = Arc::new(Semaphore::new(
+            config
+                .concurrency
+                .unwrap_or(DEFAULT_DISCOVERY_CONCURRENCY)
+                .max(1),
+        ));
+        let mut lookups = JoinSet::new();
+        for (index, zone) in config.zones.iter().cloned().enumerate() {
+            let (client, limit) = (client.clone(), limit.clone());
+            lookups.spawn(async move {
+                // NOTE: The semaphore is never closed, so the permit is always granted.
+                let _permit = limit.acquire().await;
+                let result = client.lookup_zone(&zone).await;
+                (index, zone, result)
+            });
+        }
 
-        tracing::info!(count = resources.len(), "DNS records discovered");
+        let mut results = Vec::new();
+        while let Some(joined) = lookups.join_next().await {
+            results.push(joined.map_err(|e| {
+                ProviderError::Cloudflare(format!("zone lookup task failed: {}", e))
+            })?);
+        }
+        results.sort_by_key(|(index, _, _)| *index);
+        for (_, zone, result) in results {
+            match result {
+                Ok(zone_info) => {
+                    tracing::info!(
+                        zone_id = %zone_info.zone_id,
+                        account_id = %zone_info.account_id,
+                        "Zone lookup successful"
+                    );
+                    zones.push(zone_info);
+                }
+                Err(e) => failures.push((zone, e.into())),
+            }
+        }
+    }
+    if let Some(account_id) = &config.account_id {
+        for zone_info in &mut zones {
+            zone_info.account_id = account_id.clone();
+        }
+    }
+    Ok((zones, failures))
+}
 
-        let page_rules = client
-            .discover_page_rules(&zone_info.zone_id)
-            .await
-            .map_err(|e| ProviderError::Cloudflare(e.to_string()))?;
+#[async_trait]
+impl Provider for CloudflareProvider {
+    fn name(&self) -> &str {
+        "cloudflare"
+    }
 
-        tracing::info!(count = page_rules.len(), "page rules discovered");
+    /// Fails on the first resource type that fails; see
+    /// [`discover_report`](Provider::discover_report) to keep the others.
+    async fn discover(&self, config: &DiscoverConfig) -> Result<Vec<Resource>, ProviderError> {
+        let report = self.discover_report(config).await?;
+        match report.errors.into_iter().next() {
+            Some(failure) => Err(failure.error),
+            None => Ok(report.resources),
+        }
+    }
 
-        let page_rule_resources: Vec<Resource> = page_rules
-            .into_iter()
-            .map(|rule| rule.into_resource(&zone_info.zone_id))
-            .collect();
+    async fn discover_report(
+        &self,
+        config: &DiscoverConfig,
+    ) -> Result<DiscoveryReport, ProviderError> {
+        if let Some(types) = &config.resource_types {
+            self.check_resource_types(types)?;
+        }
+        self.check_resource_types(&config.exclude_types)?;
+        let client = self.client(config)?;
 
-        resources.extend(page_rule_resources);
+        if config.dry_run {
+            for request in dry_run_requests(config) {
+                tracing::info!(%request, "dry run: request not sent");
+            }
+            return Ok(DiscoveryReport::default());
+        }
 
-        let rulesets = client
-            .discover_rulesets(&zone_info.zone_id, types::DISCOVERABLE_PHASES)
-            .await
-            .map_err(|e| ProviderError::Cloudflare(e.to_string()))?;
+        let (zone_infos, mut lookup_failures) = connect_each(&client, config).await?;
+        // With no zone left there's nothing to report on; fail as a single zone would.
+        // `fail_fast` fails the same way as soon as any zone is missing.
+        if (config.fail_fast || zone_infos.is_empty()) && !lookup_failures.is_empty() {
+            return Err(lookup_failures.remove(0).1);
+        }
+        let zone_ids: Vec<String> = zone_infos.iter().map(|z| z.zone_id.clone()).collect();
 
-        tracing::info!(count = rulesets.len(), "rulesets discovered");
+        let concurrency = config
+            .concurrency
+            .unwrap_or(DEFAULT_DISCOVERY_CONCURRENCY)
+            .max(1);
+        // Either split bound replaces `concurrency` for families, which then only bounds
+        // zone lookups; the bound left unset defaults to `concurrency`.
+        let (limit, zone_concurrency, per_zone) =
+            if config.zone_concurrency.is_some() || config.per_zone_concurrency.is_some() {
+                (
+                    None,
+                    config.zone_concurrency.unwrap_or(concurrency).max(1),
+                    config.per_zone_concurrency.unwrap_or(concurrency).max(1),
+                )
+            } else {
+                (
+                    Some(Arc::new(Semaphore::new(concurrency))),
+                    Semaphore::MAX_PERMITS,
+                    Semaphore::MAX_PERMITS,
+                )
+            };
+        let zone_limit = Arc::new(Semaphore::new(zone_concurrency));
+        let (include_disabled, include_defaults) =
+            (config.include_disabled, config.include_defaults);
+        let mut scopes = Vec::new();
+        let mut accounts = HashSet::new();
+        for zone_info in zone_infos {
+            // Account-level families are fetched once, through the account's first zone.
+            let first_in_account = accounts.insert(zone_info.account_id.clone());
+            // Families with no requested type are never fetched.
+            let families: Vec<Family> = Family::ALL
+                .into_iter()
+                .filter(|f| {
+                    f.resource_types().iter().any(|t| config.wants(t))
+                        && (first_in_account || !f.is_account_level())
+                })
+                .collect();
+            scopes.push((zone_info, families));
+        }
+        let account_families = account_families(config);
+        if !account_families.is_empty() {
+            for account_id in discovery_accounts(&client, config).await {
+                if accounts.insert(account_id.clone()) {
+                    scopes.push((ZoneInfo::account_only(account_id), account_families.clone()));
+                }
+            }
+        }
 
-        let ruleset_resources: Vec<Resource> = rulesets
-            .into_iter()
-            .map(|ruleset| ruleset.into_resource(&zone_info.zone_id))
-            .collect();
+        let mut tasks = JoinSet::new();
+        for (zone_info, families) in scopes {
+            let (client, limit, zone_limit) = (client.clone(), limit.clone(), zone_limit.clone());
+            // One task per zone, holding a zone permit until all its families are done.
+            tasks.spawn(async move {
+                // NOTE: The semaphores are never closed, so permits are always granted.
+                let _zone_permit = zone_limit.acquire().await;
+                let zone_slots = Semaphore::new(per_zone);
+                let results = future::join_all(families.into_iter().map(|family| {
+                    let (client, zone_info, limit, zone_slots) =
+                        (&client, &zone_info, &limit, &zone_slots);
+                    async move {
+                        let _slot = zone_slots.acquire().await;
+                        let _permit = match limit {
+                            Some(limit) => Some(limit.acquire().await),
+                            None => None,
+                        };
+                        let result = family
+                            .discover(client, zone_info, include_disabled, include_defaults)
+                            .await;
+                        (family, result)
+                    }
+                }))
+                .await;
+                (zone_info, results)
+            });
+        }
 
-        resources.extend(ruleset_resources);
+        let mut report = DiscoveryReport {
+            zones: zone_ids,
+            ..Default::default()
+        };
+        for (zone, error) in lookup_failures {
+            tracing::warn!(zone, error = %error, "zone lookup failed");
+            report.errors.push(DiscoveryFailure {
+                resource_type: "zone".to_string(),
+                zone,
+                error,
+            });
+        }
+        let mut skips = Vec::new();
+        // Returning early drops `tasks`, which aborts whatever is still running.
+        while let Some(joined) = tasks.join_next().await {
+            let (zone_info, results) = joined
+                .map_err(|e| ProviderError::Cloudflare(format!("discovery task failed: {}", e)))?;
+            for (family, result) in results {
+                match result {
+                    Ok(found) => {
+                        tracing::info!(
+                            zone_id = %zone_info.zone_id,
+                            resource_type = family.resource_type(),
+                            count = found.len(),
+                            "resources discovered"
+                        );
+                        report.resources.extend(
+                            found
+                                .into_iter()
+                                .filter(|r| config.wants(&r.resource_type))
+                                .map(|r| zone_info.annotate(r)),
+                        );
+                        report.succeeded += 1;
+                    }
+                    Err(CloudflareError::Api {
+                        status: 403,
+                        message,
+                    }) if family.is_optional() => {
+                        if config.strict {
+                            return Err(ProviderError::PermissionDenied(format!(
+                                "{}: {}",
+                                family.resource_type(),
+                                message
+                            )));
+                        }
+                        skips.push((family.resource_type(), message))
+                    }
+                    Err(e) if config.fail_fast => return Err(e.into()),
+                    Err(e) => {
+                        tracing::warn!(
+                            zone_id = %zone_info.zone_id,
+                            resource_type = family.resource_type(),
+                            error = %e,
+                            "resource type failed"
+                        );
+                        report.errors.push(DiscoveryFailure {
+                            resource_type: family.resource_type().to_string(),
+                            zone: zone_info.name.clone(),
+                            error: e.into(),
+                        });
+                    }
+                }
+            }
+        }
 
-        Ok(resources)
+        // Families finish in any order; sort so output is stable between runs. A type
+        // skipped in several zones is only reported once.
+        skips.sort();
+        skips.dedup_by_key(|(resource_type, _)| *resource_type);
+        for (resource_type, reason) in skips {
+            report.skipped.record(resource_type, &reason);
+        }
+        report.resources.sort_by(|a, b| {
+            (&a.resource_type, &a.resource_id).cmp(&(&b.resource_type, &b.resource_id))
+        });
+        report
+            .errors
+            .sort_by(|a, b| (&a.resource_type, &a.zone).cmp(&(&b.resource_type, &b.zone)));
+
+        report.api_usage = ApiUsage {
+            requests: client.unique_request_count(),
+            attempts: client.request_count(),
+        };
+        tracing::info!(
+            requests = report.api_usage.requests,
+            attempts = report.api_usage.attempts,
+            "API usage"
+        );
+
+        Ok(report)
     }
 
-    fn generate_import(&self, resource: &Resource) -> String {
-        format!(
-            "import {{\n  to = {}.{}\n  id = \"{}\"\n}}",
-            resource.resource_type, resource.name, resource.resource_id
-        )
+    fn generate_import(&self, resource: &Resource, module: Option<&str>) -> String {
+        terraform::import_block(resource, module)
+    }
+
+    fn import_id(&self, resource: &Resource) -> String {
+        import_id(resource)
+    }
+
+    fn generate_config(&self, resource: &Resource, identifier: &str) -> Option<String> {
+        resource_block(resource, identifier)
     }
 
     fn resource_types(&self) -> Vec<&str> {
@@ -120,6 +903,22 @@ impl Provider for CloudflareProvider {
             "cloudflare_dns_record",
             "cloudflare_page_rule",
             "cloudflare_ruleset",
+            RULESET_RULE_TYPE,
+            "cloudflare_rate_limit",
+            "cloudflare_workers_route",
+            "cloudflare_custom_ssl",
+            "cloudflare_origin_ca_certificate",
+            "cloudflare_zone_setting",
+            "cloudflare_load_balancer",
+            "cloudflare_healthcheck",
+            "cloudflare_spectrum_application",
+            "cloudflare_custom_hostname",
+            "cloudflare_load_balancer_pool",
+            "cloudflare_list",
+            "cloudflare_workers_script",
+            "cloudflare_pages_project",
+            "cloudflare_r2_bucket",
+            "cloudflare_turnstile_widget",
         ]
     }
 }
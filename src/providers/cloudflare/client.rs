@@ -1,11 +1,192 @@
-use reqwest::header::{AUTHORIZATION, HeaderMap, HeaderValue};
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use futures::stream::{self, Stream, TryStreamExt};
+use reqwest::header::{AUTHORIZATION, HeaderMap, HeaderName, HeaderValue};
+use tokio::sync::Semaphore;
 
 use super::CloudflareError;
 use super::types::{
-    CloudflareResponse, DEFAULT_PAGE_SIZE, DnsRecord, PageRule, Ruleset, Zone, ZoneInfo, is_zone_id,
+    ACCOUNTS_PAGE_SIZE, Account, AccountList, CUSTOM_CERTIFICATES_PAGE_SIZE, CloudflareApiMessage,
+    CloudflareResponse, CustomCertificate, CustomHostname, DEFAULT_PAGE_SIZE, DnsRecord,
+    HealthCheck, LoadBalancer, LoadBalancerPool, MAX_PAGE_SIZE, MIN_PAGE_SIZE, OriginCertificate,
+    PageRule, PageToken, PagedResponse, PagesProject, PaginationStrategy, R2Bucket, R2BucketList,
+    RateLimit, Ruleset, RulesetRule, SpectrumApp, TurnstileWidget, WorkerRoute, WorkerScript,
+    ZONES_PAGE_SIZE, Zone, ZoneInfo, ZoneSetting, is_zone_id,
 };
+use crate::cache::ZoneCache;
+
+pub const CLOUDFLARE_API_BASE: &str = "https://api.cloudflare.com/client/v4";
+
+/// Environment variable [`CloudflareClient::new`] reads a base URL from instead of
+/// [`CLOUDFLARE_API_BASE`], e.g. for a self-hosted gateway.
+pub const API_BASE_URL_ENV: &str = "CLOUDFLARE_API_BASE_URL";
+
+/// How long a whole request may take unless overridden with
+/// [`CloudflareClient::with_timeout`], so a hung endpoint can't stall discovery.
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Requests in flight at once unless overridden with
+/// [`CloudflareClient::with_max_concurrency`].
+pub const DEFAULT_MAX_CONCURRENCY: usize = 4;
+
+/// Pages a single list request may follow unless overridden with
+/// [`CloudflareClient::with_max_pages`], so a server that never stops paging can't loop
+/// forever.
+pub const DEFAULT_MAX_PAGES: u32 = 10_000;
+
+const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+const IDEMPOTENCY_KEY_HEADER: HeaderName = HeaderName::from_static("idempotency-key");
+
+/// Statuses retried unless overridden with [`CloudflareClient::with_retry_on`].
+pub const DEFAULT_RETRY_ON: &[u16] = &[429, 500, 502, 503, 504];
+const DEFAULT_MAX_RETRIES: u32 = 3;
+const DEFAULT_RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+/// Upper bound on a server-requested `Retry-After`, so a bogus value can't stall a run.
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone)]
+struct RetryPolicy {
+    max_retries: u32,
+    base_delay: Duration,
+    retry_on: Vec<u16>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: DEFAULT_MAX_RETRIES,
+            base_delay: DEFAULT_RETRY_BASE_DELAY,
+            retry_on: DEFAULT_RETRY_ON.to_vec(),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Doubles the base delay for every retry already made.
+    fn delay(&self, retries: u32) -> Duration {
+        self.base_delay.saturating_mul(2u32.saturating_pow(retries))
+    }
+
+    /// The backoff delay plus up to half of it again as jitter, so clients that failed
+    /// together don't all retry in lockstep.
+    fn delay_with_jitter(&self, retries: u32) -> Duration {
+        let delay = self.delay(retries);
+        let spread = delay.as_millis() as u64 / 2;
+        if spread == 0 {
+            return delay;
+        }
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.subsec_nanos() as u64)
+            .unwrap_or(0);
+        delay + Duration::from_millis(seed % (spread + 1))
+    }
+}
+
+/// Reads a `Retry-After` given in seconds; the HTTP-date form isn't used by Cloudflare.
+fn retry_after(response: &reqwest::Response) -> Option<Duration> {
+    let seconds = response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<u64>()
+        .ok()?;
+    Some(Duration::from_secs(seconds).min(MAX_RETRY_AFTER))
+}
+
+/// Reads `response` as JSON, failing with [`CloudflareError::InvalidResponse`] when the
+/// body isn't JSON at all, e.g. an HTML error page from a proxy or a truncated body.
+async fn read_json(response: reqwest::Response) -> Result<serde_json::Value, CloudflareError> {
+    let status = response.status().as_u16();
+    let body = response.text().await?;
+    serde_json::from_str(&body).map_err(|_| CloudflareError::invalid_response(status, &body))
+}
 
-const CLOUDFLARE_API_BASE: &str = "https://api.cloudflare.com/client/v4";
+/// Appends `query` to `url`, after any query string `url` already carries.
+fn with_query(url: &str, query: &str) -> String {
+    let separator = if url.contains('?') { '&' } else { '?' };
+    format!("{}{}{}", url, separator, query)
+}
+
+/// The base URL [`CloudflareClient::new`] uses: [`API_BASE_URL_ENV`] when set and not
+/// blank, otherwise [`CLOUDFLARE_API_BASE`].
+pub fn default_base_url() -> String {
+    std::env::var(API_BASE_URL_ENV)
+        .ok()
+        .filter(|url| !url.trim().is_empty())
+        .unwrap_or_else(|| CLOUDFLARE_API_BASE.to_string())
+}
+
+/// Checks `base_url` is an absolute `http`/`https` URL and drops any trailing `/`, since
+/// request paths are appended with their own leading `/`.
+fn validate_base_url(base_url: &str) -> Result<String, CloudflareError> {
+    let trimmed = base_url.trim();
+    let invalid = |message: String| CloudflareError::InvalidBaseUrl {
+        url: base_url.to_string(),
+        message,
+    };
+    let url = reqwest::Url::parse(trimmed).map_err(|e| invalid(e.to_string()))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(invalid(format!(
+            "scheme must be http or https, not '{}'",
+            url.scheme()
+        )));
+    }
+    Ok(trimmed.trim_end_matches('/').to_string())
+}
+
+/// Per-request settings for [`CloudflareClient::send`].
+#[derive(Debug, Clone, Default)]
+pub struct RequestOptions {
+    pub headers: HeaderMap,
+    /// Sent as `Idempotency-Key` so a retried non-GET request is applied at most once.
+    /// GETs are already safe to retry and leave this unset.
+    pub idempotency_key: Option<String>,
+}
+
+/// Reported after each page of a paginated list request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageProgress {
+    /// The list endpoint relative to the API base, e.g. `/zones/{id}/dns_records`.
+    pub path: String,
+    /// Pages fetched so far, starting at 1.
+    pub pages: u32,
+    /// Items fetched so far across those pages.
+    pub items: usize,
+}
+
+type ProgressCallback = Arc<dyn Fn(&PageProgress) + Send + Sync>;
+
+/// Requests sent by a client and its clones; see [`CloudflareClient::request_count`].
+#[derive(Debug, Default)]
+struct RequestCounters {
+    /// Every HTTP request sent, retries included.
+    attempts: AtomicU64,
+    /// Each call to [`CloudflareClient::send`] once, however often it was retried.
+    unique: AtomicU64,
+}
+
+/// Generates an id that stays fixed across every attempt of one logical request, so
+/// retries can be correlated in logs and on the server side.
+fn next_request_id() -> String {
+    static SEQUENCE: AtomicU64 = AtomicU64::new(0);
+
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    format!(
+        "tia-{:x}-{:x}-{}",
+        std::process::id(),
+        nanos,
+        SEQUENCE.fetch_add(1, Ordering::Relaxed)
+    )
+}
 
 #[derive(Clone)]
 pub struct CloudflareClient {
@@ -13,19 +194,150 @@ pub struct CloudflareClient {
     #[allow(dead_code)] // TODO: remove (currently needed for token refresh)
     token: String,
     base_url: String,
+    connect_timeout: Option<Duration>,
+    read_timeout: Option<Duration>,
+    retry: RetryPolicy,
+    zone_cache: Option<(ZoneCache, Duration)>,
+    page_size: u32,
+    max_pages: u32,
+    progress: Option<ProgressCallback>,
+    /// Shared by every clone, so the bound holds across all zones and resource types.
+    limiter: Arc<Semaphore>,
+    /// Shared by every clone, like `limiter`, so the counts cover a whole run.
+    counters: Arc<RequestCounters>,
 }
 
 impl CloudflareClient {
     pub fn new(token: String) -> Result<Self, CloudflareError> {
-        Self::with_base_url(token, CLOUDFLARE_API_BASE.to_string())
+        Self::with_base_url(token, default_base_url())
     }
 
-    /// NOTE: Primarily used for testing with mock servers.
+    /// Sends requests to `base_url` instead, e.g. a gateway or a mock server. It must be
+    /// an absolute `http` or `https` URL.
     pub fn with_base_url(token: String, base_url: String) -> Result<Self, CloudflareError> {
-        Self::create_client(token, base_url)
+        Self::create_client(token, validate_base_url(&base_url)?)
+    }
+
+    /// Sets how long to wait for a connection separately from how long a full response
+    /// may take, so connects fail fast while large responses are still tolerated. A
+    /// `None` keeps the current value.
+    pub fn with_timeouts(
+        self,
+        connect_timeout: Option<Duration>,
+        read_timeout: Option<Duration>,
+    ) -> Result<Self, CloudflareError> {
+        let connect_timeout = connect_timeout.or(self.connect_timeout);
+        let read_timeout = read_timeout.or(self.read_timeout);
+        let mut client = Self::build(
+            self.token.clone(),
+            self.base_url.clone(),
+            connect_timeout,
+            read_timeout,
+        )?;
+        client.retry = self.retry;
+        client.zone_cache = self.zone_cache;
+        client.page_size = self.page_size;
+        client.max_pages = self.max_pages;
+        client.progress = self.progress;
+        client.limiter = self.limiter;
+        client.counters = self.counters;
+        Ok(client)
+    }
+
+    /// Sets how long a whole request may take before failing with
+    /// [`CloudflareError::Timeout`]; defaults to [`DEFAULT_REQUEST_TIMEOUT`].
+    pub fn with_timeout(self, timeout: Duration) -> Result<Self, CloudflareError> {
+        self.with_timeouts(None, Some(timeout))
+    }
+
+    /// Sets the `per_page` requested from list endpoints, clamped to the range
+    /// Cloudflare accepts; endpoints with a lower cap use that instead. Larger pages
+    /// mean fewer requests but bigger responses held in memory at once.
+    pub fn with_page_size(mut self, page_size: u32) -> Result<Self, CloudflareError> {
+        if page_size == 0 {
+            return Err(CloudflareError::InvalidPageSize { page_size });
+        }
+        self.page_size = page_size.clamp(MIN_PAGE_SIZE, MAX_PAGE_SIZE);
+        Ok(self)
+    }
+
+    pub fn page_size(&self) -> u32 {
+        self.page_size
+    }
+
+    /// Sets how many pages one list request may follow before failing; at least 1.
+    pub fn with_max_pages(mut self, max_pages: u32) -> Self {
+        self.max_pages = max_pages.max(1);
+        self
+    }
+
+    /// Bounds how many requests this client and all of its clones have in flight at
+    /// once; defaults to [`DEFAULT_MAX_CONCURRENCY`]. Clones made before this call keep
+    /// the previous limit.
+    pub fn with_max_concurrency(mut self, max_concurrency: usize) -> Result<Self, CloudflareError> {
+        if max_concurrency == 0 {
+            return Err(CloudflareError::InvalidMaxConcurrency { max_concurrency });
+        }
+        self.limiter = Arc::new(Semaphore::new(max_concurrency));
+        Ok(self)
+    }
+
+    /// Calls `callback` after every page that
+    /// [`fetch_all_pages`](Self::fetch_all_pages) or
+    /// [`fetch_all_cursors`](Self::fetch_all_cursors) fetches, e.g. to show progress on
+    /// large zones. It runs on the discovery tasks, so it should return quickly.
+    pub fn with_progress(
+        mut self,
+        callback: impl Fn(&PageProgress) + Send + Sync + 'static,
+    ) -> Self {
+        self.progress = Some(Arc::new(callback));
+        self
+    }
+
+    /// Serves [`lookup_zone`](Self::lookup_zone) from `cache` while entries are younger
+    /// than `ttl`, and records every lookup that goes to the API.
+    pub fn with_zone_cache(mut self, cache: ZoneCache, ttl: Duration) -> Self {
+        self.zone_cache = Some((cache, ttl));
+        self
+    }
+
+    /// Sets how many times a retryable response is retried and the delay before the
+    /// first retry, which doubles on each subsequent one.
+    pub fn with_retry_config(mut self, max_retries: u32, base_delay: Duration) -> Self {
+        self.retry.max_retries = max_retries;
+        self.retry.base_delay = base_delay;
+        self
+    }
+
+    /// Replaces the set of HTTP statuses that are retried; an empty set disables
+    /// retrying altogether.
+    pub fn with_retry_on(mut self, statuses: Vec<u16>) -> Self {
+        self.retry.retry_on = statuses;
+        self
+    }
+
+    pub fn retry_on(&self) -> &[u16] {
+        &self.retry.retry_on
+    }
+
+    pub fn connect_timeout(&self) -> Option<Duration> {
+        self.connect_timeout
+    }
+
+    pub fn read_timeout(&self) -> Option<Duration> {
+        self.read_timeout
     }
 
     fn create_client(token: String, base_url: String) -> Result<Self, CloudflareError> {
+        Self::build(token, base_url, None, Some(DEFAULT_REQUEST_TIMEOUT))
+    }
+
+    fn build(
+        token: String,
+        base_url: String,
+        connect_timeout: Option<Duration>,
+        read_timeout: Option<Duration>,
+    ) -> Result<Self, CloudflareError> {
         let mut headers = HeaderMap::new();
         let auth_value = format!("Bearer {}", token);
         let header_value =
@@ -34,28 +346,116 @@ impl CloudflareClient {
             })?;
         headers.insert(AUTHORIZATION, header_value);
 
-        let client = reqwest::Client::builder()
-            .default_headers(headers)
-            .build()
-            .map_err(CloudflareError::Network)?;
+        let mut builder = reqwest::Client::builder().default_headers(headers);
+        if let Some(timeout) = connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(timeout) = read_timeout {
+            builder = builder.timeout(timeout);
+        }
+        let client = builder.build().map_err(CloudflareError::Network)?;
 
         Ok(Self {
             client,
             token,
             base_url,
+            connect_timeout,
+            read_timeout,
+            retry: RetryPolicy::default(),
+            zone_cache: None,
+            page_size: DEFAULT_PAGE_SIZE,
+            max_pages: DEFAULT_MAX_PAGES,
+            progress: None,
+            limiter: Arc::new(Semaphore::new(DEFAULT_MAX_CONCURRENCY)),
+            counters: Arc::new(RequestCounters::default()),
         })
     }
 
+    /// Sends a GET with a generated request id plus any per-request headers.
+    ///
+    /// Responses with a retryable status are retried under the same request id: a 429
+    /// waits for its `Retry-After` (capped), anything else backs off exponentially with
+    /// jitter. A final 429 becomes [`CloudflareError::RateLimited`]; any other status is
+    /// returned as-is once retries run out.
+    ///
+    /// Each attempt waits for a slot under the client's
+    /// [`max concurrency`](Self::with_max_concurrency); the slot is released while
+    /// backing off.
+    pub async fn send(
+        &self,
+        url: &str,
+        options: &RequestOptions,
+    ) -> Result<reqwest::Response, CloudflareError> {
+        let request_id = next_request_id();
+        let mut attempt = 1;
+        self.counters.unique.fetch_add(1, Ordering::Relaxed);
+
+        loop {
+            tracing::debug!(request_id = %request_id, attempt, url, "sending request");
+            self.counters.attempts.fetch_add(1, Ordering::Relaxed);
+
+            let mut request = self
+                .client
+                .get(url)
+                .headers(options.headers.clone())
+                .header(REQUEST_ID_HEADER, &request_id);
+            if let Some(key) = &options.idempotency_key {
+                request = request.header(IDEMPOTENCY_KEY_HEADER, key);
+            }
+            let response = {
+                // NOTE: The semaphore is never closed, so acquiring only fails in theory.
+                let _permit = self.limiter.acquire().await.ok();
+                request.send().await?
+            };
+
+            let status = response.status().as_u16();
+            let server_delay = retry_after(&response);
+            if attempt > self.retry.max_retries || !self.retry.retry_on.contains(&status) {
+                if status == 429 {
+                    return Err(CloudflareError::RateLimited {
+                        retry_after: server_delay.map(|d| d.as_secs()).unwrap_or(0),
+                    });
+                }
+                return Ok(response);
+            }
+
+            let delay = match (status, server_delay) {
+                (429, Some(delay)) => delay,
+                _ => self.retry.delay_with_jitter(attempt - 1),
+            };
+            tracing::warn!(
+                request_id = %request_id,
+                attempt,
+                status,
+                delay_ms = delay.as_millis() as u64,
+                "retrying request"
+            );
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    /// HTTP requests this client and its clones have sent, counting every retry, which
+    /// is what Cloudflare's rate limits count too.
+    pub fn request_count(&self) -> u64 {
+        self.counters.attempts.load(Ordering::Relaxed)
+    }
+
+    /// Like [`request_count`](Self::request_count), but a retried request counts once.
+    pub fn unique_request_count(&self) -> u64 {
+        self.counters.unique.load(Ordering::Relaxed)
+    }
+
+    async fn get(&self, url: &str) -> Result<reqwest::Response, CloudflareError> {
+        self.send(url, &RequestOptions::default()).await
+    }
+
     pub async fn verify_auth(&self) -> Result<(), CloudflareError> {
         let url = format!("{}/user/tokens/verify", self.base_url);
 
-        let response = self.client.get(&url).send().await?;
+        let response = self.get(&url).await?;
 
-        let status = response.status();
-        let body: serde_json::Value = response.json().await.map_err(|e| CloudflareError::Api {
-            status: status.as_u16(),
-            message: format!("Failed to parse response: {}", e),
-        })?;
+        let body = read_json(response).await?;
 
         if body.get("success").and_then(|v| v.as_bool()) == Some(true) {
             return Ok(());
@@ -74,27 +474,113 @@ impl CloudflareClient {
         })
     }
 
+    /// Asks the list endpoint at `path` (relative to the API base) for a single item,
+    /// to check the token may read it without fetching everything.
+    pub async fn probe(&self, path: &str) -> Result<(), CloudflareError> {
+        let url = with_query(&format!("{}{}", self.base_url, path), "per_page=1");
+
+        let response = self.get(&url).await?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(());
+        }
+
+        let body: serde_json::Value = response.json().await.unwrap_or_default();
+        let message = body
+            .get("errors")
+            .and_then(|e| e.as_array())
+            .and_then(|arr| arr.first())
+            .and_then(|e| e.get("message"))
+            .and_then(|m| m.as_str())
+            .or(status.canonical_reason())
+            .unwrap_or("Unknown API error");
+
+        Err(CloudflareError::Api {
+            status: status.as_u16(),
+            message: message.to_string(),
+        })
+    }
+
+    /// Lists every account the token can access, used to fan out account-scoped
+    /// discovery when no explicit account is configured.
+    pub async fn accounts(&self) -> Result<Vec<Account>, CloudflareError> {
+        let url = format!("{}/accounts", self.base_url);
+
+        self.fetch_all_pages(
+            &url,
+            self.page_size.min(ACCOUNTS_PAGE_SIZE),
+            |result| async move {
+                serde_json::from_value::<Vec<Account>>(result).map_err(|e| CloudflareError::Api {
+                    status: 0,
+                    message: format!("Failed to parse accounts: {}", e),
+                })
+            },
+        )
+        .await
+    }
+
+    /// Lists every zone the token can access, for discovery across all of them.
+    pub async fn zones(&self) -> Result<Vec<ZoneInfo>, CloudflareError> {
+        let url = format!("{}/zones", self.base_url);
+
+        self.fetch_all_pages(
+            &url,
+            self.page_size.min(ZONES_PAGE_SIZE),
+            |result| async move {
+                serde_json::from_value::<Vec<Zone>>(result)
+                    .map(|zones| zones.into_iter().map(ZoneInfo::from).collect())
+                    .map_err(|e| CloudflareError::Api {
+                        status: 0,
+                        message: format!("Failed to parse zones: {}", e),
+                    })
+            },
+        )
+        .await
+    }
+
     pub async fn lookup_zone(&self, zone: &str) -> Result<ZoneInfo, CloudflareError> {
-        if is_zone_id(zone) {
-            self.lookup_zone_by_id(zone).await
+        if let Some((cache, ttl)) = &self.zone_cache
+            && let Some(entry) = cache.get(zone, *ttl)
+        {
+            tracing::debug!(zone, zone_id = %entry.zone_id, "zone lookup served from cache");
+            return Ok(ZoneInfo {
+                zone_id: entry.zone_id,
+                account_id: entry.account_id,
+                name: entry.name,
+            });
+        }
+
+        let info = if is_zone_id(zone) {
+            // A 32-character hex string is also a valid domain label, so an id that
+            // isn't found is retried as a name before giving up.
+            match self.lookup_zone_by_id(zone).await {
+                Err(CloudflareError::ZoneNotFound { .. }) => {
+                    tracing::debug!(zone, "no zone with this id; retrying as a name");
+                    self.lookup_zone_by_name(zone).await?
+                }
+                result => result?,
+            }
         } else {
-            self.lookup_zone_by_name(zone).await
+            self.lookup_zone_by_name(zone).await?
+        };
+
+        // Best-effort: a cache write failure never fails the lookup itself.
+        if let Some((cache, _)) = &self.zone_cache
+            && let Err(e) = cache.put(zone, &info.zone_id, &info.account_id, &info.name)
+        {
+            tracing::warn!(error = %e, "failed to write zone cache");
         }
+
+        Ok(info)
     }
 
     async fn lookup_zone_by_id(&self, zone_id: &str) -> Result<ZoneInfo, CloudflareError> {
         let url = format!("{}/zones/{}", self.base_url, zone_id);
 
-        let response = self.client.get(&url).send().await?;
+        let response = self.get(&url).await?;
         let status = response.status();
 
-        let body: serde_json::Value =
-            response
-                .json()
-                .await
-                .map_err(|e| CloudflareError::ZoneLookupFailed {
-                    message: format!("Failed to parse response: {}", e),
-                })?;
+        let body = read_json(response).await?;
 
         let success = body
             .get("success")
@@ -126,25 +612,16 @@ impl CloudflareClient {
             }
         })?;
 
-        Ok(ZoneInfo {
-            zone_id: result.id,
-            account_id: result.account.id,
-        })
+        Ok(result.into())
     }
 
     async fn lookup_zone_by_name(&self, zone_name: &str) -> Result<ZoneInfo, CloudflareError> {
         let encoded_name = urlencoding::encode(zone_name);
         let url = format!("{}/zones?name={}", self.base_url, encoded_name);
 
-        let response = self.client.get(&url).send().await?;
+        let response = self.get(&url).await?;
 
-        let body: serde_json::Value =
-            response
-                .json()
-                .await
-                .map_err(|e| CloudflareError::ZoneLookupFailed {
-                    message: format!("Failed to parse response: {}", e),
-                })?;
+        let body = read_json(response).await?;
 
         let success = body
             .get("success")
@@ -177,10 +654,7 @@ impl CloudflareClient {
                 zone: zone_name.to_string(),
             })?;
 
-        Ok(ZoneInfo {
-            zone_id: zone.id,
-            account_id: zone.account.id,
-        })
+        Ok(zone.into())
     }
 
     pub async fn discover_dns_records(
@@ -189,7 +663,7 @@ impl CloudflareClient {
     ) -> Result<Vec<DnsRecord>, CloudflareError> {
         let url = format!("{}/zones/{}/dns_records", self.base_url, zone_id);
 
-        self.fetch_all_pages(&url, DEFAULT_PAGE_SIZE, |result| async move {
+        self.fetch_all_pages(&url, self.page_size, |result| async move {
             serde_json::from_value::<Vec<DnsRecord>>(result).map_err(|e| {
                 CloudflareError::DiscoveryFailed {
                     resource_type: "cloudflare_dns_record".to_string(),
@@ -200,12 +674,35 @@ impl CloudflareClient {
         .await
     }
 
+    /// Like [`discover_dns_records`](Self::discover_dns_records), but yields each page's
+    /// records as soon as it arrives, so a large zone needn't be held in memory at once.
+    pub fn stream_dns_records(
+        &self,
+        zone_id: &str,
+    ) -> impl Stream<Item = Result<DnsRecord, CloudflareError>> + use<> {
+        let url = format!("{}/zones/{}/dns_records", self.base_url, zone_id);
+
+        self.stream_all(
+            url,
+            PaginationStrategy::PageBased,
+            self.page_size,
+            |result| {
+                serde_json::from_value::<Vec<DnsRecord>>(result).map_err(|e| {
+                    CloudflareError::DiscoveryFailed {
+                        resource_type: "cloudflare_dns_record".to_string(),
+                        message: format!("Failed to parse DNS records: {}", e),
+                    }
+                })
+            },
+        )
+    }
+
     pub async fn discover_page_rules(
         &self,
         zone_id: &str,
     ) -> Result<Vec<PageRule>, CloudflareError> {
         let url = format!("{}/zones/{}/pagerules", self.base_url, zone_id);
-        let response = self.client.get(&url).send().await?;
+        let response = self.get(&url).await?;
 
         let status = response.status();
         let body: CloudflareResponse<Vec<PageRule>> =
@@ -227,169 +724,790 @@ impl CloudflareClient {
                     .unwrap_or_else(|| "Unknown API error".to_string()),
             });
         }
+        self.warn_api_messages(&url, &body.messages);
 
         Ok(body.result.unwrap_or_default())
     }
 
-    pub async fn fetch_all_pages<T, F, Fut>(
+    /// Rules from the legacy rate limiting API, which pages like the other list
+    /// endpoints.
+    pub async fn discover_rate_limits(
         &self,
-        base_url: &str,
-        page_size: u32,
-        parse_fn: F,
-    ) -> Result<Vec<T>, CloudflareError>
-    where
-        F: Fn(serde_json::Value) -> Fut,
-        Fut: std::future::Future<Output = Result<Vec<T>, CloudflareError>>,
-    {
-        let mut all_results = Vec::new();
-        let mut page = 1u32;
+        zone_id: &str,
+    ) -> Result<Vec<RateLimit>, CloudflareError> {
+        let url = format!("{}/zones/{}/rate_limits", self.base_url, zone_id);
 
-        loop {
-            let url = format!("{}?page={}&per_page={}", base_url, page, page_size);
-            let response = self.client.get(&url).send().await?;
+        self.fetch_all_pages(&url, self.page_size, |result| async move {
+            serde_json::from_value::<Vec<RateLimit>>(result).map_err(|e| {
+                CloudflareError::DiscoveryFailed {
+                    resource_type: "cloudflare_rate_limit".to_string(),
+                    message: format!("Failed to parse rate limits: {}", e),
+                }
+            })
+        })
+        .await
+    }
 
-            let body: serde_json::Value =
-                response.json().await.map_err(|e| CloudflareError::Api {
-                    status: 0,
-                    message: format!("Failed to parse response: {}", e),
-                })?;
+    pub async fn discover_load_balancers(
+        &self,
+        zone_id: &str,
+    ) -> Result<Vec<LoadBalancer>, CloudflareError> {
+        let url = format!("{}/zones/{}/load_balancers", self.base_url, zone_id);
 
-            let success = body
-                .get("success")
-                .and_then(|v| v.as_bool())
-                .unwrap_or(false);
-
-            if !success {
-                let error_msg = body
-                    .get("errors")
-                    .and_then(|e| e.as_array())
-                    .and_then(|arr| arr.first())
-                    .and_then(|e| e.get("message"))
-                    .and_then(|m| m.as_str())
-                    .unwrap_or("Unknown error")
-                    .to_string();
+        self.fetch_all_pages(&url, self.page_size, |result| async move {
+            serde_json::from_value::<Vec<LoadBalancer>>(result).map_err(|e| {
+                CloudflareError::DiscoveryFailed {
+                    resource_type: "cloudflare_load_balancer".to_string(),
+                    message: format!("Failed to parse load balancers: {}", e),
+                }
+            })
+        })
+        .await
+    }
 
-                return Err(CloudflareError::Api {
-                    status: 0,
-                    message: error_msg,
-                });
-            }
+    /// Standalone health checks, which page like the other list endpoints.
+    pub async fn discover_health_checks(
+        &self,
+        zone_id: &str,
+    ) -> Result<Vec<HealthCheck>, CloudflareError> {
+        let url = format!("{}/zones/{}/healthchecks", self.base_url, zone_id);
 
-            let page_results = parse_fn(body["result"].clone()).await?;
-            let count = page_results.len();
-            all_results.extend(page_results);
+        self.fetch_all_pages(&url, self.page_size, |result| async move {
+            serde_json::from_value::<Vec<HealthCheck>>(result).map_err(|e| {
+                CloudflareError::DiscoveryFailed {
+                    resource_type: "cloudflare_healthcheck".to_string(),
+                    message: format!("Failed to parse health checks: {}", e),
+                }
+            })
+        })
+        .await
+    }
 
-            let total_count = body
-                .get("result_info")
-                .and_then(|ri| ri.get("total_count"))
-                .and_then(|tc| tc.as_u64())
-                .unwrap_or(0) as u32;
+    /// Spectrum applications on the zone. Zones without Spectrum answer 404, which is
+    /// treated as having none rather than as a failure.
+    pub async fn discover_spectrum_apps(
+        &self,
+        zone_id: &str,
+    ) -> Result<Vec<SpectrumApp>, CloudflareError> {
+        let url = format!("{}/zones/{}/spectrum/apps", self.base_url, zone_id);
 
-            if page * page_size >= total_count || count == 0 {
-                break;
+        let result = self
+            .fetch_all_pages(&url, self.page_size, |result| async move {
+                serde_json::from_value::<Vec<SpectrumApp>>(result).map_err(|e| {
+                    CloudflareError::DiscoveryFailed {
+                        resource_type: "cloudflare_spectrum_application".to_string(),
+                        message: format!("Failed to parse Spectrum applications: {}", e),
+                    }
+                })
+            })
+            .await;
+        match result {
+            Err(
+                CloudflareError::Api { status: 404, .. }
+                | CloudflareError::InvalidResponse { status: 404, .. },
+            ) => {
+                tracing::debug!(zone_id, "Spectrum not enabled on the zone");
+                Ok(Vec::new())
             }
-
-            page += 1;
+            result => result,
         }
+    }
 
-        Ok(all_results)
+    /// Pools are account-scoped and shared by every load balancer in the account.
+    pub async fn discover_load_balancer_pools(
+        &self,
+        account_id: &str,
+    ) -> Result<Vec<LoadBalancerPool>, CloudflareError> {
+        let url = format!(
+            "{}/accounts/{}/load_balancers/pools",
+            self.base_url, account_id
+        );
+
+        self.fetch_all_pages(&url, self.page_size, |result| async move {
+            serde_json::from_value::<Vec<LoadBalancerPool>>(result).map_err(|e| {
+                CloudflareError::DiscoveryFailed {
+                    resource_type: "cloudflare_load_balancer_pool".to_string(),
+                    message: format!("Failed to parse load balancer pools: {}", e),
+                }
+            })
+        })
+        .await
     }
 
-    pub async fn discover_rulesets(
+    /// Origin CA certificates live under `/certificates` rather than the zone, filtered
+    /// to the zone by the `zone_id` query parameter.
+    pub async fn discover_origin_certificates(
         &self,
         zone_id: &str,
-        phases: &[&str],
-    ) -> Result<Vec<Ruleset>, CloudflareError> {
-        let url = format!("{}/zones/{}/rulesets", self.base_url, zone_id);
+    ) -> Result<Vec<OriginCertificate>, CloudflareError> {
+        let url = format!("{}/certificates?zone_id={}", self.base_url, zone_id);
 
-        let all_rulesets = self
-            .fetch_all_cursors(&url, DEFAULT_PAGE_SIZE, |result| async move {
-                serde_json::from_value::<Vec<Ruleset>>(result).map_err(|e| {
+        self.fetch_all_pages(&url, self.page_size, |result| async move {
+            serde_json::from_value::<Vec<OriginCertificate>>(result).map_err(|e| {
+                CloudflareError::DiscoveryFailed {
+                    resource_type: "cloudflare_origin_ca_certificate".to_string(),
+                    message: format!("Failed to parse origin certificates: {}", e),
+                }
+            })
+        })
+        .await
+    }
+
+    /// Certificates uploaded for the zone's edge; Cloudflare-issued ones aren't listed.
+    pub async fn discover_custom_certificates(
+        &self,
+        zone_id: &str,
+    ) -> Result<Vec<CustomCertificate>, CloudflareError> {
+        let url = format!("{}/zones/{}/custom_certificates", self.base_url, zone_id);
+
+        self.fetch_all_pages(
+            &url,
+            self.page_size.min(CUSTOM_CERTIFICATES_PAGE_SIZE),
+            |result| async move {
+                serde_json::from_value::<Vec<CustomCertificate>>(result).map_err(|e| {
                     CloudflareError::DiscoveryFailed {
-                        resource_type: "cloudflare_ruleset".to_string(),
-                        message: format!("Failed to parse rulesets: {}", e),
+                        resource_type: "cloudflare_custom_ssl".to_string(),
+                        message: format!("Failed to parse custom certificates: {}", e),
                     }
                 })
-            })
-            .await?;
-
-        Ok(all_rulesets
-            .into_iter()
-            .filter(|r| phases.contains(&r.phase.as_str()))
-            .collect())
+            },
+        )
+        .await
     }
 
-    pub async fn fetch_all_cursors<T, F, Fut>(
+    /// Workers routes aren't paginated; the endpoint returns every route in the zone.
+    pub async fn discover_worker_routes(
         &self,
-        base_url: &str,
-        page_size: u32,
-        parse_fn: F,
-    ) -> Result<Vec<T>, CloudflareError>
-    where
-        F: Fn(serde_json::Value) -> Fut,
-        Fut: std::future::Future<Output = Result<Vec<T>, CloudflareError>>,
-    {
-        let mut all_results = Vec::new();
-        let mut cursor: Option<String> = None;
-
-        loop {
-            let url = match &cursor {
-                Some(c) => format!("{}?per_page={}&cursor={}", base_url, page_size, c),
-                None => format!("{}?per_page={}", base_url, page_size),
-            };
-
-            let response = self.client.get(&url).send().await?;
+        zone_id: &str,
+    ) -> Result<Vec<WorkerRoute>, CloudflareError> {
+        let url = format!("{}/zones/{}/workers/routes", self.base_url, zone_id);
+        let response = self.get(&url).await?;
 
-            let body: serde_json::Value =
-                response.json().await.map_err(|e| CloudflareError::Api {
-                    status: 0,
-                    message: format!("Failed to parse response: {}", e),
+        let status = response.status();
+        let body: CloudflareResponse<Vec<WorkerRoute>> =
+            response
+                .json()
+                .await
+                .map_err(|e| CloudflareError::DiscoveryFailed {
+                    resource_type: "cloudflare_workers_route".to_string(),
+                    message: format!("Failed to parse Workers routes response: {}", e),
                 })?;
 
-            let success = body
-                .get("success")
-                .and_then(|v| v.as_bool())
-                .unwrap_or(false);
-
-            if !success {
-                let error_msg = body
-                    .get("errors")
-                    .and_then(|e| e.as_array())
-                    .and_then(|arr| arr.first())
-                    .and_then(|e| e.get("message"))
-                    .and_then(|m| m.as_str())
-                    .unwrap_or("Unknown error")
-                    .to_string();
+        if !body.success {
+            return Err(CloudflareError::Api {
+                status: status.as_u16(),
+                message: body
+                    .errors
+                    .first()
+                    .map(|e| e.message.clone())
+                    .unwrap_or_else(|| "Unknown API error".to_string()),
+            });
+        }
+        self.warn_api_messages(&url, &body.messages);
 
-                return Err(CloudflareError::Api {
-                    status: 0,
-                    message: error_msg,
-                });
-            }
+        Ok(body.result.unwrap_or_default())
+    }
 
-            let result_value = body
-                .get("result")
-                .cloned()
-                .unwrap_or(serde_json::Value::Array(vec![]));
-            let page_results = parse_fn(result_value).await?;
-            all_results.extend(page_results);
+    /// Every setting of the zone, changed or not; the endpoint isn't paginated.
+    pub async fn discover_zone_settings(
+        &self,
+        zone_id: &str,
+    ) -> Result<Vec<ZoneSetting>, CloudflareError> {
+        let url = format!("{}/zones/{}/settings", self.base_url, zone_id);
+        let response = self.get(&url).await?;
 
-            let next_cursor = body
-                .get("result_info")
-                .and_then(|ri| ri.get("cursors"))
-                .and_then(|c| c.get("after"))
-                .and_then(|a| a.as_str())
+        let status = response.status();
+        let body: CloudflareResponse<Vec<ZoneSetting>> =
+            response
+                .json()
+                .await
+                .map_err(|e| CloudflareError::DiscoveryFailed {
+                    resource_type: "cloudflare_zone_setting".to_string(),
+                    message: format!("Failed to parse zone settings response: {}", e),
+                })?;
+
+        if !body.success {
+            return Err(CloudflareError::Api {
+                status: status.as_u16(),
+                message: body
+                    .errors
+                    .first()
+                    .map(|e| e.message.clone())
+                    .unwrap_or_else(|| "Unknown API error".to_string()),
+            });
+        }
+        self.warn_api_messages(&url, &body.messages);
+
+        Ok(body.result.unwrap_or_default())
+    }
+
+    /// IP, hostname, ASN and redirect lists are account-scoped; the endpoint returns
+    /// every list in one response.
+    pub async fn discover_account_lists(
+        &self,
+        account_id: &str,
+    ) -> Result<Vec<AccountList>, CloudflareError> {
+        let url = format!("{}/accounts/{}/rules/lists", self.base_url, account_id);
+        let response = self.get(&url).await?;
+
+        let status = response.status();
+        let body: CloudflareResponse<Vec<AccountList>> =
+            response
+                .json()
+                .await
+                .map_err(|e| CloudflareError::DiscoveryFailed {
+                    resource_type: "cloudflare_list".to_string(),
+                    message: format!("Failed to parse lists response: {}", e),
+                })?;
+
+        if !body.success {
+            return Err(CloudflareError::Api {
+                status: status.as_u16(),
+                message: body
+                    .errors
+                    .first()
+                    .map(|e| e.message.clone())
+                    .unwrap_or_else(|| "Unknown API error".to_string()),
+            });
+        }
+        self.warn_api_messages(&url, &body.messages);
+
+        Ok(body.result.unwrap_or_default())
+    }
+
+    /// Custom hostnames pointed at the zone by SaaS customers, paged like the other list
+    /// endpoints.
+    pub async fn discover_custom_hostnames(
+        &self,
+        zone_id: &str,
+    ) -> Result<Vec<CustomHostname>, CloudflareError> {
+        let url = format!("{}/zones/{}/custom_hostnames", self.base_url, zone_id);
+
+        self.fetch_all_pages(&url, self.page_size, |result| async move {
+            serde_json::from_value::<Vec<CustomHostname>>(result).map_err(|e| {
+                CloudflareError::DiscoveryFailed {
+                    resource_type: "cloudflare_custom_hostname".to_string(),
+                    message: format!("Failed to parse custom hostnames: {}", e),
+                }
+            })
+        })
+        .await
+    }
+
+    /// Turnstile widgets are account-scoped and page like the other list endpoints. The
+    /// secret each one carries is never parsed out of the response.
+    pub async fn discover_turnstile_widgets(
+        &self,
+        account_id: &str,
+    ) -> Result<Vec<TurnstileWidget>, CloudflareError> {
+        let url = format!(
+            "{}/accounts/{}/challenges/widgets",
+            self.base_url, account_id
+        );
+
+        self.fetch_all_pages(&url, self.page_size, |result| async move {
+            serde_json::from_value::<Vec<TurnstileWidget>>(result).map_err(|e| {
+                CloudflareError::DiscoveryFailed {
+                    resource_type: "cloudflare_turnstile_widget".to_string(),
+                    message: format!("Failed to parse Turnstile widgets: {}", e),
+                }
+            })
+        })
+        .await
+    }
+
+    /// Pages projects are account-scoped and page like the other list endpoints.
+    pub async fn discover_pages_projects(
+        &self,
+        account_id: &str,
+    ) -> Result<Vec<PagesProject>, CloudflareError> {
+        let url = format!("{}/accounts/{}/pages/projects", self.base_url, account_id);
+
+        self.fetch_all_pages(&url, self.page_size, |result| async move {
+            serde_json::from_value::<Vec<PagesProject>>(result).map_err(|e| {
+                CloudflareError::DiscoveryFailed {
+                    resource_type: "cloudflare_pages_project".to_string(),
+                    message: format!("Failed to parse Pages projects: {}", e),
+                }
+            })
+        })
+        .await
+    }
+
+    /// R2 buckets are account-scoped. The listing wraps them in `result.buckets` and names
+    /// the next page in `result_info.cursor`, so it can't use the shared paging helpers.
+    pub async fn discover_r2_buckets(
+        &self,
+        account_id: &str,
+    ) -> Result<Vec<R2Bucket>, CloudflareError> {
+        let url = format!("{}/accounts/{}/r2/buckets", self.base_url, account_id);
+        let mut buckets = Vec::new();
+        let mut next = None;
+        let mut pages = 0u32;
+        let mut seen = HashSet::new();
+
+        loop {
+            let request_url = match &next {
+                Some(PageToken::Cursor(cursor)) => {
+                    format!(
+                        "{}?per_page={}&cursor={}",
+                        url,
+                        self.page_size,
+                        urlencoding::encode(cursor)
+                    )
+                }
+                _ => format!("{}?per_page={}", url, self.page_size),
+            };
+            let response = self.get(&request_url).await?;
+
+            let status = response.status();
+            let body: CloudflareResponse<R2BucketList> =
+                response
+                    .json()
+                    .await
+                    .map_err(|e| CloudflareError::DiscoveryFailed {
+                        resource_type: "cloudflare_r2_bucket".to_string(),
+                        message: format!("Failed to parse R2 buckets response: {}", e),
+                    })?;
+
+            if !body.success {
+                return Err(CloudflareError::Api {
+                    status: status.as_u16(),
+                    message: body
+                        .errors
+                        .first()
+                        .map(|e| e.message.clone())
+                        .unwrap_or_else(|| "Unknown API error".to_string()),
+                });
+            }
+            self.warn_api_messages(&url, &body.messages);
+
+            buckets.extend(body.result.unwrap_or_default().buckets);
+            pages += 1;
+            self.report_progress(&url, pages, buckets.len());
+
+            let cursor = body
+                .result_info
+                .and_then(|ri| ri.cursor)
+                .filter(|c| !c.is_empty())
+                .map(PageToken::Cursor);
+            next = self.next_page(&url, pages, &mut seen, cursor)?;
+            if next.is_none() {
+                break;
+            }
+        }
+
+        Ok(buckets)
+    }
+
+    /// Workers scripts are account-scoped; like routes, the endpoint returns every
+    /// script in one response.
+    pub async fn discover_worker_scripts(
+        &self,
+        account_id: &str,
+    ) -> Result<Vec<WorkerScript>, CloudflareError> {
+        let url = format!("{}/accounts/{}/workers/scripts", self.base_url, account_id);
+        let response = self.get(&url).await?;
+
+        let status = response.status();
+        let body: CloudflareResponse<Vec<WorkerScript>> =
+            response
+                .json()
+                .await
+                .map_err(|e| CloudflareError::DiscoveryFailed {
+                    resource_type: "cloudflare_workers_script".to_string(),
+                    message: format!("Failed to parse Workers scripts response: {}", e),
+                })?;
+
+        if !body.success {
+            return Err(CloudflareError::Api {
+                status: status.as_u16(),
+                message: body
+                    .errors
+                    .first()
+                    .map(|e| e.message.clone())
+                    .unwrap_or_else(|| "Unknown API error".to_string()),
+            });
+        }
+        self.warn_api_messages(&url, &body.messages);
+
+        Ok(body.result.unwrap_or_default())
+    }
+
+    pub async fn fetch_all_pages<T, F, Fut>(
+        &self,
+        base_url: &str,
+        page_size: u32,
+        parse_fn: F,
+    ) -> Result<Vec<T>, CloudflareError>
+    where
+        F: Fn(serde_json::Value) -> Fut,
+        Fut: std::future::Future<Output = Result<Vec<T>, CloudflareError>>,
+    {
+        self.fetch_all(base_url, PaginationStrategy::PageBased, page_size, parse_fn)
+            .await
+    }
+
+    /// Lists the zone's rulesets in `phases`. Cloudflare-managed rulesets can't be
+    /// imported, so they're left out with a warning unless `include_managed` is set.
+    pub async fn discover_rulesets(
+        &self,
+        zone_id: &str,
+        phases: &[&str],
+        include_managed: bool,
+    ) -> Result<Vec<Ruleset>, CloudflareError> {
+        let url = format!("{}/zones/{}/rulesets", self.base_url, zone_id);
+
+        let all_rulesets = self
+            .fetch_all_cursors(&url, self.page_size, |result| async move {
+                serde_json::from_value::<Vec<Ruleset>>(result).map_err(|e| {
+                    CloudflareError::DiscoveryFailed {
+                        resource_type: "cloudflare_ruleset".to_string(),
+                        message: format!("Failed to parse rulesets: {}", e),
+                    }
+                })
+            })
+            .await?;
+
+        let in_phases = all_rulesets
+            .into_iter()
+            .filter(|r| phases.contains(&r.phase.as_str()));
+        if include_managed {
+            return Ok(in_phases.collect());
+        }
+
+        let (managed, rulesets): (Vec<Ruleset>, Vec<Ruleset>) =
+            in_phases.partition(Ruleset::is_managed);
+        if !managed.is_empty() {
+            let ids: Vec<&str> = managed.iter().map(|r| r.id.as_str()).collect();
+            tracing::warn!(
+                zone_id,
+                ruleset_ids = %ids.join(", "),
+                "skipping Cloudflare-managed rulesets, which Terraform can't import"
+            );
+        }
+
+        Ok(rulesets)
+    }
+
+    /// Fetches a single ruleset including its `rules` array, which the list endpoint omits.
+    pub async fn get_ruleset(
+        &self,
+        zone_id: &str,
+        ruleset_id: &str,
+    ) -> Result<Ruleset, CloudflareError> {
+        let url = format!(
+            "{}/zones/{}/rulesets/{}",
+            self.base_url, zone_id, ruleset_id
+        );
+        let response = self.get(&url).await?;
+
+        let status = response.status();
+        let body: CloudflareResponse<Ruleset> =
+            response
+                .json()
+                .await
+                .map_err(|e| CloudflareError::DiscoveryFailed {
+                    resource_type: "cloudflare_ruleset".to_string(),
+                    message: format!("Failed to parse ruleset response: {}", e),
+                })?;
+
+        if !body.success {
+            return Err(CloudflareError::Api {
+                status: status.as_u16(),
+                message: body
+                    .errors
+                    .first()
+                    .map(|e| e.message.clone())
+                    .unwrap_or_else(|| "Unknown API error".to_string()),
+            });
+        }
+        self.warn_api_messages(&url, &body.messages);
+
+        body.result.ok_or_else(|| CloudflareError::DiscoveryFailed {
+            resource_type: "cloudflare_ruleset".to_string(),
+            message: format!("Ruleset '{}' response had no result", ruleset_id),
+        })
+    }
+
+    /// The rules of a single ruleset; empty when the ruleset has no `rules` array.
+    pub async fn discover_ruleset_rules(
+        &self,
+        zone_id: &str,
+        ruleset_id: &str,
+    ) -> Result<Vec<RulesetRule>, CloudflareError> {
+        Ok(self.get_ruleset(zone_id, ruleset_id).await?.rules)
+    }
+
+    pub async fn fetch_all_cursors<T, F, Fut>(
+        &self,
+        base_url: &str,
+        page_size: u32,
+        parse_fn: F,
+    ) -> Result<Vec<T>, CloudflareError>
+    where
+        F: Fn(serde_json::Value) -> Fut,
+        Fut: std::future::Future<Output = Result<Vec<T>, CloudflareError>>,
+    {
+        self.fetch_all(
+            base_url,
+            PaginationStrategy::CursorBased,
+            page_size,
+            parse_fn,
+        )
+        .await
+    }
+
+    /// Fetches a single page from the list endpoint at `url`, for callers that want to
+    /// stop early or handle items as they arrive rather than collect every page.
+    ///
+    /// `after` is the previous page's [`next`](PagedResponse::next) token, or `None` for
+    /// the first page. [`PaginationStrategy::SinglePage`] endpoints are requested without
+    /// paging parameters and never have a next page.
+    pub async fn fetch_page(
+        &self,
+        url: &str,
+        strategy: PaginationStrategy,
+        page_size: u32,
+        after: Option<&PageToken>,
+    ) -> Result<PagedResponse<serde_json::Value>, CloudflareError> {
+        let page = match after {
+            Some(PageToken::Page(page)) => *page,
+            _ => 1,
+        };
+        let request_url = match (strategy, after) {
+            (PaginationStrategy::PageBased, _) => {
+                with_query(url, &format!("page={}&per_page={}", page, page_size))
+            }
+            (PaginationStrategy::CursorBased, Some(PageToken::Cursor(cursor))) => with_query(
+                url,
+                &format!(
+                    "per_page={}&cursor={}",
+                    page_size,
+                    urlencoding::encode(cursor)
+                ),
+            ),
+            (PaginationStrategy::CursorBased, _) => {
+                with_query(url, &format!("per_page={}", page_size))
+            }
+            (PaginationStrategy::SinglePage, _) => url.to_string(),
+        };
+
+        let response = self.get(&request_url).await?;
+        let status = response.status().as_u16();
+
+        let mut body = read_json(response).await?;
+
+        let success = body
+            .get("success")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        if !success {
+            let error_msg = body
+                .get("errors")
+                .and_then(|e| e.as_array())
+                .and_then(|arr| arr.first())
+                .and_then(|e| e.get("message"))
+                .and_then(|m| m.as_str())
+                .unwrap_or("Unknown error")
+                .to_string();
+
+            return Err(CloudflareError::Api {
+                status,
+                message: error_msg,
+            });
+        }
+
+        if let Some(messages) = body.get("messages")
+            && let Ok(messages) =
+                serde_json::from_value::<Vec<CloudflareApiMessage>>(messages.clone())
+        {
+            self.warn_api_messages(url, &messages);
+        }
+
+        let items = match body.get_mut("result").map(serde_json::Value::take) {
+            None | Some(serde_json::Value::Null) => Vec::new(),
+            Some(serde_json::Value::Array(items)) => items,
+            Some(_) => {
+                return Err(CloudflareError::Api {
+                    status,
+                    message: "Expected a list in the response result".to_string(),
+                });
+            }
+        };
+
+        let result_info = body.get("result_info");
+        let total_count = result_info
+            .and_then(|ri| ri.get("total_count"))
+            .and_then(|tc| tc.as_u64())
+            .map(|tc| tc as u32);
+
+        let next = match strategy {
+            PaginationStrategy::PageBased => (!items.is_empty()
+                && page * page_size < total_count.unwrap_or(0))
+            .then_some(PageToken::Page(page + 1)),
+            PaginationStrategy::CursorBased => result_info
+                .and_then(|ri| ri.get("cursors"))
+                .and_then(|c| c.get("after"))
+                .and_then(|a| a.as_str())
                 .filter(|s| !s.is_empty())
-                .map(|s| s.to_string());
+                .map(|s| PageToken::Cursor(s.to_string())),
+            PaginationStrategy::SinglePage => None,
+        };
 
-            match next_cursor {
-                Some(c) => cursor = Some(c),
-                None => break,
+        Ok(PagedResponse::new(items, total_count, next.is_some()).with_next(next))
+    }
+
+    /// Follows [`fetch_page`](Self::fetch_page) to the last page, handing each page's
+    /// items to `parse_fn`; paging stops as [`next_page`](Self::next_page) describes.
+    async fn fetch_all<T, F, Fut>(
+        &self,
+        url: &str,
+        strategy: PaginationStrategy,
+        page_size: u32,
+        parse_fn: F,
+    ) -> Result<Vec<T>, CloudflareError>
+    where
+        F: Fn(serde_json::Value) -> Fut,
+        Fut: std::future::Future<Output = Result<Vec<T>, CloudflareError>>,
+    {
+        let mut all_results = Vec::new();
+        let mut next = None;
+        let mut pages = 0u32;
+        let mut seen = HashSet::new();
+
+        loop {
+            let page = self
+                .fetch_page(url, strategy, page_size, next.as_ref())
+                .await?;
+            all_results.extend(parse_fn(serde_json::Value::Array(page.items)).await?);
+            pages += 1;
+            self.report_progress(url, pages, all_results.len());
+
+            next = self.next_page(url, pages, &mut seen, page.next)?;
+            if next.is_none() {
+                break;
             }
         }
 
         Ok(all_results)
     }
+
+    /// The streaming counterpart of [`fetch_all`](Self::fetch_all): requests the next
+    /// page only once the previous page's items have been consumed, and stops at the
+    /// first error.
+    fn stream_all<T, F>(
+        &self,
+        url: String,
+        strategy: PaginationStrategy,
+        page_size: u32,
+        parse_fn: F,
+    ) -> impl Stream<Item = Result<T, CloudflareError>> + use<T, F>
+    where
+        T: 'static,
+        F: Fn(serde_json::Value) -> Result<Vec<T>, CloudflareError> + 'static,
+    {
+        struct State<F> {
+            client: CloudflareClient,
+            url: String,
+            parse_fn: F,
+            next: Option<PageToken>,
+            pages: u32,
+            items: usize,
+            seen: HashSet<PageToken>,
+            done: bool,
+        }
+
+        let state = State {
+            client: self.clone(),
+            url,
+            parse_fn,
+            next: None,
+            pages: 0,
+            items: 0,
+            seen: HashSet::new(),
+            done: false,
+        };
+        stream::try_unfold(state, move |mut state| async move {
+            if state.done {
+                return Ok::<_, CloudflareError>(None);
+            }
+            let page = state
+                .client
+                .fetch_page(&state.url, strategy, page_size, state.next.as_ref())
+                .await?;
+            let items = (state.parse_fn)(serde_json::Value::Array(page.items))?;
+            state.pages += 1;
+            state.items += items.len();
+            state
+                .client
+                .report_progress(&state.url, state.pages, state.items);
+
+            state.next =
+                state
+                    .client
+                    .next_page(&state.url, state.pages, &mut state.seen, page.next)?;
+            state.done = state.next.is_none();
+            Ok(Some((
+                stream::iter(items.into_iter().map(Ok::<T, CloudflareError>)),
+                state,
+            )))
+        })
+        .try_flatten()
+    }
+}
+
+impl CloudflareClient {
+    /// Logs the notices, e.g. deprecations, that Cloudflare attaches to a successful
+    /// response to `url`.
+    fn warn_api_messages(&self, url: &str, messages: &[CloudflareApiMessage]) {
+        let path = url.strip_prefix(&self.base_url).unwrap_or(url);
+        for message in messages {
+            tracing::warn!(path, code = ?message.code, "{}", message.message);
+        }
+    }
+
+    /// The token to request after `pages` pages, or `None` after the last one. Fails
+    /// rather than looping forever when the server hands out a token it already gave, or
+    /// keeps paging past the client's [`max pages`](Self::with_max_pages).
+    fn next_page(
+        &self,
+        url: &str,
+        pages: u32,
+        seen: &mut HashSet<PageToken>,
+        next: Option<PageToken>,
+    ) -> Result<Option<PageToken>, CloudflareError> {
+        match next {
+            Some(token) if !seen.insert(token.clone()) => {
+                Err(self
+                    .pagination_anomaly(url, format!("returned the page token {:?} twice", token)))
+            }
+            Some(_) if pages >= self.max_pages => {
+                Err(self.pagination_anomaly(url, format!("still had more pages after {}", pages)))
+            }
+            next => Ok(next),
+        }
+    }
+
+    /// NOTE: Every page came back successfully; it's the paging itself that's broken.
+    fn pagination_anomaly(&self, url: &str, problem: String) -> CloudflareError {
+        let path = url.strip_prefix(&self.base_url).unwrap_or(url);
+        CloudflareError::Api {
+            status: 200,
+            message: format!("pagination of {} {}; stopping", path, problem),
+        }
+    }
+
+    fn report_progress(&self, url: &str, pages: u32, items: usize) {
+        if let Some(callback) = &self.progress {
+            callback(&PageProgress {
+                path: url.strip_prefix(&self.base_url).unwrap_or(url).to_string(),
+                pages,
+                items,
+            });
+        }
+    }
 }
 
 impl std::fmt::Debug for CloudflareClient {
@@ -425,6 +1543,179 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_with_timeouts_sets_connect_and_read_separately() {
+        let client = CloudflareClient::new("test_token".to_string())
+            .unwrap()
+            .with_timeouts(Some(Duration::from_secs(2)), Some(Duration::from_secs(90)))
+            .unwrap();
+
+        assert_eq!(client.connect_timeout(), Some(Duration::from_secs(2)));
+        assert_eq!(client.read_timeout(), Some(Duration::from_secs(90)));
+    }
+
+    #[test]
+    fn test_client_has_default_request_timeout() {
+        let client = CloudflareClient::new("test_token".to_string()).unwrap();
+
+        assert_eq!(client.connect_timeout(), None);
+        assert_eq!(client.read_timeout(), Some(DEFAULT_REQUEST_TIMEOUT));
+    }
+
+    #[test]
+    fn test_with_timeouts_keeps_unset_values() {
+        let client = CloudflareClient::new("test_token".to_string())
+            .unwrap()
+            .with_timeouts(Some(Duration::from_secs(2)), None)
+            .unwrap()
+            .with_timeout(Duration::from_secs(5))
+            .unwrap();
+
+        assert_eq!(client.connect_timeout(), Some(Duration::from_secs(2)));
+        assert_eq!(client.read_timeout(), Some(Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn test_retry_on_survives_with_timeouts() {
+        let client = CloudflareClient::new("test_token".to_string())
+            .unwrap()
+            .with_retry_on(vec![409])
+            .with_timeouts(Some(Duration::from_secs(2)), None)
+            .unwrap();
+
+        assert_eq!(client.retry_on(), &[409]);
+    }
+
+    #[test]
+    fn test_retry_delay_doubles() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.delay(0), DEFAULT_RETRY_BASE_DELAY);
+        assert_eq!(policy.delay(2), DEFAULT_RETRY_BASE_DELAY * 4);
+    }
+
+    #[test]
+    fn test_retry_jitter_stays_within_half_the_delay() {
+        let policy = RetryPolicy::default();
+        for retries in 0..3 {
+            let jittered = policy.delay_with_jitter(retries);
+            let base = policy.delay(retries);
+            assert!(jittered >= base);
+            assert!(jittered <= base + base / 2);
+        }
+    }
+
+    #[test]
+    fn test_with_retry_config_overrides_defaults() {
+        let client = CloudflareClient::new("test_token".to_string())
+            .unwrap()
+            .with_retry_config(5, Duration::from_millis(10));
+
+        assert_eq!(client.retry.max_retries, 5);
+        assert_eq!(client.retry.base_delay, Duration::from_millis(10));
+        assert_eq!(client.retry_on(), DEFAULT_RETRY_ON);
+    }
+
+    #[test]
+    fn test_request_ids_are_unique() {
+        let first = next_request_id();
+        let second = next_request_id();
+        assert!(first.starts_with("tia-"));
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn test_with_base_url_trims_trailing_slash() {
+        let client = CloudflareClient::with_base_url(
+            "test_token".to_string(),
+            "https://gateway.internal/cloudflare/".to_string(),
+        )
+        .unwrap();
+
+        assert_eq!(client.base_url, "https://gateway.internal/cloudflare");
+    }
+
+    #[test]
+    fn test_with_base_url_rejects_url_without_scheme() {
+        let result = CloudflareClient::with_base_url(
+            "test_token".to_string(),
+            "api.cloudflare.com/client/v4".to_string(),
+        );
+
+        assert!(matches!(
+            result,
+            Err(CloudflareError::InvalidBaseUrl { ref url, .. }) if url == "api.cloudflare.com/client/v4"
+        ));
+    }
+
+    #[test]
+    fn test_with_base_url_rejects_non_http_scheme() {
+        let result = CloudflareClient::with_base_url(
+            "test_token".to_string(),
+            "ftp://gateway.internal".to_string(),
+        );
+
+        let Err(CloudflareError::InvalidBaseUrl { message, .. }) = result else {
+            panic!("expected InvalidBaseUrl, got {:?}", result);
+        };
+        assert!(message.contains("'ftp'"), "message: {}", message);
+    }
+
+    #[test]
+    fn test_with_query_extends_an_existing_query_string() {
+        assert_eq!(
+            with_query("https://api/zones", "per_page=1"),
+            "https://api/zones?per_page=1"
+        );
+        assert_eq!(
+            with_query("https://api/certificates?zone_id=z1", "per_page=1"),
+            "https://api/certificates?zone_id=z1&per_page=1"
+        );
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_new_reads_base_url_from_env() {
+        let backup = std::env::var(API_BASE_URL_ENV).ok();
+        unsafe { std::env::set_var(API_BASE_URL_ENV, "http://gateway.internal/v4") };
+        let from_env = CloudflareClient::new("test_token".to_string()).unwrap();
+        let explicit = CloudflareClient::with_base_url(
+            "test_token".to_string(),
+            "http://127.0.0.1:8080".to_string(),
+        )
+        .unwrap();
+        unsafe { std::env::set_var(API_BASE_URL_ENV, "  ") };
+        let blank = CloudflareClient::new("test_token".to_string()).unwrap();
+        unsafe {
+            match backup {
+                Some(url) => std::env::set_var(API_BASE_URL_ENV, url),
+                None => std::env::remove_var(API_BASE_URL_ENV),
+            }
+        }
+
+        assert_eq!(from_env.base_url, "http://gateway.internal/v4");
+        assert_eq!(explicit.base_url, "http://127.0.0.1:8080");
+        assert_eq!(blank.base_url, CLOUDFLARE_API_BASE);
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_new_rejects_invalid_base_url_from_env() {
+        let backup = std::env::var(API_BASE_URL_ENV).ok();
+        unsafe { std::env::set_var(API_BASE_URL_ENV, "not a url") };
+        let result = CloudflareClient::new("test_token".to_string());
+        unsafe {
+            match backup {
+                Some(url) => std::env::set_var(API_BASE_URL_ENV, url),
+                None => std::env::remove_var(API_BASE_URL_ENV),
+            }
+        }
+
+        assert!(matches!(
+            result,
+            Err(CloudflareError::InvalidBaseUrl { .. })
+        ));
+    }
+
     #[test]
     fn test_client_is_clone() {
         let client = CloudflareClient::new("test_token".to_string()).unwrap();
@@ -1,5 +1,14 @@
 use thiserror::Error;
 
+use crate::providers::ProviderError;
+
+/// How many characters of an unparseable body [`CloudflareError::InvalidResponse`] keeps.
+const SNIPPET_CHARS: usize = 200;
+
+/// Runs of token characters at least this long are redacted from snippets; Cloudflare
+/// API tokens are 40 characters, while zone and account ids are 32.
+const TOKEN_LIKE_CHARS: usize = 40;
+
 #[derive(Debug, Error)]
 pub enum CloudflareError {
     #[error("authentication failed: {message}")]
@@ -9,18 +18,33 @@ pub enum CloudflareError {
     Api { status: u16, message: String },
 
     #[error("network error: {0}")]
-    Network(#[from] reqwest::Error),
+    Network(reqwest::Error),
+
+    #[error("request timed out: {0}")]
+    Timeout(reqwest::Error),
 
-    #[allow(dead_code)] // NOTE: TBA in future iterations (retry logic)
     #[error("rate limited, retry after {retry_after}s")]
     RateLimited { retry_after: u64 },
 
+    /// A body that isn't the JSON the API returns, e.g. an HTML page from a proxy.
+    #[error("invalid response ({status}): {snippet}")]
+    InvalidResponse { status: u16, snippet: String },
+
     #[error("zone not found: '{zone}'")]
     ZoneNotFound { zone: String },
 
     #[error("zone lookup failed: {message}")]
     ZoneLookupFailed { message: String },
 
+    #[error("invalid page size {page_size}: must be at least 1")]
+    InvalidPageSize { page_size: u32 },
+
+    #[error("invalid API base URL '{url}': {message}")]
+    InvalidBaseUrl { url: String, message: String },
+
+    #[error("invalid max concurrency {max_concurrency}: must be at least 1")]
+    InvalidMaxConcurrency { max_concurrency: usize },
+
     #[error("discovery failed for {resource_type}: {message}")]
     DiscoveryFailed {
         resource_type: String,
@@ -28,9 +52,70 @@ pub enum CloudflareError {
     },
 }
 
-impl From<CloudflareError> for crate::providers::ProviderError {
+impl CloudflareError {
+    /// An [`InvalidResponse`](Self::InvalidResponse) keeping the start of `body`, with
+    /// token-like strings redacted first so a cut can't leave part of one behind.
+    pub fn invalid_response(status: u16, body: &str) -> Self {
+        CloudflareError::InvalidResponse {
+            status,
+            snippet: redact_tokens(body).chars().take(SNIPPET_CHARS).collect(),
+        }
+    }
+}
+
+/// Replaces every run of at least [`TOKEN_LIKE_CHARS`] letters, digits, `-` and `_`
+/// with `[REDACTED]`.
+fn redact_tokens(text: &str) -> String {
+    let is_token_char = |c: char| c.is_ascii_alphanumeric() || c == '-' || c == '_';
+    let mut redacted = String::with_capacity(text.len());
+    // NOTE: Each piece is a run of token characters followed by at most one other character.
+    for piece in text.split_inclusive(|c: char| !is_token_char(c)) {
+        let run = piece.trim_end_matches(|c: char| !is_token_char(c));
+        if run.len() >= TOKEN_LIKE_CHARS {
+            redacted.push_str("[REDACTED]");
+        } else {
+            redacted.push_str(run);
+        }
+        redacted.push_str(&piece[run.len()..]);
+    }
+    redacted
+}
+
+/// Timeouts get their own variant so they can be told apart from other transport
+/// failures.
+impl From<reqwest::Error> for CloudflareError {
+    fn from(err: reqwest::Error) -> Self {
+        if err.is_timeout() {
+            CloudflareError::Timeout(err)
+        } else {
+            CloudflareError::Network(err)
+        }
+    }
+}
+
+/// Maps errors callers may want to act on to the matching structured variant; the
+/// rest keep their message under `ProviderError::Cloudflare`.
+impl From<CloudflareError> for ProviderError {
     fn from(err: CloudflareError) -> Self {
-        crate::providers::ProviderError::Cloudflare(err.to_string())
+        match err {
+            CloudflareError::Auth { message }
+            | CloudflareError::Api {
+                status: 401,
+                message,
+            } => ProviderError::Auth(message),
+            CloudflareError::Api {
+                status: 403,
+                message,
+            } => ProviderError::PermissionDenied(message),
+            CloudflareError::RateLimited { retry_after } => {
+                ProviderError::RateLimited { retry_after }
+            }
+            CloudflareError::ZoneNotFound { zone } => ProviderError::NotFound {
+                kind: "zone".to_string(),
+                name: zone,
+            },
+            other => ProviderError::Cloudflare(other.to_string()),
+        }
     }
 }
 
@@ -80,13 +165,79 @@ mod tests {
         let cf_err = CloudflareError::Auth {
             message: "test error".to_string(),
         };
-        let provider_err: crate::providers::ProviderError = cf_err.into();
+        let provider_err: ProviderError = cf_err.into();
 
+        assert!(matches!(&provider_err, ProviderError::Auth(msg) if msg == "test error"));
+        assert_eq!(provider_err.to_string(), "authentication error: test error");
+    }
+
+    #[test]
+    fn test_conversion_maps_structured_variants() {
+        let rate_limited: ProviderError = CloudflareError::RateLimited { retry_after: 30 }.into();
         assert!(matches!(
-            provider_err,
-            crate::providers::ProviderError::Cloudflare(_)
+            rate_limited,
+            ProviderError::RateLimited { retry_after: 30 }
+        ));
+        assert_eq!(rate_limited.to_string(), "rate limited, retry after 30s");
+
+        let not_found: ProviderError = CloudflareError::ZoneNotFound {
+            zone: "example.com".to_string(),
+        }
+        .into();
+        assert!(matches!(&not_found, ProviderError::NotFound { kind, .. } if kind == "zone"));
+        assert_eq!(not_found.to_string(), "zone not found: 'example.com'");
+
+        let forbidden: ProviderError = CloudflareError::Api {
+            status: 403,
+            message: "Forbidden".to_string(),
+        }
+        .into();
+        assert!(matches!(forbidden, ProviderError::PermissionDenied(_)));
+        assert_eq!(forbidden.to_string(), "permission denied: Forbidden");
+
+        let unauthorized: ProviderError = CloudflareError::Api {
+            status: 401,
+            message: "Invalid token".to_string(),
+        }
+        .into();
+        assert!(matches!(unauthorized, ProviderError::Auth(_)));
+    }
+
+    #[test]
+    fn test_conversion_keeps_other_errors_as_messages() {
+        let server_error: ProviderError = CloudflareError::Api {
+            status: 500,
+            message: "Internal Server Error".to_string(),
+        }
+        .into();
+
+        assert!(matches!(server_error, ProviderError::Cloudflare(_)));
+        assert_eq!(
+            server_error.to_string(),
+            "cloudflare error: API error (500): Internal Server Error"
+        );
+    }
+
+    #[test]
+    fn test_invalid_response_truncates_and_redacts_tokens() {
+        let token = "Zx9_abcdefghijklmnopqrstuvwxyz0123456789-AB";
+        let body = format!(
+            "<html><body>Bearer {} rejected by zone 023e105f4ecef8ad9ca31a8372d0c353{}</body></html>",
+            token,
+            "!".repeat(300)
+        );
+
+        let err = CloudflareError::invalid_response(502, &body);
+
+        let CloudflareError::InvalidResponse { status, snippet } = &err else {
+            panic!("Expected InvalidResponse, got {:?}", err);
+        };
+        assert_eq!(*status, 502);
+        assert_eq!(snippet.chars().count(), 200);
+        assert!(snippet.starts_with(
+            "<html><body>Bearer [REDACTED] rejected by zone 023e105f4ecef8ad9ca31a8372d0c353!"
         ));
-        assert!(provider_err.to_string().contains("authentication failed"));
+        assert!(!err.to_string().contains(token));
     }
 
     #[test]
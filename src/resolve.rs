@@ -0,0 +1,155 @@
+//! Optional DNS resolution of CNAME targets for auditing.
+//!
+//! Resolved addresses are added to a CNAME record's metadata as `resolved_addresses`.
+//! Targets that fail to resolve are logged and left alone, so enrichment never fails
+//! discovery.
+
+use std::collections::HashMap;
+use std::io;
+use std::net::IpAddr;
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use crate::resource::Resource;
+
+/// Pause between consecutive lookups so large zones don't flood the resolver.
+pub const DEFAULT_LOOKUP_INTERVAL: Duration = Duration::from_millis(50);
+
+#[async_trait]
+pub trait Resolver: Send + Sync {
+    async fn resolve(&self, host: &str) -> io::Result<Vec<IpAddr>>;
+}
+
+/// Resolves through the operating system's resolver.
+#[derive(Debug, Default)]
+pub struct SystemResolver;
+
+#[async_trait]
+impl Resolver for SystemResolver {
+    async fn resolve(&self, host: &str) -> io::Result<Vec<IpAddr>> {
+        let addrs = tokio::net::lookup_host((host, 0)).await?;
+        Ok(addrs.map(|addr| addr.ip()).collect())
+    }
+}
+
+/// Resolves the `target` of every CNAME record in `resources`, at most one lookup per
+/// distinct target and `interval` apart.
+pub async fn enrich_cname_targets(
+    resources: &mut [Resource],
+    resolver: &dyn Resolver,
+    interval: Duration,
+) {
+    let mut resolved: HashMap<String, Option<Vec<String>>> = HashMap::new();
+
+    for resource in resources.iter_mut() {
+        if resource.resource_type != "cloudflare_dns_record" || resource.metadata["type"] != "CNAME"
+        {
+            continue;
+        }
+        let Some(target) = resource.metadata["target"].as_str().map(str::to_string) else {
+            continue;
+        };
+
+        if !resolved.contains_key(&target) {
+            if !resolved.is_empty() {
+                tokio::time::sleep(interval).await;
+            }
+            let addresses = match resolver.resolve(&target).await {
+                Ok(addrs) if !addrs.is_empty() => {
+                    let mut addrs: Vec<String> = addrs.iter().map(IpAddr::to_string).collect();
+                    addrs.sort();
+                    addrs.dedup();
+                    Some(addrs)
+                }
+                Ok(_) => None,
+                Err(e) => {
+                    tracing::debug!(target = %target, error = %e, "could not resolve CNAME target");
+                    None
+                }
+            };
+            resolved.insert(target.clone(), addresses);
+        }
+
+        if let Some(Some(addresses)) = resolved.get(&target) {
+            resource.metadata["resolved_addresses"] = serde_json::json!(addresses);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    struct MockResolver {
+        answers: HashMap<&'static str, Vec<IpAddr>>,
+        lookups: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl Resolver for MockResolver {
+        async fn resolve(&self, host: &str) -> io::Result<Vec<IpAddr>> {
+            self.lookups.lock().unwrap().push(host.to_string());
+            self.answers
+                .get(host)
+                .cloned()
+                .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "NXDOMAIN"))
+        }
+    }
+
+    fn record(id: &str, metadata: serde_json::Value) -> Resource {
+        Resource {
+            resource_type: "cloudflare_dns_record".to_string(),
+            resource_id: id.to_string(),
+            name: format!("{}.example.com", id),
+            zone_id: "zone123".to_string(),
+            zone_name: None,
+            account_id: None,
+            metadata,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resolved_targets_land_in_metadata_and_failures_are_skipped() {
+        let resolver = MockResolver {
+            answers: HashMap::from([(
+                "example.pages.dev",
+                vec!["192.0.2.2".parse().unwrap(), "192.0.2.1".parse().unwrap()],
+            )]),
+            lookups: Mutex::new(Vec::new()),
+        };
+        let mut resources = vec![
+            record(
+                "www",
+                serde_json::json!({"type": "CNAME", "target": "example.pages.dev"}),
+            ),
+            record(
+                "docs",
+                serde_json::json!({"type": "CNAME", "target": "gone.invalid"}),
+            ),
+            record(
+                "blog",
+                serde_json::json!({"type": "CNAME", "target": "example.pages.dev"}),
+            ),
+            record("api", serde_json::json!({"type": "A"})),
+        ];
+
+        enrich_cname_targets(&mut resources, &resolver, Duration::ZERO).await;
+
+        assert_eq!(
+            resources[0].metadata["resolved_addresses"],
+            serde_json::json!(["192.0.2.1", "192.0.2.2"])
+        );
+        assert!(resources[1].metadata.get("resolved_addresses").is_none());
+        assert_eq!(
+            resources[2].metadata["resolved_addresses"],
+            resources[0].metadata["resolved_addresses"]
+        );
+        assert_eq!(resources[3].metadata, serde_json::json!({"type": "A"}));
+        assert_eq!(
+            *resolver.lookups.lock().unwrap(),
+            vec!["example.pages.dev", "gone.invalid"]
+        );
+    }
+}
@@ -1,5 +1,11 @@
+use std::collections::BTreeSet;
+use std::time::Duration;
+
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 
+use crate::error::TiaError;
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub struct Resource {
@@ -7,17 +13,269 @@ pub struct Resource {
     pub resource_id: String,
     pub name: String,
     pub zone_id: String,
+    /// Name of the zone `zone_id` refers to, when the provider looked it up.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub zone_name: Option<String>,
+    /// Account owning the zone, when the provider looked it up.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account_id: Option<String>,
+    /// Provider-specific attributes, kept as the provider built them: with serde_json's
+    /// `preserve_order` feature, which this crate enables, object keys stay in whatever
+    /// order the API returned them (without it they'd always come out sorted). Compare
+    /// [`Resource::normalized`] copies rather than relying on that order.
     pub metadata: serde_json::Value,
 }
 
+impl Resource {
+    /// A copy whose `metadata` has every object's keys sorted, recursively, so two
+    /// resources with the same attributes compare equal however they were built.
+    /// Array order is kept, since it's meaningful.
+    pub fn normalized(&self) -> Resource {
+        Resource {
+            metadata: normalize_value(&self.metadata),
+            ..self.clone()
+        }
+    }
+
+    /// Stable identity used to match the same resource across discovery runs.
+    pub fn identity_key(&self) -> (&str, &str) {
+        (&self.resource_type, &self.resource_id)
+    }
+
+    /// Lists top-level metadata keys that were added, removed, or modified between two
+    /// versions of a resource, as `(key, old, new)` sorted by key. Non-object metadata
+    /// is treated as having no keys.
+    pub fn metadata_diff(
+        old: &Resource,
+        new: &Resource,
+    ) -> Vec<(String, Option<serde_json::Value>, Option<serde_json::Value>)> {
+        let empty = serde_json::Map::new();
+        let old_fields = old.metadata.as_object().unwrap_or(&empty);
+        let new_fields = new.metadata.as_object().unwrap_or(&empty);
+
+        let keys: BTreeSet<&String> = old_fields.keys().chain(new_fields.keys()).collect();
+
+        keys.into_iter()
+            .filter_map(|key| {
+                let before = old_fields.get(key);
+                let after = new_fields.get(key);
+                (before.map(normalize_value) != after.map(normalize_value))
+                    .then(|| (key.clone(), before.cloned(), after.cloned()))
+            })
+            .collect()
+    }
+}
+
+fn normalize_value(value: &serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(fields) => {
+            let mut sorted: Vec<(&String, &serde_json::Value)> = fields.iter().collect();
+            sorted.sort_by_key(|(key, _)| *key);
+            serde_json::Value::Object(
+                sorted
+                    .into_iter()
+                    .map(|(key, value)| (key.clone(), normalize_value(value)))
+                    .collect(),
+            )
+        }
+        serde_json::Value::Array(items) => {
+            serde_json::Value::Array(items.iter().map(normalize_value).collect())
+        }
+        other => other.clone(),
+    }
+}
+
+/// What to discover and how. Prefer [`DiscoverConfig::builder`], which rejects configs
+/// with nothing to discover; the fields stay public for callers that need the rest.
 #[derive(Debug, Clone, Default)]
 pub struct DiscoverConfig {
-    pub zone: Option<String>,
+    /// Zones to discover, by name or id; resources carry the `zone_id` they came from.
+    pub zones: Vec<String>,
+    /// Discover every zone the token can access instead of `zones`.
+    pub all_zones: bool,
     pub token: Option<String>,
     #[allow(dead_code)] // NOTE: Populated after zone lookup
     pub zone_id: Option<String>,
-    #[allow(dead_code)] // NOTE: Populated after zone lookup, needed for Workers Scripts
+    /// The only account to list account-level resources (pools, lists, Workers scripts)
+    /// from, in place of each zone's account; with no zone, just those resources are
+    /// discovered. When unset, each discovered zone's own account.
     pub account_id: Option<String>,
+    /// Also list account-level resources from every account the token can access, not
+    /// only the discovered zones' accounts.
+    pub all_accounts: bool,
+    pub connect_timeout: Option<Duration>,
+    pub read_timeout: Option<Duration>,
+    /// Suppress per-type warnings for skipped resource types; the summary still counts them.
+    pub quiet_skip: bool,
+    /// Fail on a resource type that would otherwise be skipped, e.g. for a 403 on a
+    /// paid feature.
+    pub strict: bool,
+    /// HTTP statuses to retry instead of the client defaults.
+    pub retry_on: Option<Vec<u16>>,
+    /// How long cached zone lookups stay valid; `None` bypasses the zone cache.
+    pub zone_cache_ttl: Option<Duration>,
+    /// Only discover these resource types; `None` discovers every type.
+    pub resource_types: Option<Vec<String>>,
+    /// Never discover these resource types, even when `resource_types` lists them.
+    pub exclude_types: Vec<String>,
+    /// Upper bound on zone lookups, and on resource families fetched at once unless
+    /// `zone_concurrency` or `per_zone_concurrency` is set; the provider picks a
+    /// default when unset.
+    pub concurrency: Option<usize>,
+    /// Upper bound on zones whose resource families are being fetched at once. Setting
+    /// it or `per_zone_concurrency` bounds families by their product instead of by
+    /// `concurrency`, which then stands in for whichever of the two is unset.
+    pub zone_concurrency: Option<usize>,
+    /// Upper bound on resource families fetched at once within a single zone; see
+    /// `zone_concurrency`.
+    pub per_zone_concurrency: Option<usize>,
+    /// Upper bound on API requests in flight at once, however many zones and resource
+    /// families run in parallel; the client default when unset.
+    pub max_concurrency: Option<usize>,
+    /// Keep rules the API reports as disabled, which are skipped by default.
+    pub include_disabled: bool,
+    /// Keep what discovery skips as default or unmanageable: zone settings left at
+    /// their default and Cloudflare-managed rulesets.
+    pub include_defaults: bool,
+    /// Stop at the first zone or resource type that fails, instead of reporting it
+    /// and discovering the rest.
+    pub fail_fast: bool,
+    /// `per_page` for list requests; the client default when unset.
+    pub page_size: Option<u32>,
+    /// Print a line to stderr after each page a list request fetches.
+    pub progress: bool,
+    /// Log the requests discovery would send and return no resources, without
+    /// contacting the API.
+    pub dry_run: bool,
+    /// NOTE: Overrides the provider's API endpoint; primarily used for testing with mock servers.
+    pub api_base_url: Option<String>,
+}
+
+impl DiscoverConfig {
+    pub fn builder() -> DiscoverConfigBuilder {
+        DiscoverConfigBuilder::default()
+    }
+
+    /// Whether `resource_type` passes the `resource_types` filter and isn't excluded;
+    /// an exclusion wins over an inclusion of the same type.
+    pub fn wants(&self, resource_type: &str) -> bool {
+        !self.exclude_types.iter().any(|t| t == resource_type)
+            && self
+                .resource_types
+                .as_ref()
+                .is_none_or(|types| types.iter().any(|t| t == resource_type))
+    }
+
+    /// Whether `--type` or `--exclude-type` narrows what is discovered.
+    pub fn filters_types(&self) -> bool {
+        self.resource_types.is_some() || !self.exclude_types.is_empty()
+    }
+}
+
+/// Builds a [`DiscoverConfig`] from the common settings; start from
+/// [`DiscoverConfig::builder`].
+#[derive(Debug, Clone, Default)]
+pub struct DiscoverConfigBuilder {
+    config: DiscoverConfig,
+}
+
+impl DiscoverConfigBuilder {
+    /// Adds a zone to discover, by name or id; repeatable.
+    pub fn zone(mut self, zone: impl Into<String>) -> Self {
+        self.config.zones.push(zone.into());
+        self
+    }
+
+    /// Discovers every zone the token can access instead of the added zones.
+    pub fn all_zones(mut self) -> Self {
+        self.config.all_zones = true;
+        self
+    }
+
+    pub fn token(mut self, token: impl Into<String>) -> Self {
+        self.config.token = Some(token.into());
+        self
+    }
+
+    pub fn account(mut self, account_id: impl Into<String>) -> Self {
+        self.config.account_id = Some(account_id.into());
+        self
+    }
+
+    /// Also discovers account-level resources in every account the token can access.
+    pub fn all_accounts(mut self) -> Self {
+        self.config.all_accounts = true;
+        self
+    }
+
+    /// Only discovers these resource types, e.g. `cloudflare_dns_record`.
+    pub fn types<I, S>(mut self, types: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.config.resource_types = Some(types.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Never discovers these resource types, even if [`types`](Self::types) lists them.
+    pub fn exclude_types<I, S>(mut self, types: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.config
+            .exclude_types
+            .extend(types.into_iter().map(Into::into));
+        self
+    }
+
+    /// Trims every value and drops blank ones, so an empty token or account becomes
+    /// `None` and an empty type list means every type. Fails when no zone or account is
+    /// left to discover.
+    pub fn build(self) -> Result<DiscoverConfig, TiaError> {
+        let mut config = self.config;
+        config.zones = normalize_list(config.zones);
+        config.token = normalize(config.token);
+        config.account_id = normalize(config.account_id);
+        config.resource_types = config
+            .resource_types
+            .map(normalize_list)
+            .filter(|types| !types.is_empty());
+        config.exclude_types = normalize_list(config.exclude_types);
+
+        if config.zones.is_empty()
+            && !config.all_zones
+            && config.account_id.is_none()
+            && !config.all_accounts
+        {
+            return Err(TiaError::Config(
+                "nothing to discover: add a zone or an account, or ask for all zones".to_string(),
+            ));
+        }
+        Ok(config)
+    }
+}
+
+fn normalize(value: Option<String>) -> Option<String> {
+    value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+fn normalize_list(values: Vec<String>) -> Vec<String> {
+    values
+        .into_iter()
+        .filter_map(|v| normalize(Some(v)))
+        .collect()
+}
+
+/// Keeps the resources whose name matches `pattern` anywhere; anchor it to match whole names.
+pub fn filter_by_name(resources: Vec<Resource>, pattern: &Regex) -> Vec<Resource> {
+    resources
+        .into_iter()
+        .filter(|resource| pattern.is_match(&resource.name))
+        .collect()
 }
 
 #[cfg(test)]
@@ -31,6 +289,8 @@ mod tests {
             resource_id: "abc123".to_string(),
             name: "api.example.com".to_string(),
             zone_id: "zone456".to_string(),
+            zone_name: None,
+            account_id: None,
             metadata: serde_json::json!({"record_type": "A"}),
         };
         let json = serde_json::to_string(&resource).unwrap();
@@ -40,6 +300,26 @@ mod tests {
         assert!(!json.contains("resourceType"));
         assert!(!json.contains("resourceId"));
         assert!(!json.contains("zoneId"));
+        assert!(!json.contains("zone_name"));
+        assert!(!json.contains("account_id"));
+    }
+
+    #[test]
+    fn test_resource_serializes_zone_name_and_account_when_known() {
+        let resource = Resource {
+            resource_type: "cloudflare_record".to_string(),
+            resource_id: "abc123".to_string(),
+            name: "api.example.com".to_string(),
+            zone_id: "zone456".to_string(),
+            zone_name: Some("example.com".to_string()),
+            account_id: Some("acc789".to_string()),
+            metadata: serde_json::json!({}),
+        };
+
+        let json = serde_json::to_value(&resource).unwrap();
+
+        assert_eq!(json["zone_name"], "example.com");
+        assert_eq!(json["account_id"], "acc789");
     }
 
     #[test]
@@ -56,9 +336,249 @@ mod tests {
         assert_eq!(resource.resource_id, "abc123");
         assert_eq!(resource.name, "api.example.com");
         assert_eq!(resource.zone_id, "zone456");
+        assert_eq!(resource.zone_name, None);
+        assert_eq!(resource.account_id, None);
         assert_eq!(resource.metadata["record_type"], "A");
     }
 
+    #[test]
+    fn test_identity_key_ignores_name_and_metadata() {
+        let a = Resource {
+            resource_type: "cloudflare_dns_record".to_string(),
+            resource_id: "abc123".to_string(),
+            name: "old.example.com".to_string(),
+            zone_id: "zone456".to_string(),
+            zone_name: None,
+            account_id: None,
+            metadata: serde_json::json!({"type": "A"}),
+        };
+        let b = Resource {
+            name: "new.example.com".to_string(),
+            metadata: serde_json::json!({"type": "CNAME"}),
+            ..a.clone()
+        };
+        assert_eq!(a.identity_key(), ("cloudflare_dns_record", "abc123"));
+        assert_eq!(a.identity_key(), b.identity_key());
+    }
+
+    #[test]
+    fn test_metadata_diff_reports_modified_and_added_keys() {
+        let old = Resource {
+            resource_type: "cloudflare_dns_record".to_string(),
+            resource_id: "abc123".to_string(),
+            name: "api.example.com".to_string(),
+            zone_id: "zone456".to_string(),
+            zone_name: None,
+            account_id: None,
+            metadata: serde_json::json!({"type": "A", "proxied": true}),
+        };
+        let new = Resource {
+            metadata: serde_json::json!({"type": "A", "proxied": false, "comment": "moved"}),
+            ..old.clone()
+        };
+
+        let diff = Resource::metadata_diff(&old, &new);
+
+        assert_eq!(
+            diff,
+            vec![
+                (
+                    "comment".to_string(),
+                    None,
+                    Some(serde_json::json!("moved"))
+                ),
+                (
+                    "proxied".to_string(),
+                    Some(serde_json::json!(true)),
+                    Some(serde_json::json!(false))
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_metadata_diff_reports_removed_keys_and_handles_null() {
+        let old = Resource {
+            resource_type: "cloudflare_ruleset".to_string(),
+            resource_id: "rs1".to_string(),
+            name: "rules".to_string(),
+            zone_id: "zone456".to_string(),
+            zone_name: None,
+            account_id: None,
+            metadata: serde_json::json!({"phase": "http_request_transform"}),
+        };
+        let new = Resource {
+            metadata: serde_json::json!(null),
+            ..old.clone()
+        };
+
+        let diff = Resource::metadata_diff(&old, &new);
+
+        assert_eq!(
+            diff,
+            vec![(
+                "phase".to_string(),
+                Some(serde_json::json!("http_request_transform")),
+                None
+            )]
+        );
+        assert!(Resource::metadata_diff(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn test_builder_requires_a_zone_or_account() {
+        let err = DiscoverConfig::builder().token("t").build().unwrap_err();
+        assert!(matches!(err, TiaError::Config(_)));
+
+        let err = DiscoverConfig::builder().zone("  ").build().unwrap_err();
+        assert!(matches!(err, TiaError::Config(_)));
+
+        assert!(DiscoverConfig::builder().all_zones().build().is_ok());
+        assert!(DiscoverConfig::builder().account("acc123").build().is_ok());
+    }
+
+    #[test]
+    fn test_builder_normalizes_blank_values() {
+        let config = DiscoverConfig::builder()
+            .zone(" example.com ")
+            .zone("")
+            .token("")
+            .account("  ")
+            .types(["", " "])
+            .build()
+            .unwrap();
+
+        assert_eq!(config.zones, vec!["example.com"]);
+        assert_eq!(config.token, None);
+        assert_eq!(config.account_id, None);
+        assert_eq!(config.resource_types, None);
+        assert!(config.wants("cloudflare_dns_record"));
+
+        let config = DiscoverConfig::builder()
+            .zone("example.com")
+            .token(" secret ")
+            .types(["cloudflare_dns_record"])
+            .build()
+            .unwrap();
+
+        assert_eq!(config.token.as_deref(), Some("secret"));
+        assert!(config.wants("cloudflare_dns_record"));
+        assert!(!config.wants("cloudflare_page_rule"));
+    }
+
+    #[test]
+    fn test_wants_allowlist_only() {
+        let config = DiscoverConfig::builder()
+            .zone("example.com")
+            .types(["cloudflare_dns_record", "cloudflare_ruleset"])
+            .build()
+            .unwrap();
+
+        assert!(config.wants("cloudflare_dns_record"));
+        assert!(config.wants("cloudflare_ruleset"));
+        assert!(!config.wants("cloudflare_page_rule"));
+        assert!(config.filters_types());
+    }
+
+    #[test]
+    fn test_wants_denylist_only() {
+        let config = DiscoverConfig::builder()
+            .zone("example.com")
+            .exclude_types(["cloudflare_ruleset", " "])
+            .build()
+            .unwrap();
+
+        assert_eq!(config.exclude_types, vec!["cloudflare_ruleset"]);
+        assert!(config.wants("cloudflare_dns_record"));
+        assert!(config.wants("cloudflare_page_rule"));
+        assert!(!config.wants("cloudflare_ruleset"));
+        assert!(config.filters_types());
+    }
+
+    #[test]
+    fn test_wants_exclusion_wins_over_inclusion() {
+        let config = DiscoverConfig::builder()
+            .zone("example.com")
+            .types(["cloudflare_dns_record", "cloudflare_ruleset"])
+            .exclude_types(["cloudflare_ruleset"])
+            .build()
+            .unwrap();
+
+        assert!(config.wants("cloudflare_dns_record"));
+        assert!(!config.wants("cloudflare_ruleset"));
+        assert!(!config.wants("cloudflare_page_rule"));
+    }
+
+    #[test]
+    fn test_filter_by_name_drops_non_matching_resources() {
+        let resource = |name: &str| Resource {
+            resource_type: "cloudflare_dns_record".to_string(),
+            resource_id: format!("id_{}", name),
+            name: name.to_string(),
+            zone_id: "zone456".to_string(),
+            zone_name: None,
+            account_id: None,
+            metadata: serde_json::json!({}),
+        };
+        let pattern = Regex::new(r"^api\.").unwrap();
+
+        let kept = filter_by_name(
+            vec![
+                resource("api.example.com"),
+                resource("www.example.com"),
+                resource("staging.api.example.com"),
+                resource("api.internal.example.com"),
+            ],
+            &pattern,
+        );
+
+        let names: Vec<&str> = kept.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["api.example.com", "api.internal.example.com"]);
+    }
+
+    #[test]
+    fn test_normalized_metadata_ignores_key_order() {
+        let resource = |metadata: &str| Resource {
+            resource_type: "cloudflare_dns_record".to_string(),
+            resource_id: "abc123".to_string(),
+            name: "api.example.com".to_string(),
+            zone_id: "zone456".to_string(),
+            zone_name: None,
+            account_id: None,
+            metadata: serde_json::from_str(metadata).unwrap(),
+        };
+        let a = resource(r#"{"type": "A", "ttl": 1, "meta": {"source": "api", "auto": false}}"#);
+        let b = resource(r#"{"meta": {"auto": false, "source": "api"}, "ttl": 1, "type": "A"}"#);
+
+        assert_ne!(
+            serde_json::to_string(&a.metadata).unwrap(),
+            serde_json::to_string(&b.metadata).unwrap()
+        );
+        assert_eq!(a.normalized(), b.normalized());
+        assert_eq!(
+            serde_json::to_string(&a.normalized().metadata).unwrap(),
+            serde_json::to_string(&b.normalized().metadata).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_normalized_metadata_keeps_array_order() {
+        let resource = |metadata: serde_json::Value| Resource {
+            resource_type: "cloudflare_list".to_string(),
+            resource_id: "abc123".to_string(),
+            name: "allowlist".to_string(),
+            zone_id: "zone456".to_string(),
+            zone_name: None,
+            account_id: None,
+            metadata,
+        };
+
+        assert_ne!(
+            resource(serde_json::json!({"items": ["a", "b"]})).normalized(),
+            resource(serde_json::json!({"items": ["b", "a"]})).normalized()
+        );
+    }
+
     #[test]
     fn test_resource_roundtrip() {
         let resource = Resource {
@@ -66,6 +586,8 @@ mod tests {
             resource_id: "abc123".to_string(),
             name: "test".to_string(),
             zone_id: "zone789".to_string(),
+            zone_name: None,
+            account_id: None,
             metadata: serde_json::json!(null),
         };
         let json = serde_json::to_string(&resource).unwrap();
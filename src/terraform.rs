@@ -1,3 +1,90 @@
-// TODO: implement
-
+pub mod drift;
+pub mod naming;
 pub mod state;
+
+use crate::output::hcl;
+use crate::providers;
+use crate::resource::Resource;
+use naming::resource_identifier;
+
+/// Renders the import block for `resource` on its own, addressed by its sanitized
+/// name inside `module`, if any, and using the import id format of the provider
+/// owning its type.
+///
+/// Unlike [`Provider::generate_imports`](crate::providers::Provider::generate_imports),
+/// identifiers aren't de-duplicated across resources.
+pub fn import_block(resource: &Resource, module: Option<&str>) -> String {
+    hcl::import_block(
+        module,
+        &resource.resource_type,
+        &resource_identifier(resource),
+        &providers::import_id(resource),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resource(resource_type: &str, id: &str, name: &str) -> Resource {
+        Resource {
+            resource_type: resource_type.to_string(),
+            resource_id: id.to_string(),
+            name: name.to_string(),
+            zone_id: "zone456".to_string(),
+            zone_name: None,
+            account_id: None,
+            metadata: serde_json::json!({}),
+        }
+    }
+
+    #[test]
+    fn test_import_block_for_dns_record() {
+        let record = resource("cloudflare_dns_record", "rec1", "api.example.com");
+
+        assert_eq!(
+            import_block(&record, None),
+            "import {\n  to = cloudflare_dns_record.api_example_com\n  id = \"zone456/rec1\"\n}"
+        );
+    }
+
+    #[test]
+    fn test_import_block_for_page_rule_sanitizes_url_pattern() {
+        let rule = resource("cloudflare_page_rule", "pr1", "*example.com/images/*");
+
+        assert_eq!(
+            import_block(&rule, None),
+            "import {\n  to = cloudflare_page_rule._example_com_images__\n  id = \"zone456/pr1\"\n}"
+        );
+    }
+
+    #[test]
+    fn test_import_block_falls_back_to_id_and_prefixes_leading_digit() {
+        let rule = resource("cloudflare_page_rule", "9f2c", "");
+
+        assert_eq!(
+            import_block(&rule, None),
+            "import {\n  to = cloudflare_page_rule._9f2c\n  id = \"zone456/9f2c\"\n}"
+        );
+    }
+
+    #[test]
+    fn test_import_block_suffixes_dns_record_named_count() {
+        let record = resource("cloudflare_dns_record", "rec1", "count");
+
+        assert_eq!(
+            import_block(&record, None),
+            "import {\n  to = cloudflare_dns_record.count_\n  id = \"zone456/rec1\"\n}"
+        );
+    }
+
+    #[test]
+    fn test_import_block_inside_module() {
+        let record = resource("cloudflare_dns_record", "rec1", "www");
+
+        assert_eq!(
+            import_block(&record, Some("cloudflare")),
+            "import {\n  to = module.cloudflare.cloudflare_dns_record.www\n  id = \"zone456/rec1\"\n}"
+        );
+    }
+}
@@ -0,0 +1,265 @@
+//! Comparison of live resources against what a Terraform state already manages.
+
+use std::collections::BTreeSet;
+
+use super::state::TerraformState;
+use crate::providers;
+use crate::resource::Resource;
+
+/// Live resources are matched to managed state entries on resource type and id.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StateDiff {
+    /// Live resources the state doesn't manage, i.e. import candidates.
+    pub to_import: Vec<Resource>,
+    /// Managed `(resource_type, id)` pairs with no live counterpart, among the types and
+    /// zones discovery covered.
+    pub state_only: Vec<(String, String)>,
+    pub matched: usize,
+}
+
+impl StateDiff {
+    /// Compares `discovered` against `state`. A managed entry is only state-only when
+    /// its type is one of `discovered_types` and, if it records a `zone_id`, that zone
+    /// is one of `discovered_zones`, even one that returned nothing; anything else is
+    /// outside what discovery looked at, e.g. a managed `cloudflare_zone` or another
+    /// zone's records.
+    pub fn between(
+        discovered: &[Resource],
+        state: &TerraformState,
+        discovered_types: &[&str],
+        discovered_zones: &[String],
+    ) -> Self {
+        let managed: BTreeSet<(String, String)> = state.resource_ids().into_iter().collect();
+        let live: BTreeSet<(String, String)> = discovered
+            .iter()
+            .map(|r| (r.resource_type.clone(), r.resource_id.clone()))
+            .collect();
+
+        let mut diff = Self::default();
+        // Nested resources such as ruleset rules are tracked through their parent.
+        for resource in discovered.iter().filter(|r| providers::is_importable(r)) {
+            if managed.contains(&(resource.resource_type.clone(), resource.resource_id.clone())) {
+                diff.matched += 1;
+            } else {
+                diff.to_import.push(resource.clone());
+            }
+        }
+        let in_scope: BTreeSet<(String, String)> = state
+            .resources
+            .iter()
+            .filter(|r| r.mode == "managed" && discovered_types.contains(&r.resource_type.as_str()))
+            .flat_map(|r| {
+                r.instances.iter().filter_map(|instance| {
+                    let id = instance.attributes.get("id")?.as_str()?;
+                    let zone_id = instance.attributes.get("zone_id").and_then(|z| z.as_str());
+                    zone_id
+                        .is_none_or(|zone_id| discovered_zones.iter().any(|z| z == zone_id))
+                        .then(|| (r.resource_type.clone(), id.to_string()))
+                })
+            })
+            .collect();
+        diff.state_only = managed
+            .intersection(&in_scope)
+            .filter(|key| !live.contains(*key))
+            .cloned()
+            .collect();
+        diff
+    }
+
+    /// Drops the state entries of resources discovery found but `.tiaignore` left out,
+    /// which would otherwise look deleted.
+    pub fn without_ignored(mut self, ignored: &[Resource]) -> Self {
+        self.state_only.retain(|(resource_type, id)| {
+            !ignored
+                .iter()
+                .any(|r| &r.resource_type == resource_type && &r.resource_id == id)
+        });
+        self
+    }
+
+    pub fn has_drift(&self) -> bool {
+        !self.to_import.is_empty() || !self.state_only.is_empty()
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "import: {}, state-only: {}, matched: {}",
+            self.to_import.len(),
+            self.state_only.len(),
+            self.matched
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TYPES: &[&str] = &[
+        "cloudflare_dns_record",
+        "cloudflare_ruleset",
+        "cloudflare_ruleset_rule",
+    ];
+
+    fn zones() -> Vec<String> {
+        vec!["zone123".to_string()]
+    }
+
+    fn record(id: &str) -> Resource {
+        Resource {
+            resource_type: "cloudflare_dns_record".to_string(),
+            resource_id: id.to_string(),
+            name: format!("{}.example.com", id),
+            zone_id: "zone123".to_string(),
+            zone_name: None,
+            account_id: None,
+            metadata: serde_json::json!({}),
+        }
+    }
+
+    #[test]
+    fn test_summary_counts_mixed_diff() {
+        let state = TerraformState::parse(
+            r#"{
+                "version": 4,
+                "resources": [
+                    {
+                        "mode": "managed",
+                        "type": "cloudflare_dns_record",
+                        "name": "records",
+                        "instances": [
+                            {"attributes": {"id": "kept1"}},
+                            {"attributes": {"id": "kept2"}},
+                            {"attributes": {"id": "deleted"}}
+                        ]
+                    },
+                    {
+                        "mode": "data",
+                        "type": "cloudflare_dns_record",
+                        "name": "lookup",
+                        "instances": [{"attributes": {"id": "new1"}}]
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let discovered = vec![
+            record("kept1"),
+            record("new1"),
+            record("kept2"),
+            record("new2"),
+        ];
+
+        let diff = StateDiff::between(&discovered, &state, TYPES, &zones());
+
+        assert_eq!(diff.summary(), "import: 2, state-only: 1, matched: 2");
+        assert_eq!(
+            diff.state_only,
+            vec![("cloudflare_dns_record".to_string(), "deleted".to_string())]
+        );
+        assert!(diff.has_drift());
+    }
+
+    #[test]
+    fn test_ruleset_rules_are_not_import_candidates() {
+        let state = TerraformState::parse(
+            r#"{"resources": [{"mode": "managed", "type": "cloudflare_ruleset",
+                "name": "waf", "instances": [{"attributes": {"id": "rs1"}}]}]}"#,
+        )
+        .unwrap();
+        let ruleset = Resource {
+            resource_type: "cloudflare_ruleset".to_string(),
+            ..record("rs1")
+        };
+        let rule = Resource {
+            resource_type: "cloudflare_ruleset_rule".to_string(),
+            metadata: serde_json::json!({"ruleset_id": "rs1"}),
+            ..record("rule1")
+        };
+
+        let diff = StateDiff::between(&[ruleset, rule], &state, TYPES, &zones());
+
+        assert!(!diff.has_drift());
+        assert_eq!(diff.matched, 1);
+    }
+
+    #[test]
+    fn test_state_only_skips_undiscovered_types_and_zones() {
+        let state = TerraformState::parse(
+            r#"{"resources": [
+                {"mode": "managed", "type": "cloudflare_dns_record", "name": "a",
+                 "instances": [
+                    {"attributes": {"id": "r1", "zone_id": "zone123"}},
+                    {"attributes": {"id": "gone", "zone_id": "zone123"}},
+                    {"attributes": {"id": "elsewhere", "zone_id": "zone999"}}
+                 ]},
+                {"mode": "managed", "type": "cloudflare_page_rule", "name": "p",
+                 "instances": [{"attributes": {"id": "pr1", "zone_id": "zone123"}}]}
+            ]}"#,
+        )
+        .unwrap();
+
+        let diff = StateDiff::between(
+            &[record("r1")],
+            &state,
+            &["cloudflare_dns_record"],
+            &zones(),
+        );
+
+        assert_eq!(
+            diff.state_only,
+            vec![("cloudflare_dns_record".to_string(), "gone".to_string())]
+        );
+        assert_eq!(diff.matched, 1);
+    }
+
+    #[test]
+    fn test_zone_that_returned_nothing_still_reports_state_only() {
+        let state = TerraformState::parse(
+            r#"{"resources": [{"mode": "managed", "type": "cloudflare_dns_record",
+                "name": "a", "instances": [{"attributes": {"id": "gone", "zone_id": "zone123"}}]}]}"#,
+        )
+        .unwrap();
+
+        let diff = StateDiff::between(&[], &state, TYPES, &zones());
+
+        assert_eq!(
+            diff.state_only,
+            vec![("cloudflare_dns_record".to_string(), "gone".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_ignored_resources_are_not_state_only() {
+        let state = TerraformState::parse(
+            r#"{"resources": [{"mode": "managed", "type": "cloudflare_dns_record",
+                "name": "a", "instances": [
+                    {"attributes": {"id": "ignored", "zone_id": "zone123"}},
+                    {"attributes": {"id": "gone", "zone_id": "zone123"}}
+                ]}]}"#,
+        )
+        .unwrap();
+
+        let diff =
+            StateDiff::between(&[], &state, TYPES, &zones()).without_ignored(&[record("ignored")]);
+
+        assert_eq!(
+            diff.state_only,
+            vec![("cloudflare_dns_record".to_string(), "gone".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_fully_managed_has_no_drift() {
+        let state = TerraformState::parse(
+            r#"{"resources": [{"mode": "managed", "type": "cloudflare_dns_record",
+                "name": "a", "instances": [{"attributes": {"id": "r1"}}]}]}"#,
+        )
+        .unwrap();
+
+        let diff = StateDiff::between(&[record("r1")], &state, TYPES, &zones());
+
+        assert!(!diff.has_drift());
+        assert_eq!(diff.summary(), "import: 0, state-only: 0, matched: 1");
+    }
+}
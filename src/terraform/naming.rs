@@ -0,0 +1,261 @@
+//! Terraform identifier handling for generated resource addresses.
+
+use std::collections::HashSet;
+
+use crate::error::TiaError;
+use crate::resource::Resource;
+
+/// Names Terraform reserves for meta-arguments and block types, which break
+/// `terraform validate` when used as a resource name.
+pub const RESERVED_IDENTIFIERS: &[&str] = &[
+    "count",
+    "data",
+    "depends_on",
+    "for_each",
+    "lifecycle",
+    "locals",
+    "module",
+    "provider",
+    "resource",
+    "terraform",
+    "variable",
+];
+
+/// Rewrites `name` into a valid Terraform identifier: every character other than
+/// ASCII letters, digits and `_` becomes `_`, a leading digit is prefixed with `_`,
+/// and a [reserved name](RESERVED_IDENTIFIERS) is suffixed with `_`.
+pub fn sanitize_identifier(name: &str) -> String {
+    let mut identifier: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if identifier.starts_with(|c: char| c.is_ascii_digit()) {
+        identifier.insert(0, '_');
+    }
+    if RESERVED_IDENTIFIERS.contains(&identifier.as_str()) {
+        identifier.push('_');
+    }
+    identifier
+}
+
+/// The Terraform address of `resource_type.identifier`, prefixed with
+/// `module.<module>.` when it lives in a module.
+pub fn address(module: Option<&str>, resource_type: &str, identifier: &str) -> String {
+    match module {
+        Some(module) => format!("module.{}.{}.{}", module, resource_type, identifier),
+        None => format!("{}.{}", resource_type, identifier),
+    }
+}
+
+/// Rejects a `--module` path that isn't made of Terraform identifiers separated by
+/// dots, e.g. `cloudflare` or `edge.module.dns` for a nested module. Each nested
+/// module is preceded by a `module` segment, as in its Terraform address.
+pub fn check_module_path(path: &str) -> Result<(), TiaError> {
+    let is_identifier = |segment: &str| {
+        segment.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && segment
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    };
+    if let Some(rest) = path.strip_prefix("module.") {
+        return Err(TiaError::Config(format!(
+            "invalid module path {:?}: leave out the leading `module.`, e.g. {:?}",
+            path, rest
+        )));
+    }
+    if !path.split('.').all(is_identifier) {
+        return Err(TiaError::Config(format!(
+            "invalid module path {:?}: expected module names separated by dots, e.g. \"cloudflare\"",
+            path
+        )));
+    }
+    let segments: Vec<&str> = path.split('.').collect();
+    let nested_correctly = segments.len() % 2 == 1
+        && segments
+            .iter()
+            .enumerate()
+            .all(|(index, segment)| (index % 2 == 1) == (*segment == "module"));
+    if !nested_correctly {
+        return Err(TiaError::Config(format!(
+            "invalid module path {:?}: put `module` before each nested module, e.g. \"edge.module.dns\"",
+            path
+        )));
+    }
+    Ok(())
+}
+
+/// The sanitized identifier for `resource`, falling back to its id when it has no name.
+pub fn resource_identifier(resource: &Resource) -> String {
+    if resource.name.is_empty() {
+        sanitize_identifier(&resource.resource_id)
+    } else {
+        sanitize_identifier(&resource.name)
+    }
+}
+
+/// Clones `resources` with each name replaced by an identifier that is unique within
+/// its type. Repeats are suffixed in iteration order, so the same input order always
+/// yields the same identifiers.
+pub fn assign_identifiers<'a>(resources: impl IntoIterator<Item = &'a Resource>) -> Vec<Resource> {
+    let mut allocator = IdentifierAllocator::default();
+    resources
+        .into_iter()
+        .map(|resource| {
+            let mut named = resource.clone();
+            named.name =
+                allocator.allocate(&resource.resource_type, &resource_identifier(resource));
+            named
+        })
+        .collect()
+}
+
+/// Hands out identifiers that are unique per resource type, suffixing repeats with
+/// `_2`, `_3`, ... in the order they are requested.
+#[derive(Debug, Default)]
+pub struct IdentifierAllocator {
+    taken: HashSet<(String, String)>,
+}
+
+impl IdentifierAllocator {
+    pub fn allocate(&mut self, resource_type: &str, base: &str) -> String {
+        let mut candidate = base.to_string();
+        let mut suffix = 2;
+        while !self
+            .taken
+            .insert((resource_type.to_string(), candidate.clone()))
+        {
+            candidate = format!("{}_{}", base, suffix);
+            suffix += 1;
+        }
+        candidate
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_identifier_replaces_illegal_characters() {
+        assert_eq!(sanitize_identifier("api.example.com"), "api_example_com");
+        assert_eq!(sanitize_identifier("*.example.com"), "__example_com");
+        assert_eq!(sanitize_identifier("my-record_1"), "my_record_1");
+    }
+
+    #[test]
+    fn test_sanitize_identifier_prefixes_leading_digit() {
+        assert_eq!(sanitize_identifier("1password"), "_1password");
+    }
+
+    #[test]
+    fn test_sanitize_identifier_suffixes_reserved_words() {
+        assert_eq!(sanitize_identifier("resource"), "resource_");
+        assert_eq!(sanitize_identifier("for-each"), "for_each_");
+        assert_eq!(sanitize_identifier("count_"), "count_");
+        assert_eq!(sanitize_identifier("counter"), "counter");
+    }
+
+    #[test]
+    fn test_sanitize_identifier_wildcard_page_rule_target() {
+        let identifier = sanitize_identifier("*example.com/images/*");
+        assert_eq!(identifier, "_example_com_images__");
+        assert_eq!(sanitize_identifier(&identifier), identifier);
+    }
+
+    #[test]
+    fn test_resource_identifier_falls_back_to_id_when_unnamed() {
+        let resource = Resource {
+            resource_type: "cloudflare_page_rule".to_string(),
+            resource_id: "9a7806061c88ada191ed06f989cc3dac".to_string(),
+            name: String::new(),
+            zone_id: "zone456".to_string(),
+            zone_name: None,
+            account_id: None,
+            metadata: serde_json::json!({}),
+        };
+        assert_eq!(
+            resource_identifier(&resource),
+            "_9a7806061c88ada191ed06f989cc3dac"
+        );
+    }
+
+    #[test]
+    fn test_address_inside_module() {
+        assert_eq!(
+            address(Some("cloudflare"), "cloudflare_dns_record", "www"),
+            "module.cloudflare.cloudflare_dns_record.www"
+        );
+        assert_eq!(
+            address(None, "cloudflare_dns_record", "www"),
+            "cloudflare_dns_record.www"
+        );
+    }
+
+    #[test]
+    fn test_check_module_path() {
+        assert!(check_module_path("cloudflare").is_ok());
+        assert!(check_module_path("edge.module.dns-zones").is_ok());
+        assert!(check_module_path("a.module.b.module.c").is_ok());
+        for invalid in [
+            "",
+            "module.cloudflare",
+            "cloud flare",
+            "1st",
+            "a..b",
+            "a.",
+            "edge.dns",
+            "edge.module",
+            "edge.module.module.dns",
+        ] {
+            assert!(check_module_path(invalid).is_err(), "{:?}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_allocator_suffixes_repeats_per_type() {
+        let mut allocator = IdentifierAllocator::default();
+        assert_eq!(allocator.allocate("cloudflare_dns_record", "www"), "www");
+        assert_eq!(allocator.allocate("cloudflare_dns_record", "www"), "www_2");
+        assert_eq!(allocator.allocate("cloudflare_dns_record", "www"), "www_3");
+        assert_eq!(allocator.allocate("cloudflare_page_rule", "www"), "www");
+    }
+
+    #[test]
+    fn test_assign_identifiers_follows_input_order() {
+        let record = |id: &str, name: &str| Resource {
+            resource_type: "cloudflare_dns_record".to_string(),
+            resource_id: id.to_string(),
+            name: name.to_string(),
+            zone_id: "zone456".to_string(),
+            zone_name: None,
+            account_id: None,
+            metadata: serde_json::json!({}),
+        };
+        let resources = [
+            record("r1", "www."),
+            record("r2", "www-"),
+            record("r3", "www_"),
+        ];
+
+        let names: Vec<String> = assign_identifiers(&resources)
+            .into_iter()
+            .map(|r| r.name)
+            .collect();
+
+        assert_eq!(names, vec!["www_", "www__2", "www__3"]);
+    }
+
+    #[test]
+    fn test_allocator_skips_suffix_already_taken_by_a_real_name() {
+        let mut allocator = IdentifierAllocator::default();
+        allocator.allocate("cloudflare_dns_record", "www_2");
+        allocator.allocate("cloudflare_dns_record", "www");
+        assert_eq!(allocator.allocate("cloudflare_dns_record", "www"), "www_3");
+    }
+}
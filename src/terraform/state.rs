@@ -1,6 +1,261 @@
-/// Terraform state parser for drift detection.
-///
-/// Parses tfstate v4 files and extracts resource IDs for comparison.
-#[allow(dead_code)]
-#[derive(Debug)]
-pub struct TerraformState;
+//! Terraform state parser for drift detection.
+//!
+//! Parses tfstate v4 files and extracts resource IDs for comparison.
+
+use std::collections::HashSet;
+use std::io::Read;
+
+use serde::Deserialize;
+
+use crate::error::TiaError;
+
+/// The only state format version this parser understands.
+pub const SUPPORTED_STATE_VERSION: u64 = 4;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TerraformState {
+    #[serde(default)]
+    pub version: Option<u64>,
+    #[serde(default)]
+    pub resources: Vec<StateResource>,
+}
+
+/// One `resources[]` entry. `module` is the module address (e.g. `module.network`),
+/// absent for resources in the root module.
+#[derive(Debug, Clone, Deserialize)]
+pub struct StateResource {
+    #[serde(default)]
+    pub module: Option<String>,
+    pub mode: String,
+    #[serde(rename = "type")]
+    pub resource_type: String,
+    pub name: String,
+    #[serde(default)]
+    pub instances: Vec<StateInstance>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct StateInstance {
+    #[serde(default)]
+    pub attributes: serde_json::Value,
+}
+
+impl StateResource {
+    /// Whether this resource lives in `module` or one of its child modules.
+    /// Instance keys are part of the address, so `module.zones` also covers
+    /// `module.zones["example.com"]`.
+    pub fn in_module(&self, module: &str) -> bool {
+        let Some(address) = &self.module else {
+            return false;
+        };
+        match address.strip_prefix(module) {
+            Some(rest) => rest.is_empty() || rest.starts_with('.') || rest.starts_with('['),
+            None => false,
+        }
+    }
+
+    pub fn ids(&self) -> impl Iterator<Item = &str> {
+        self.instances
+            .iter()
+            .filter_map(|instance| instance.attributes.get("id").and_then(|id| id.as_str()))
+    }
+}
+
+impl TerraformState {
+    pub fn parse(contents: &str) -> Result<Self, TiaError> {
+        Self::from_reader(contents.as_bytes())
+    }
+
+    /// Reads a tfstate document, rejecting state format versions other than 4.
+    pub fn from_reader(reader: impl Read) -> Result<Self, TiaError> {
+        let state: Self = serde_json::from_reader(reader)
+            .map_err(|e| TiaError::State(format!("failed to parse state: {}", e)))?;
+        match state.version {
+            Some(version) if version != SUPPORTED_STATE_VERSION => Err(TiaError::State(format!(
+                "unsupported state version {} (expected {})",
+                version, SUPPORTED_STATE_VERSION
+            ))),
+            _ => Ok(state),
+        }
+    }
+
+    /// `(resource_type, id)` of every managed resource instance, across all modules.
+    /// Data sources are skipped since they can't be imported.
+    pub fn resource_ids(&self) -> HashSet<(String, String)> {
+        self.resources
+            .iter()
+            .filter(|r| r.mode == "managed")
+            .flat_map(|r| {
+                r.ids()
+                    .map(move |id| (r.resource_type.clone(), id.to_string()))
+            })
+            .collect()
+    }
+
+    /// Keeps only resources under `module`, for diffing a single module.
+    pub fn scoped_to_module(self, module: &str) -> Self {
+        Self {
+            version: self.version,
+            resources: self
+                .resources
+                .into_iter()
+                .filter(|resource| resource.in_module(module))
+                .collect(),
+        }
+    }
+
+    /// Whether a managed resource of `resource_type` with import id `id` is tracked.
+    pub fn contains(&self, resource_type: &str, id: &str) -> bool {
+        self.resources
+            .iter()
+            .filter(|r| r.mode == "managed" && r.resource_type == resource_type)
+            .any(|r| r.ids().any(|tracked| tracked == id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MIXED_STATE: &str = r#"{
+        "version": 4,
+        "terraform_version": "1.9.0",
+        "resources": [
+            {
+                "mode": "managed",
+                "type": "cloudflare_dns_record",
+                "name": "root_www",
+                "instances": [{"attributes": {"id": "rec_root"}}]
+            },
+            {
+                "module": "module.network",
+                "mode": "managed",
+                "type": "cloudflare_dns_record",
+                "name": "api",
+                "instances": [{"attributes": {"id": "rec_network"}}]
+            },
+            {
+                "module": "module.network.module.edge",
+                "mode": "managed",
+                "type": "cloudflare_ruleset",
+                "name": "waf",
+                "instances": [{"attributes": {"id": "rs_edge"}}]
+            },
+            {
+                "module": "module.network_legacy",
+                "mode": "managed",
+                "type": "cloudflare_dns_record",
+                "name": "old",
+                "instances": [{"attributes": {"id": "rec_legacy"}}]
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn test_parse_tracks_module_addresses() {
+        let state = TerraformState::parse(MIXED_STATE).unwrap();
+
+        let modules: Vec<Option<&str>> = state
+            .resources
+            .iter()
+            .map(|r| r.module.as_deref())
+            .collect();
+        assert_eq!(
+            modules,
+            vec![
+                None,
+                Some("module.network"),
+                Some("module.network.module.edge"),
+                Some("module.network_legacy"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scoped_to_module_keeps_only_module_resources() {
+        let state = TerraformState::parse(MIXED_STATE)
+            .unwrap()
+            .scoped_to_module("module.network");
+
+        assert!(state.contains("cloudflare_dns_record", "rec_network"));
+        assert!(state.contains("cloudflare_ruleset", "rs_edge"));
+        assert!(!state.contains("cloudflare_dns_record", "rec_root"));
+        assert!(!state.contains("cloudflare_dns_record", "rec_legacy"));
+    }
+
+    #[test]
+    fn test_only_module_resources_participate_in_diff() {
+        let state = TerraformState::parse(MIXED_STATE)
+            .unwrap()
+            .scoped_to_module("module.network");
+        let discovered = ["rec_root", "rec_network", "rec_new"];
+
+        let missing: Vec<&str> = discovered
+            .into_iter()
+            .filter(|id| !state.contains("cloudflare_dns_record", id))
+            .collect();
+
+        // rec_root is managed, but by the root module, so it is outside the diff.
+        assert_eq!(missing, vec!["rec_root", "rec_new"]);
+    }
+
+    #[test]
+    fn test_in_module_matches_instance_keys() {
+        let resource = StateResource {
+            module: Some("module.zones[\"example.com\"]".to_string()),
+            mode: "managed".to_string(),
+            resource_type: "cloudflare_dns_record".to_string(),
+            name: "www".to_string(),
+            instances: vec![],
+        };
+        assert!(resource.in_module("module.zones"));
+        assert!(!resource.in_module("module.zone"));
+    }
+
+    const CLOUDFLARE_STATE: &str = include_str!("../../tests/fixtures/cloudflare.tfstate");
+
+    fn key(resource_type: &str, id: &str) -> (String, String) {
+        (resource_type.to_string(), id.to_string())
+    }
+
+    #[test]
+    fn test_resource_ids_from_cloudflare_fixture() {
+        let state = TerraformState::from_reader(CLOUDFLARE_STATE.as_bytes()).unwrap();
+
+        assert_eq!(
+            state.resource_ids(),
+            HashSet::from([
+                key("cloudflare_dns_record", "372e67954025e0ba6aaa6d586b9e0b59"),
+                key("cloudflare_dns_record", "9a7806061c88ada191ed06f989cc3dac"),
+                key("cloudflare_dns_record", "b1946ac92492d2347c6235b4d2611184"),
+                key("cloudflare_ruleset", "2f2feab2026849078ba485f918791bdc"),
+                key("cloudflare_ruleset", "4814384a9e5d4991b9815dcfc25d2f1f"),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_resource_ids_skip_data_sources() {
+        let state = TerraformState::from_reader(CLOUDFLARE_STATE.as_bytes()).unwrap();
+
+        assert!(
+            !state
+                .resource_ids()
+                .iter()
+                .any(|(resource_type, _)| resource_type == "cloudflare_zone")
+        );
+    }
+
+    #[test]
+    fn test_from_reader_rejects_other_versions() {
+        let err =
+            TerraformState::from_reader(r#"{"version": 3, "modules": []}"#.as_bytes()).unwrap_err();
+        assert!(err.to_string().contains("unsupported state version 3"));
+    }
+
+    #[test]
+    fn test_parse_invalid_json_is_state_error() {
+        let err = TerraformState::parse("not json").unwrap_err();
+        assert!(matches!(err, TiaError::State(_)));
+    }
+}
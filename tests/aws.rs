@@ -0,0 +1,192 @@
+use tia::DiscoverConfig;
+use tia::providers::aws::{AwsCredentials, AwsProvider};
+use tia::providers::{Provider, ProviderError};
+use wiremock::matchers::{header_exists, method, path, query_param, query_param_is_missing};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const ZONE_ID: &str = "Z1D633PJN98FT9";
+
+fn provider() -> AwsProvider {
+    AwsProvider::new(Some(AwsCredentials {
+        access_key_id: "AKIDEXAMPLE".to_string(),
+        secret_access_key: "secret".to_string(),
+        session_token: None,
+    }))
+}
+
+fn config(mock_server: &MockServer, zones: &[&str]) -> DiscoverConfig {
+    DiscoverConfig {
+        zones: zones.iter().map(|z| z.to_string()).collect(),
+        api_base_url: Some(mock_server.uri()),
+        ..Default::default()
+    }
+}
+
+fn xml(body: &str) -> ResponseTemplate {
+    ResponseTemplate::new(200).set_body_raw(
+        format!(r#"<?xml version="1.0" encoding="UTF-8"?>{}"#, body),
+        "text/xml",
+    )
+}
+
+async fn mount_hosted_zones(mock_server: &MockServer) {
+    Mock::given(method("GET"))
+        .and(path("/hostedzone"))
+        .and(header_exists("authorization"))
+        .and(header_exists("x-amz-date"))
+        .respond_with(xml(&format!(
+            "<ListHostedZonesResponse><HostedZones>\
+             <HostedZone><Id>/hostedzone/{}</Id><Name>example.com.</Name></HostedZone>\
+             <HostedZone><Id>/hostedzone/Z2OTHER</Id><Name>other.com.</Name></HostedZone>\
+             </HostedZones><IsTruncated>false</IsTruncated><MaxItems>100</MaxItems>\
+             </ListHostedZonesResponse>",
+            ZONE_ID
+        )))
+        .mount(mock_server)
+        .await;
+}
+
+#[tokio::test]
+async fn test_discover_route53_records_across_pages() {
+    let mock_server = MockServer::start().await;
+    mount_hosted_zones(&mock_server).await;
+
+    Mock::given(method("GET"))
+        .and(path(format!("/hostedzone/{}/rrset", ZONE_ID)))
+        .and(query_param_is_missing("name"))
+        .respond_with(xml("<ListResourceRecordSetsResponse><ResourceRecordSets>\
+             <ResourceRecordSet><Name>example.com.</Name><Type>A</Type><TTL>300</TTL>\
+             <ResourceRecords><ResourceRecord><Value>192.0.2.1</Value></ResourceRecord>\
+             <ResourceRecord><Value>192.0.2.2</Value></ResourceRecord></ResourceRecords>\
+             </ResourceRecordSet></ResourceRecordSets>\
+             <IsTruncated>true</IsTruncated><NextRecordName>\\052.example.com.</NextRecordName>\
+             <NextRecordType>CNAME</NextRecordType><MaxItems>1</MaxItems>\
+             </ListResourceRecordSetsResponse>"))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(format!("/hostedzone/{}/rrset", ZONE_ID)))
+        .and(query_param("name", "\\052.example.com."))
+        .and(query_param("type", "CNAME"))
+        .respond_with(xml("<ListResourceRecordSetsResponse><ResourceRecordSets>\
+             <ResourceRecordSet><Name>\\052.example.com.</Name><Type>CNAME</Type><TTL>60</TTL>\
+             <ResourceRecords><ResourceRecord><Value>example.com</Value></ResourceRecord>\
+             </ResourceRecords></ResourceRecordSet></ResourceRecordSets>\
+             <IsTruncated>false</IsTruncated><MaxItems>1</MaxItems>\
+             </ListResourceRecordSetsResponse>"))
+        .mount(&mock_server)
+        .await;
+
+    let resources = provider()
+        .discover(&config(&mock_server, &["example.com"]))
+        .await
+        .unwrap();
+
+    let ids: Vec<&str> = resources.iter().map(|r| r.resource_id.as_str()).collect();
+    assert_eq!(
+        ids,
+        vec![
+            "Z1D633PJN98FT9_example.com_A",
+            "Z1D633PJN98FT9_*.example.com_CNAME"
+        ]
+    );
+    assert!(
+        resources
+            .iter()
+            .all(|r| r.resource_type == "aws_route53_record")
+    );
+    assert!(resources.iter().all(|r| r.zone_id == ZONE_ID));
+    assert_eq!(resources[1].name, "*.example.com");
+    assert_eq!(
+        resources[0].metadata,
+        serde_json::json!({ "type": "A", "records": ["192.0.2.1", "192.0.2.2"], "ttl": 300 })
+    );
+    assert!(
+        provider()
+            .generate_imports(&resources)
+            .contains("id = \"Z1D633PJN98FT9_*.example.com_CNAME\"")
+    );
+}
+
+#[tokio::test]
+async fn test_discover_selects_zone_by_id() {
+    let mock_server = MockServer::start().await;
+    mount_hosted_zones(&mock_server).await;
+    Mock::given(method("GET"))
+        .and(path("/hostedzone/Z2OTHER/rrset"))
+        .respond_with(xml("<ListResourceRecordSetsResponse><ResourceRecordSets>\
+             <ResourceRecordSet><Name>other.com.</Name><Type>MX</Type><TTL>300</TTL>\
+             <ResourceRecords><ResourceRecord><Value>10 mail.other.com</Value></ResourceRecord>\
+             </ResourceRecords></ResourceRecordSet></ResourceRecordSets>\
+             <IsTruncated>false</IsTruncated></ListResourceRecordSetsResponse>"))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let resources = provider()
+        .discover(&config(&mock_server, &["Z2OTHER"]))
+        .await
+        .unwrap();
+
+    assert_eq!(resources.len(), 1);
+    assert_eq!(resources[0].resource_id, "Z2OTHER_other.com_MX");
+}
+
+#[tokio::test]
+async fn test_unknown_hosted_zone_is_not_found() {
+    let mock_server = MockServer::start().await;
+    mount_hosted_zones(&mock_server).await;
+
+    let err = provider()
+        .discover(&config(&mock_server, &["missing.com"]))
+        .await
+        .unwrap_err();
+
+    match err {
+        ProviderError::NotFound { kind, name } => {
+            assert_eq!(kind, "hosted zone");
+            assert_eq!(name, "missing.com");
+        }
+        other => panic!("expected NotFound, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_access_denied_is_permission_denied() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/hostedzone"))
+        .respond_with(ResponseTemplate::new(403).set_body_raw(
+            "<ErrorResponse><Error><Type>Sender</Type><Code>AccessDenied</Code>\
+             <Message>User is not authorized to perform route53:ListHostedZones</Message>\
+             </Error></ErrorResponse>",
+            "text/xml",
+        ))
+        .mount(&mock_server)
+        .await;
+
+    let err = provider()
+        .discover(&config(&mock_server, &["example.com"]))
+        .await
+        .unwrap_err();
+
+    match err {
+        ProviderError::PermissionDenied(message) => {
+            assert!(message.contains("route53:ListHostedZones"))
+        }
+        other => panic!("expected PermissionDenied, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_missing_credentials_is_auth_error() {
+    let mock_server = MockServer::start().await;
+
+    let err = AwsProvider::new(None)
+        .discover(&config(&mock_server, &["example.com"]))
+        .await
+        .unwrap_err();
+
+    assert!(matches!(err, ProviderError::Auth(_)));
+    assert!(mock_server.received_requests().await.unwrap().is_empty());
+}
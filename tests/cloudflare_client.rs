@@ -1,5 +1,15 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use tia::cache::ZoneCache;
+use tia::providers::cloudflare::{PageProgress, PageToken, PaginationStrategy, RequestOptions};
 use tia::{CloudflareClient, CloudflareError};
-use wiremock::matchers::{method, path, query_param, query_param_is_missing};
+use wiremock::matchers::{
+    header, header_exists, method, path, query_param, query_param_is_missing,
+};
 use wiremock::{Mock, MockServer, ResponseTemplate};
 
 #[tokio::test]
@@ -59,7 +69,10 @@ async fn test_discover_rulesets_phase_filtering() {
         "http_request_transform",
         "http_request_firewall_custom",
     ];
-    let result = client.discover_rulesets("zone123", phases).await.unwrap();
+    let result = client
+        .discover_rulesets("zone123", phases, false)
+        .await
+        .unwrap();
 
     assert_eq!(result.len(), 3);
     assert_eq!(result[0].id, "rs_redirect");
@@ -70,6 +83,75 @@ async fn test_discover_rulesets_phase_filtering() {
     assert_eq!(result[2].phase, "http_request_firewall_custom");
 }
 
+#[tokio::test]
+async fn test_discover_rulesets_excludes_managed_kind_in_matching_phase() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/zones/zone123/rulesets"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": true,
+            "errors": [],
+            "result": [
+                {
+                    "id": "rs_zone",
+                    "name": "Custom Firewall Rules",
+                    "phase": "http_request_firewall_custom",
+                    "kind": "zone"
+                },
+                {
+                    "id": "rs_managed",
+                    "name": "Cloudflare Custom Defaults",
+                    "phase": "http_request_firewall_custom",
+                    "kind": "managed"
+                },
+                {
+                    "id": "rs_redirect",
+                    "name": "Redirects",
+                    "phase": "http_request_dynamic_redirect",
+                    "kind": "zone"
+                }
+            ],
+            "result_info": { "cursors": {} }
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client =
+        CloudflareClient::with_base_url("test_token".to_string(), mock_server.uri()).unwrap();
+
+    let result = client
+        .discover_rulesets(
+            "zone123",
+            &[
+                "http_request_firewall_custom",
+                "http_request_dynamic_redirect",
+            ],
+            false,
+        )
+        .await
+        .unwrap();
+
+    let ids: Vec<&str> = result.iter().map(|r| r.id.as_str()).collect();
+    assert_eq!(ids, vec!["rs_zone", "rs_redirect"]);
+    assert!(result.iter().all(|r| r.kind.as_deref() == Some("zone")));
+
+    let result = client
+        .discover_rulesets(
+            "zone123",
+            &[
+                "http_request_firewall_custom",
+                "http_request_dynamic_redirect",
+            ],
+            true,
+        )
+        .await
+        .unwrap();
+
+    let ids: Vec<&str> = result.iter().map(|r| r.id.as_str()).collect();
+    assert_eq!(ids, vec!["rs_zone", "rs_managed", "rs_redirect"]);
+}
+
 #[tokio::test]
 async fn test_discover_rulesets_cursor_pagination() {
     let mock_server = MockServer::start().await;
@@ -114,7 +196,10 @@ async fn test_discover_rulesets_cursor_pagination() {
         CloudflareClient::with_base_url("test_token".to_string(), mock_server.uri()).unwrap();
 
     let phases = &["http_request_dynamic_redirect", "http_request_transform"];
-    let result = client.discover_rulesets("zone123", phases).await.unwrap();
+    let result = client
+        .discover_rulesets("zone123", phases, false)
+        .await
+        .unwrap();
 
     assert_eq!(result.len(), 2);
     assert_eq!(result[0].id, "rs_page1");
@@ -155,7 +240,10 @@ async fn test_discover_rulesets_excludes_non_discoverable_phases() {
         "http_request_transform",
         "http_request_firewall_custom",
     ];
-    let result = client.discover_rulesets("zone123", phases).await.unwrap();
+    let result = client
+        .discover_rulesets("zone123", phases, false)
+        .await
+        .unwrap();
 
     assert!(result.is_empty());
 }
@@ -179,7 +267,10 @@ async fn test_discover_rulesets_empty_response() {
         CloudflareClient::with_base_url("test_token".to_string(), mock_server.uri()).unwrap();
 
     let phases = &["http_request_dynamic_redirect"];
-    let result = client.discover_rulesets("zone123", phases).await.unwrap();
+    let result = client
+        .discover_rulesets("zone123", phases, false)
+        .await
+        .unwrap();
 
     assert!(result.is_empty());
 }
@@ -230,6 +321,33 @@ async fn test_verify_auth_invalid_token() {
     }
 }
 
+#[tokio::test]
+async fn test_slow_response_fails_with_timeout() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/user/tokens/verify"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({
+                    "success": true,
+                    "result": { "id": "abc123", "status": "active" }
+                }))
+                .set_delay(Duration::from_secs(2)),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let client = CloudflareClient::with_base_url("valid_token".to_string(), mock_server.uri())
+        .unwrap()
+        .with_timeout(Duration::from_millis(100))
+        .unwrap();
+
+    let err = client.verify_auth().await.unwrap_err();
+    assert!(matches!(err, CloudflareError::Timeout(_)), "{:?}", err);
+    assert!(err.to_string().starts_with("request timed out"));
+}
+
 #[tokio::test]
 async fn test_verify_auth_error_does_not_contain_token() {
     let mock_server = MockServer::start().await;
@@ -288,6 +406,79 @@ async fn test_lookup_zone_by_name_success() {
     let zone_info = result.unwrap();
     assert_eq!(zone_info.zone_id, "023e105f4ecef8ad9ca31a8372d0c353");
     assert_eq!(zone_info.account_id, "01a7362d577a6c3019a474fd6f485823");
+    assert_eq!(zone_info.name, "example.com");
+}
+
+fn temp_zone_cache(name: &str) -> ZoneCache {
+    let dir =
+        std::env::temp_dir().join(format!("tia-client-zones-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    ZoneCache::new(dir.join("zones.json"))
+}
+
+async fn mount_zone_by_name(mock_server: &MockServer, expected_calls: u64) {
+    Mock::given(method("GET"))
+        .and(path("/zones"))
+        .and(query_param("name", "example.com"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": true,
+            "errors": [],
+            "result": [{
+                "id": "023e105f4ecef8ad9ca31a8372d0c353",
+                "name": "example.com",
+                "account": { "id": "01a7362d577a6c3019a474fd6f485823", "name": "Test Account" }
+            }]
+        })))
+        .expect(expected_calls)
+        .mount(mock_server)
+        .await;
+}
+
+#[tokio::test]
+async fn test_lookup_zone_served_from_cache_on_repeat() {
+    let mock_server = MockServer::start().await;
+    mount_zone_by_name(&mock_server, 1).await;
+    let cache = temp_zone_cache("repeat");
+
+    let client = CloudflareClient::with_base_url("test_token".to_string(), mock_server.uri())
+        .unwrap()
+        .with_zone_cache(cache.clone(), Duration::from_secs(300));
+
+    let first = client.lookup_zone("example.com").await.unwrap();
+    let second = client.lookup_zone("example.com").await.unwrap();
+
+    assert_eq!(first, second);
+    assert_eq!(second.account_id, "01a7362d577a6c3019a474fd6f485823");
+    assert!(cache.path().exists());
+}
+
+#[tokio::test]
+async fn test_lookup_zone_expired_cache_falls_through_to_api() {
+    let mock_server = MockServer::start().await;
+    mount_zone_by_name(&mock_server, 2).await;
+
+    let client = CloudflareClient::with_base_url("test_token".to_string(), mock_server.uri())
+        .unwrap()
+        .with_zone_cache(temp_zone_cache("expired"), Duration::ZERO);
+
+    client.lookup_zone("example.com").await.unwrap();
+    client.lookup_zone("example.com").await.unwrap();
+}
+
+#[tokio::test]
+async fn test_lookup_zone_corrupt_cache_falls_through_to_api() {
+    let mock_server = MockServer::start().await;
+    mount_zone_by_name(&mock_server, 1).await;
+    let cache = temp_zone_cache("corrupt");
+    std::fs::create_dir_all(cache.path().parent().unwrap()).unwrap();
+    std::fs::write(cache.path(), b"\x00not json").unwrap();
+
+    let client = CloudflareClient::with_base_url("test_token".to_string(), mock_server.uri())
+        .unwrap()
+        .with_zone_cache(cache, Duration::from_secs(300));
+
+    let zone_info = client.lookup_zone("example.com").await.unwrap();
+    assert_eq!(zone_info.zone_id, "023e105f4ecef8ad9ca31a8372d0c353");
 }
 
 #[tokio::test]
@@ -321,6 +512,7 @@ async fn test_lookup_zone_by_id_success() {
     let zone_info = result.unwrap();
     assert_eq!(zone_info.zone_id, zone_id);
     assert_eq!(zone_info.account_id, "01a7362d577a6c3019a474fd6f485823");
+    assert_eq!(zone_info.name, "example.com");
 }
 
 #[tokio::test]
@@ -365,6 +557,17 @@ async fn test_lookup_zone_not_found_by_id() {
         })))
         .mount(&mock_server)
         .await;
+    Mock::given(method("GET"))
+        .and(path("/zones"))
+        .and(query_param("name", zone_id))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": true,
+            "errors": [],
+            "result": []
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
 
     let client =
         CloudflareClient::with_base_url("test_token".to_string(), mock_server.uri()).unwrap();
@@ -379,6 +582,46 @@ async fn test_lookup_zone_not_found_by_id() {
     }
 }
 
+#[tokio::test]
+async fn test_lookup_zone_id_style_name_falls_back_to_name_lookup() {
+    let mock_server = MockServer::start().await;
+    let hex_name = "deadbeefdeadbeefdeadbeefdeadbeef";
+
+    Mock::given(method("GET"))
+        .and(path(format!("/zones/{}", hex_name)))
+        .respond_with(ResponseTemplate::new(404).set_body_json(serde_json::json!({
+            "success": false,
+            "errors": [{ "code": 7003, "message": "Could not route to /zones/deadbeefdeadbeefdeadbeefdeadbeef, perhaps your object identifier is invalid?" }]
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/zones"))
+        .and(query_param("name", hex_name))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": true,
+            "errors": [],
+            "result": [{
+                "id": "023e105f4ecef8ad9ca31a8372d0c353",
+                "name": hex_name,
+                "account": { "id": "acc456", "name": "Test Account" }
+            }]
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client =
+        CloudflareClient::with_base_url("test_token".to_string(), mock_server.uri()).unwrap();
+
+    let info = client.lookup_zone(hex_name).await.unwrap();
+
+    assert_eq!(info.zone_id, "023e105f4ecef8ad9ca31a8372d0c353");
+    assert_eq!(info.name, hex_name);
+    assert_eq!(info.account_id, "acc456");
+}
+
 #[tokio::test]
 async fn test_lookup_zone_permission_denied() {
     let mock_server = MockServer::start().await;
@@ -406,6 +649,85 @@ async fn test_lookup_zone_permission_denied() {
     }
 }
 
+async fn mount_dns_record_pages(mock_server: &MockServer, second_page: ResponseTemplate) {
+    Mock::given(method("GET"))
+        .and(path("/zones/zone123/dns_records"))
+        .and(query_param("page", "1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": true,
+            "errors": [],
+            "result": [
+                { "id": "r1", "name": "a.example.com", "type": "A" },
+                { "id": "r2", "name": "b.example.com", "type": "A" }
+            ],
+            "result_info": { "page": 1, "per_page": 100, "total_count": 101 }
+        })))
+        .mount(mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/zones/zone123/dns_records"))
+        .and(query_param("page", "2"))
+        .respond_with(second_page)
+        .mount(mock_server)
+        .await;
+}
+
+#[tokio::test]
+async fn test_stream_dns_records_fetches_pages_as_they_are_consumed() {
+    use futures::StreamExt;
+
+    let mock_server = MockServer::start().await;
+    mount_dns_record_pages(
+        &mock_server,
+        ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": true,
+            "errors": [],
+            "result": [{ "id": "r3", "name": "c.example.com", "type": "A" }],
+            "result_info": { "page": 2, "per_page": 100, "total_count": 101 }
+        })),
+    )
+    .await;
+    let client =
+        CloudflareClient::with_base_url("test_token".to_string(), mock_server.uri()).unwrap();
+
+    let mut records = Box::pin(client.stream_dns_records("zone123"));
+
+    assert_eq!(records.next().await.unwrap().unwrap().id, "r1");
+    assert_eq!(records.next().await.unwrap().unwrap().id, "r2");
+    // The second page isn't requested until the first one has been consumed.
+    assert_eq!(mock_server.received_requests().await.unwrap().len(), 1);
+    assert_eq!(records.next().await.unwrap().unwrap().id, "r3");
+    assert!(records.next().await.is_none());
+    assert_eq!(mock_server.received_requests().await.unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_stream_dns_records_ends_at_first_error() {
+    use futures::StreamExt;
+
+    let mock_server = MockServer::start().await;
+    mount_dns_record_pages(
+        &mock_server,
+        ResponseTemplate::new(400).set_body_json(serde_json::json!({
+            "success": false,
+            "errors": [{ "code": 1000, "message": "bad page" }],
+            "result": null
+        })),
+    )
+    .await;
+    let client =
+        CloudflareClient::with_base_url("test_token".to_string(), mock_server.uri()).unwrap();
+
+    let results: Vec<_> = client.stream_dns_records("zone123").collect().await;
+
+    assert_eq!(results.len(), 3);
+    assert!(results[..2].iter().all(Result::is_ok));
+    assert!(matches!(
+        &results[2],
+        Err(CloudflareError::Api { status: 400, .. })
+    ));
+}
+
 #[tokio::test]
 async fn test_fetch_all_pages_multiple_pages() {
     let mock_server = MockServer::start().await;
@@ -504,7 +826,8 @@ async fn test_fetch_all_pages_api_error() {
         .await;
 
     assert!(result.is_err());
-    if let Err(CloudflareError::Api { message, .. }) = result {
+    if let Err(CloudflareError::Api { status, message }) = result {
+        assert_eq!(status, 403);
         assert!(message.contains("Access denied"));
     } else {
         panic!("Expected CloudflareError::Api");
@@ -565,76 +888,368 @@ async fn test_fetch_all_cursors_multiple_pages() {
 }
 
 #[tokio::test]
-async fn test_fetch_all_cursors_api_error() {
+async fn test_fetch_all_cursors_encodes_the_cursor() {
     let mock_server = MockServer::start().await;
 
     Mock::given(method("GET"))
         .and(path("/rulesets"))
-        .respond_with(ResponseTemplate::new(500).set_body_json(serde_json::json!({
-            "success": false,
-            "errors": [{ "code": 5000, "message": "Internal server error" }]
+        .and(query_param_is_missing("cursor"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": true,
+            "errors": [],
+            "result": [{"id": "rs1"}],
+            "result_info": { "cursors": { "after": "a+b/c=&d" } }
+        })))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/rulesets"))
+        .and(query_param("cursor", "a+b/c=&d"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": true,
+            "errors": [],
+            "result": [{"id": "rs2"}],
+            "result_info": { "cursors": {} }
         })))
+        .expect(1)
         .mount(&mock_server)
         .await;
 
     let client =
         CloudflareClient::with_base_url("test_token".to_string(), mock_server.uri()).unwrap();
 
-    let result: Result<Vec<String>, _> = client
+    let results = client
         .fetch_all_cursors(
             &format!("{}/rulesets", mock_server.uri()),
-            10,
-            |json| async move {
-                let items: Vec<serde_json::Value> =
-                    serde_json::from_value(json).unwrap_or_default();
-                Ok(items
-                    .into_iter()
-                    .map(|v| v["id"].as_str().unwrap().to_string())
-                    .collect())
-            },
+            2,
+            |json| async move { Ok(ids(json)) },
         )
-        .await;
+        .await
+        .unwrap();
 
-    assert!(result.is_err());
-    if let Err(CloudflareError::Api { message, .. }) = result {
-        assert!(message.contains("Internal server error"));
-    } else {
-        panic!("Expected CloudflareError::Api");
-    }
+    assert_eq!(results, vec!["rs1", "rs2"]);
+}
+
+fn recording_client(mock_server: &MockServer) -> (CloudflareClient, Arc<Mutex<Vec<PageProgress>>>) {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let recorder = seen.clone();
+    let client = CloudflareClient::with_base_url("test_token".to_string(), mock_server.uri())
+        .unwrap()
+        .with_progress(move |progress| recorder.lock().unwrap().push(progress.clone()));
+    (client, seen)
+}
+
+fn ids(json: serde_json::Value) -> Vec<String> {
+    let items: Vec<serde_json::Value> = serde_json::from_value(json).unwrap_or_default();
+    items
+        .into_iter()
+        .map(|v| v["id"].as_str().unwrap().to_string())
+        .collect()
 }
 
 #[tokio::test]
-async fn test_discover_page_rules_success() {
+async fn test_fetch_all_pages_reports_progress_per_page() {
     let mock_server = MockServer::start().await;
+    for (page, result) in [(1, vec!["r1", "r2"]), (2, vec!["r3"])] {
+        Mock::given(method("GET"))
+            .and(path("/dns_records"))
+            .and(query_param("page", page.to_string()))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "success": true,
+                "errors": [],
+                "result": result.iter().map(|id| serde_json::json!({ "id": id })).collect::<Vec<_>>(),
+                "result_info": { "page": page, "per_page": 2, "total_count": 3 }
+            })))
+            .mount(&mock_server)
+            .await;
+    }
+    let (client, seen) = recording_client(&mock_server);
+
+    client
+        .fetch_all_pages(
+            &format!("{}/dns_records", mock_server.uri()),
+            2,
+            |json| async move { Ok(ids(json)) },
+        )
+        .await
+        .unwrap();
+
+    let progress = |pages, items| PageProgress {
+        path: "/dns_records".to_string(),
+        pages,
+        items,
+    };
+    assert_eq!(*seen.lock().unwrap(), vec![progress(1, 2), progress(2, 3)]);
+}
 
+#[tokio::test]
+async fn test_fetch_all_cursors_reports_progress_per_page() {
+    let mock_server = MockServer::start().await;
     Mock::given(method("GET"))
-        .and(path("/zones/zone123/pagerules"))
+        .and(path("/rulesets"))
+        .and(query_param_is_missing("cursor"))
         .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
             "success": true,
             "errors": [],
-            "messages": [],
-            "result": [
-                {
-                    "id": "rule_abc",
-                    "status": "active",
-                    "priority": 1,
-                    "targets": [{
-                        "target": "url",
-                        "constraint": { "operator": "matches", "value": "*example.com/images/*" }
-                    }],
-                    "actions": [{ "id": "browser_check", "value": "on" }]
-                },
-                {
-                    "id": "rule_def",
-                    "status": "disabled",
-                    "priority": 2,
-                    "targets": [{
-                        "target": "url",
-                        "constraint": { "operator": "matches", "value": "*example.com/api/*" }
-                    }],
-                    "actions": [{ "id": "forwarding_url", "value": {"url": "https://api.example.com", "status_code": 301} }]
-                }
-            ]
+            "result": [{"id": "rs1"}, {"id": "rs2"}],
+            "result_info": { "cursors": { "after": "cursor_abc" } }
+        })))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/rulesets"))
+        .and(query_param("cursor", "cursor_abc"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": true,
+            "errors": [],
+            "result": [{"id": "rs3"}],
+            "result_info": { "cursors": {} }
+        })))
+        .mount(&mock_server)
+        .await;
+    let (client, seen) = recording_client(&mock_server);
+
+    client
+        .fetch_all_cursors(
+            &format!("{}/rulesets", mock_server.uri()),
+            2,
+            |json| async move { Ok(ids(json)) },
+        )
+        .await
+        .unwrap();
+
+    let seen = seen.lock().unwrap();
+    assert_eq!(seen.len(), 2);
+    assert_eq!((seen[1].pages, seen[1].items), (2, 3));
+    assert_eq!(seen[1].path, "/rulesets");
+}
+
+#[tokio::test]
+async fn test_fetch_page_page_based_returns_single_page_and_next_token() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/dns_records"))
+        .and(query_param("page", "2"))
+        .and(query_param("per_page", "2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": true,
+            "errors": [],
+            "result": [{"id": "r3"}, {"id": "r4"}],
+            "result_info": { "page": 2, "per_page": 2, "total_count": 5 }
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    let client =
+        CloudflareClient::with_base_url("test_token".to_string(), mock_server.uri()).unwrap();
+
+    let page = client
+        .fetch_page(
+            &format!("{}/dns_records", mock_server.uri()),
+            PaginationStrategy::PageBased,
+            2,
+            Some(&PageToken::Page(2)),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(ids(serde_json::Value::Array(page.items)), vec!["r3", "r4"]);
+    assert_eq!(page.total_count, Some(5));
+    assert!(page.has_more);
+    assert_eq!(page.next, Some(PageToken::Page(3)));
+}
+
+#[tokio::test]
+async fn test_fetch_page_cursor_based_returns_cursor_until_last_page() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/rulesets"))
+        .and(query_param_is_missing("cursor"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": true,
+            "errors": [],
+            "result": [{"id": "rs1"}],
+            "result_info": { "cursors": { "after": "cursor_abc" } }
+        })))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/rulesets"))
+        .and(query_param("cursor", "cursor_abc"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": true,
+            "errors": [],
+            "result": [{"id": "rs2"}],
+            "result_info": { "cursors": {} }
+        })))
+        .mount(&mock_server)
+        .await;
+    let client =
+        CloudflareClient::with_base_url("test_token".to_string(), mock_server.uri()).unwrap();
+    let url = format!("{}/rulesets", mock_server.uri());
+
+    let first = client
+        .fetch_page(&url, PaginationStrategy::CursorBased, 1, None)
+        .await
+        .unwrap();
+    assert_eq!(ids(serde_json::Value::Array(first.items)), vec!["rs1"]);
+    assert_eq!(
+        first.next,
+        Some(PageToken::Cursor("cursor_abc".to_string()))
+    );
+
+    let last = client
+        .fetch_page(
+            &url,
+            PaginationStrategy::CursorBased,
+            1,
+            first.next.as_ref(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(ids(serde_json::Value::Array(last.items)), vec!["rs2"]);
+    assert!(!last.has_more);
+    assert_eq!(last.next, None);
+}
+
+#[tokio::test]
+async fn test_fetch_all_cursors_api_error() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/rulesets"))
+        .respond_with(ResponseTemplate::new(500).set_body_json(serde_json::json!({
+            "success": false,
+            "errors": [{ "code": 5000, "message": "Internal server error" }]
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = CloudflareClient::with_base_url("test_token".to_string(), mock_server.uri())
+        .unwrap()
+        .with_retry_config(2, Duration::from_millis(1));
+
+    let result: Result<Vec<String>, _> = client
+        .fetch_all_cursors(
+            &format!("{}/rulesets", mock_server.uri()),
+            10,
+            |json| async move {
+                let items: Vec<serde_json::Value> =
+                    serde_json::from_value(json).unwrap_or_default();
+                Ok(items
+                    .into_iter()
+                    .map(|v| v["id"].as_str().unwrap().to_string())
+                    .collect())
+            },
+        )
+        .await;
+
+    assert!(result.is_err());
+    if let Err(CloudflareError::Api { status, message }) = result {
+        assert_eq!(status, 500);
+        assert!(message.contains("Internal server error"));
+    } else {
+        panic!("Expected CloudflareError::Api");
+    }
+}
+
+#[tokio::test]
+async fn test_fetch_all_pages_html_error_page_is_invalid_response() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/dns_records"))
+        .respond_with(ResponseTemplate::new(502).set_body_string("<html>Bad Gateway</html>"))
+        .mount(&mock_server)
+        .await;
+
+    let client = CloudflareClient::with_base_url("test_token".to_string(), mock_server.uri())
+        .unwrap()
+        .with_retry_on(vec![]);
+
+    let result: Result<Vec<serde_json::Value>, _> = client
+        .fetch_all_pages(
+            &format!("{}/dns_records", mock_server.uri()),
+            10,
+            |json| async move { Ok(serde_json::from_value(json).unwrap_or_default()) },
+        )
+        .await;
+
+    if let Err(CloudflareError::InvalidResponse { status, snippet }) = result {
+        assert_eq!(status, 502);
+        assert_eq!(snippet, "<html>Bad Gateway</html>");
+    } else {
+        panic!(
+            "Expected CloudflareError::InvalidResponse, got {:?}",
+            result
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_verify_auth_and_lookup_zone_report_html_as_invalid_response() {
+    let mock_server = MockServer::start().await;
+    let page = format!(
+        "<!DOCTYPE html><html><head><title>Access denied</title></head><body>{}</body></html>",
+        "<p>Please try again later.</p>".repeat(20)
+    );
+
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(403).set_body_string(page))
+        .mount(&mock_server)
+        .await;
+
+    let client = CloudflareClient::with_base_url("test_token".to_string(), mock_server.uri())
+        .unwrap()
+        .with_retry_on(vec![]);
+
+    for result in [
+        client.verify_auth().await.map(|_| ()),
+        client.lookup_zone("example.com").await.map(|_| ()),
+    ] {
+        match result {
+            Err(CloudflareError::InvalidResponse { status, snippet }) => {
+                assert_eq!(status, 403);
+                assert_eq!(snippet.chars().count(), 200);
+                assert!(snippet.starts_with("<!DOCTYPE html>"));
+            }
+            other => panic!("Expected CloudflareError::InvalidResponse, got {:?}", other),
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_discover_page_rules_success() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/zones/zone123/pagerules"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": true,
+            "errors": [],
+            "messages": [],
+            "result": [
+                {
+                    "id": "rule_abc",
+                    "status": "active",
+                    "priority": 1,
+                    "targets": [{
+                        "target": "url",
+                        "constraint": { "operator": "matches", "value": "*example.com/images/*" }
+                    }],
+                    "actions": [{ "id": "browser_check", "value": "on" }]
+                },
+                {
+                    "id": "rule_def",
+                    "status": "disabled",
+                    "priority": 2,
+                    "targets": [{
+                        "target": "url",
+                        "constraint": { "operator": "matches", "value": "*example.com/api/*" }
+                    }],
+                    "actions": [{ "id": "forwarding_url", "value": {"url": "https://api.example.com", "status_code": 301} }]
+                }
+            ]
         })))
         .mount(&mock_server)
         .await;
@@ -702,3 +1317,1409 @@ async fn test_discover_page_rules_empty_response() {
     let result = client.discover_page_rules("zone123").await.unwrap();
     assert!(result.is_empty());
 }
+
+#[tokio::test]
+async fn test_discover_load_balancers_pagination() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/zones/zone123/load_balancers"))
+        .and(query_param("page", "1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": true,
+            "errors": [],
+            "result": [
+                {
+                    "id": "lb_1",
+                    "name": "www.example.com",
+                    "default_pools": ["pool_a", "pool_b"],
+                    "fallback_pool": "pool_a",
+                    "proxied": true
+                }
+            ],
+            "result_info": { "page": 1, "per_page": 100, "total_count": 101 }
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/zones/zone123/load_balancers"))
+        .and(query_param("page", "2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": true,
+            "errors": [],
+            "result": [
+                { "id": "lb_2", "name": "api.example.com", "default_pools": ["pool_b"] }
+            ],
+            "result_info": { "page": 2, "per_page": 100, "total_count": 101 }
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client =
+        CloudflareClient::with_base_url("test_token".to_string(), mock_server.uri()).unwrap();
+
+    let load_balancers = client.discover_load_balancers("zone123").await.unwrap();
+
+    assert_eq!(load_balancers.len(), 2);
+    assert_eq!(load_balancers[0].id, "lb_1");
+    assert_eq!(load_balancers[0].default_pools, vec!["pool_a", "pool_b"]);
+    assert_eq!(load_balancers[0].fallback_pool.as_deref(), Some("pool_a"));
+    assert_eq!(load_balancers[1].name, "api.example.com");
+    assert_eq!(load_balancers[1].fallback_pool, None);
+}
+
+#[tokio::test]
+async fn test_discover_load_balancer_pools_pagination() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/accounts/acct789/load_balancers/pools"))
+        .and(query_param("page", "1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": true,
+            "errors": [],
+            "result": [
+                { "id": "pool_a", "name": "primary-dc-1", "enabled": true },
+                { "id": "pool_b", "name": "secondary-dc-1", "enabled": true }
+            ],
+            "result_info": { "page": 1, "per_page": 100, "total_count": 103 }
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/accounts/acct789/load_balancers/pools"))
+        .and(query_param("page", "2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": true,
+            "errors": [],
+            "result": [
+                { "id": "pool_c", "name": "failover", "enabled": false }
+            ],
+            "result_info": { "page": 2, "per_page": 100, "total_count": 103 }
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client =
+        CloudflareClient::with_base_url("test_token".to_string(), mock_server.uri()).unwrap();
+
+    let pools = client
+        .discover_load_balancer_pools("acct789")
+        .await
+        .unwrap();
+
+    let ids: Vec<&str> = pools.iter().map(|p| p.id.as_str()).collect();
+    assert_eq!(ids, vec!["pool_a", "pool_b", "pool_c"]);
+    assert_eq!(pools[2].name, "failover");
+}
+
+#[tokio::test]
+async fn test_discover_health_checks_pagination() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/zones/zone123/healthchecks"))
+        .and(query_param("page", "1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": true,
+            "errors": [],
+            "result": [
+                { "id": "hc_1", "name": "origin-1", "address": "192.0.2.1", "type": "HTTPS" },
+                { "id": "hc_2", "name": "origin-2", "address": "192.0.2.2", "type": "TCP" }
+            ],
+            "result_info": { "page": 1, "per_page": 100, "total_count": 101 }
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/zones/zone123/healthchecks"))
+        .and(query_param("page", "2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": true,
+            "errors": [],
+            "result": [
+                { "id": "hc_3", "name": "origin-3", "address": "192.0.2.3", "type": "HTTP" }
+            ],
+            "result_info": { "page": 2, "per_page": 100, "total_count": 101 }
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client =
+        CloudflareClient::with_base_url("test_token".to_string(), mock_server.uri()).unwrap();
+
+    let health_checks = client.discover_health_checks("zone123").await.unwrap();
+
+    let ids: Vec<&str> = health_checks.iter().map(|h| h.id.as_str()).collect();
+    assert_eq!(ids, vec!["hc_1", "hc_2", "hc_3"]);
+    assert_eq!(health_checks[2].name, "origin-3");
+}
+
+#[tokio::test]
+async fn test_discover_spectrum_apps_keeps_protocol() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/zones/zone123/spectrum/apps"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": true,
+            "errors": [],
+            "result": [
+                {
+                    "id": "app_ssh",
+                    "protocol": "tcp/22",
+                    "dns": { "type": "CNAME", "name": "ssh.example.com" }
+                },
+                {
+                    "id": "app_game",
+                    "protocol": "udp/27015-27030",
+                    "dns": { "type": "ADDRESS", "name": "game.example.com" }
+                }
+            ],
+            "result_info": { "page": 1, "per_page": 100, "total_count": 2 }
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client =
+        CloudflareClient::with_base_url("test_token".to_string(), mock_server.uri()).unwrap();
+
+    let apps = client.discover_spectrum_apps("zone123").await.unwrap();
+
+    let apps: Vec<(&str, &str, &str)> = apps
+        .iter()
+        .map(|a| (a.id.as_str(), a.dns.name.as_str(), a.protocol.as_str()))
+        .collect();
+    assert_eq!(
+        apps,
+        vec![
+            ("app_ssh", "ssh.example.com", "tcp/22"),
+            ("app_game", "game.example.com", "udp/27015-27030"),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_discover_spectrum_apps_not_found_is_empty() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/zones/zone123/spectrum/apps"))
+        .respond_with(ResponseTemplate::new(404).set_body_json(serde_json::json!({
+            "success": false,
+            "errors": [{ "code": 10006, "message": "Spectrum is not enabled for this zone" }],
+            "result": null
+        })))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/zones/zone456/spectrum/apps"))
+        .respond_with(ResponseTemplate::new(404).set_body_string("404 page not found"))
+        .mount(&mock_server)
+        .await;
+
+    let client =
+        CloudflareClient::with_base_url("test_token".to_string(), mock_server.uri()).unwrap();
+
+    assert!(
+        client
+            .discover_spectrum_apps("zone123")
+            .await
+            .unwrap()
+            .is_empty()
+    );
+    assert!(
+        client
+            .discover_spectrum_apps("zone456")
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn test_discover_spectrum_apps_other_errors_fail() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/zones/zone123/spectrum/apps"))
+        .respond_with(ResponseTemplate::new(400).set_body_json(serde_json::json!({
+            "success": false,
+            "errors": [{ "code": 1000, "message": "bad request" }],
+            "result": null
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client =
+        CloudflareClient::with_base_url("test_token".to_string(), mock_server.uri()).unwrap();
+
+    let result = client.discover_spectrum_apps("zone123").await;
+    assert!(matches!(
+        result,
+        Err(CloudflareError::Api { status: 400, .. })
+    ));
+}
+
+#[tokio::test]
+async fn test_discover_health_checks_empty_zone() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/zones/zone123/healthchecks"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": true,
+            "errors": [],
+            "result": [],
+            "result_info": { "page": 1, "per_page": 100, "total_count": 0 }
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client =
+        CloudflareClient::with_base_url("test_token".to_string(), mock_server.uri()).unwrap();
+
+    let health_checks = client.discover_health_checks("zone123").await.unwrap();
+    assert!(health_checks.is_empty());
+}
+
+#[tokio::test]
+async fn test_discover_origin_certificates_filters_by_zone_and_pages() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/certificates"))
+        .and(query_param("zone_id", "zone123"))
+        .and(query_param("page", "1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": true,
+            "errors": [],
+            "result": [
+                { "id": "origin1", "hostnames": ["example.com", "*.example.com"] }
+            ],
+            "result_info": { "page": 1, "per_page": 100, "total_count": 101 }
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/certificates"))
+        .and(query_param("zone_id", "zone123"))
+        .and(query_param("page", "2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": true,
+            "errors": [],
+            "result": [
+                { "id": "origin2", "hostnames": ["api.example.com"] }
+            ],
+            "result_info": { "page": 2, "per_page": 100, "total_count": 101 }
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client =
+        CloudflareClient::with_base_url("test_token".to_string(), mock_server.uri()).unwrap();
+    let certificates = client
+        .discover_origin_certificates("zone123")
+        .await
+        .unwrap();
+
+    let ids: Vec<&str> = certificates.iter().map(|c| c.id.as_str()).collect();
+    assert_eq!(ids, vec!["origin1", "origin2"]);
+    assert_eq!(certificates[1].hostnames, vec!["api.example.com"]);
+}
+
+#[tokio::test]
+async fn test_discover_custom_certificates_caps_per_page_at_50() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/zones/zone123/custom_certificates"))
+        .and(query_param("per_page", "50"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": true,
+            "errors": [],
+            "result": [
+                { "id": "cert1", "hosts": ["example.com", "www.example.com"] }
+            ],
+            "result_info": { "page": 1, "per_page": 50, "total_count": 1 }
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = CloudflareClient::with_base_url("test_token".to_string(), mock_server.uri())
+        .unwrap()
+        .with_page_size(500)
+        .unwrap();
+    let certificates = client
+        .discover_custom_certificates("zone123")
+        .await
+        .unwrap();
+
+    assert_eq!(certificates.len(), 1);
+    assert_eq!(
+        certificates[0].hosts,
+        vec!["example.com", "www.example.com"]
+    );
+}
+
+#[tokio::test]
+async fn test_discover_worker_routes_with_and_without_script() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/zones/zone123/workers/routes"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": true,
+            "errors": [],
+            "result": [
+                { "id": "route_api", "pattern": "example.com/api/*", "script": "api-worker" },
+                { "id": "route_static", "pattern": "*example.com/static/*" }
+            ]
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client =
+        CloudflareClient::with_base_url("test_token".to_string(), mock_server.uri()).unwrap();
+
+    let routes = client.discover_worker_routes("zone123").await.unwrap();
+
+    assert_eq!(routes.len(), 2);
+    assert_eq!(routes[0].pattern, "example.com/api/*");
+    assert_eq!(routes[0].script.as_deref(), Some("api-worker"));
+    assert_eq!(routes[1].id, "route_static");
+    assert_eq!(routes[1].script, None);
+}
+
+#[tokio::test]
+async fn test_discover_account_lists_success() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/accounts/acct789/rules/lists"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": true,
+            "errors": [],
+            "result": [
+                { "id": "list_ips", "name": "blocked_ips", "kind": "ip", "num_items": 10 },
+                { "id": "list_hosts", "name": "partner_hosts", "kind": "hostname", "num_items": 2 }
+            ]
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client =
+        CloudflareClient::with_base_url("test_token".to_string(), mock_server.uri()).unwrap();
+
+    let lists = client.discover_account_lists("acct789").await.unwrap();
+
+    let ids: Vec<&str> = lists.iter().map(|l| l.id.as_str()).collect();
+    assert_eq!(ids, vec!["list_ips", "list_hosts"]);
+    assert_eq!(lists[1].kind.as_deref(), Some("hostname"));
+}
+
+fn forbidden() -> ResponseTemplate {
+    ResponseTemplate::new(403).set_body_json(serde_json::json!({
+        "success": false,
+        "errors": [{ "code": 10000, "message": "Authentication error" }],
+        "result": null
+    }))
+}
+
+#[tokio::test]
+async fn test_account_level_discovery_forbidden() {
+    let mock_server = MockServer::start().await;
+    for endpoint in [
+        "rules/lists",
+        "workers/scripts",
+        "pages/projects",
+        "r2/buckets",
+        "challenges/widgets",
+    ] {
+        Mock::given(method("GET"))
+            .and(path(format!("/accounts/acct789/{}", endpoint)))
+            .respond_with(forbidden())
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+    }
+
+    let client =
+        CloudflareClient::with_base_url("test_token".to_string(), mock_server.uri()).unwrap();
+
+    let errors = [
+        (
+            "rules/lists",
+            client.discover_account_lists("acct789").await.unwrap_err(),
+        ),
+        (
+            "workers/scripts",
+            client.discover_worker_scripts("acct789").await.unwrap_err(),
+        ),
+        (
+            "pages/projects",
+            client.discover_pages_projects("acct789").await.unwrap_err(),
+        ),
+        (
+            "r2/buckets",
+            client.discover_r2_buckets("acct789").await.unwrap_err(),
+        ),
+        (
+            "challenges/widgets",
+            client
+                .discover_turnstile_widgets("acct789")
+                .await
+                .unwrap_err(),
+        ),
+    ];
+    for (endpoint, err) in errors {
+        assert!(
+            matches!(err, CloudflareError::Api { status: 403, .. }),
+            "{}: {:?}",
+            endpoint,
+            err
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_discover_worker_scripts_success() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/accounts/acct789/workers/scripts"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": true,
+            "errors": [],
+            "result": [
+                { "id": "edge-router", "etag": "ea95132c", "handlers": ["fetch"] },
+                { "id": "cron-cleanup", "etag": "5f1e0a2b", "handlers": ["scheduled"] }
+            ]
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client =
+        CloudflareClient::with_base_url("test_token".to_string(), mock_server.uri()).unwrap();
+
+    let scripts = client.discover_worker_scripts("acct789").await.unwrap();
+
+    let ids: Vec<&str> = scripts.iter().map(|s| s.id.as_str()).collect();
+    assert_eq!(ids, vec!["edge-router", "cron-cleanup"]);
+}
+
+#[tokio::test]
+async fn test_discover_custom_hostnames_follows_pages() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/zones/zone123/custom_hostnames"))
+        .and(query_param("page", "1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": true,
+            "errors": [],
+            "result": [
+                { "id": "ch_1", "hostname": "app.customer-a.com", "status": "active" }
+            ],
+            "result_info": { "page": 1, "per_page": 100, "total_count": 101 }
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/zones/zone123/custom_hostnames"))
+        .and(query_param("page", "2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": true,
+            "errors": [],
+            "result": [
+                { "id": "ch_2", "hostname": "shop.customer-b.com", "status": "pending" }
+            ],
+            "result_info": { "page": 2, "per_page": 100, "total_count": 101 }
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client =
+        CloudflareClient::with_base_url("test_token".to_string(), mock_server.uri()).unwrap();
+
+    let hostnames = client.discover_custom_hostnames("zone123").await.unwrap();
+
+    let names: Vec<(&str, &str)> = hostnames
+        .iter()
+        .map(|h| (h.id.as_str(), h.hostname.as_str()))
+        .collect();
+    assert_eq!(
+        names,
+        vec![
+            ("ch_1", "app.customer-a.com"),
+            ("ch_2", "shop.customer-b.com")
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_discover_custom_hostnames_empty() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/zones/zone123/custom_hostnames"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": true,
+            "errors": [],
+            "result": [],
+            "result_info": { "page": 1, "per_page": 100, "total_count": 0 }
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client =
+        CloudflareClient::with_base_url("test_token".to_string(), mock_server.uri()).unwrap();
+
+    let hostnames = client.discover_custom_hostnames("zone123").await.unwrap();
+    assert!(hostnames.is_empty());
+}
+
+#[tokio::test]
+async fn test_discover_turnstile_widgets_follows_pages() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/accounts/acct789/challenges/widgets"))
+        .and(query_param("page", "1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": true,
+            "errors": [],
+            "result": [
+                { "sitekey": "0x4AAF00AAAABn0R22HWm-YUc", "secret": "0x4AAF00AAAABn0R22HWm098HVBjhdsYUc", "name": "login" }
+            ],
+            "result_info": { "page": 1, "per_page": 100, "total_count": 101 }
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/accounts/acct789/challenges/widgets"))
+        .and(query_param("page", "2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": true,
+            "errors": [],
+            "result": [
+                { "sitekey": "0x4AAF00AAAABn0R22HWm-Zzz", "secret": "0x4AAF00AAAABn0R22HWm098HVBjhdsZzz", "name": "signup" }
+            ],
+            "result_info": { "page": 2, "per_page": 100, "total_count": 101 }
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client =
+        CloudflareClient::with_base_url("test_token".to_string(), mock_server.uri()).unwrap();
+
+    let widgets = client.discover_turnstile_widgets("acct789").await.unwrap();
+
+    let sitekeys: Vec<&str> = widgets.iter().map(|w| w.sitekey.as_str()).collect();
+    assert_eq!(
+        sitekeys,
+        vec!["0x4AAF00AAAABn0R22HWm-YUc", "0x4AAF00AAAABn0R22HWm-Zzz"]
+    );
+    assert!(!format!("{:?}", widgets).contains("098HVBjhds"));
+}
+
+#[tokio::test]
+async fn test_discover_pages_projects_follows_pages() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/accounts/acct789/pages/projects"))
+        .and(query_param("page", "1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": true,
+            "errors": [],
+            "result": [
+                { "id": "7b162ea7", "name": "nextjs-blog", "subdomain": "nextjs-blog.pages.dev" }
+            ],
+            "result_info": { "page": 1, "per_page": 100, "total_count": 101 }
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/accounts/acct789/pages/projects"))
+        .and(query_param("page", "2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": true,
+            "errors": [],
+            "result": [
+                { "id": "0f4e2c91", "name": "docs", "subdomain": "docs.pages.dev" }
+            ],
+            "result_info": { "page": 2, "per_page": 100, "total_count": 101 }
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client =
+        CloudflareClient::with_base_url("test_token".to_string(), mock_server.uri()).unwrap();
+
+    let projects = client.discover_pages_projects("acct789").await.unwrap();
+
+    let names: Vec<&str> = projects.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, vec!["nextjs-blog", "docs"]);
+}
+
+#[tokio::test]
+async fn test_discover_r2_buckets_follows_cursor() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/accounts/acct789/r2/buckets"))
+        .and(query_param_is_missing("cursor"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": true,
+            "errors": [],
+            "result": {
+                "buckets": [
+                    { "name": "assets", "creation_date": "2024-03-01T10:00:00Z", "location": "WEUR" },
+                    { "name": "backups", "creation_date": "2024-05-12T08:30:00Z", "location": "ENAM" }
+                ]
+            },
+            "result_info": { "per_page": 100, "cursor": "YmFj+3Vw/w==" }
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/accounts/acct789/r2/buckets"))
+        .and(query_param("cursor", "YmFj+3Vw/w=="))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": true,
+            "errors": [],
+            "result": {
+                "buckets": [
+                    { "name": "logs", "creation_date": "2024-06-30T12:00:00Z", "location": "WEUR" }
+                ]
+            },
+            "result_info": { "per_page": 100 }
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client =
+        CloudflareClient::with_base_url("test_token".to_string(), mock_server.uri()).unwrap();
+
+    let buckets = client.discover_r2_buckets("acct789").await.unwrap();
+
+    let names: Vec<&str> = buckets.iter().map(|b| b.name.as_str()).collect();
+    assert_eq!(names, vec!["assets", "backups", "logs"]);
+}
+
+#[tokio::test]
+async fn test_accounts_lists_every_accessible_account() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/accounts"))
+        .and(query_param("page", "1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": true,
+            "errors": [],
+            "result": [
+                { "id": "acc_one", "name": "Production", "type": "standard" },
+                { "id": "acc_two", "name": "Staging", "type": "standard" }
+            ],
+            "result_info": { "page": 1, "per_page": 50, "total_count": 2 }
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client =
+        CloudflareClient::with_base_url("test_token".to_string(), mock_server.uri()).unwrap();
+
+    let accounts = client.accounts().await.unwrap();
+
+    assert_eq!(accounts.len(), 2);
+    assert_eq!(accounts[0].id, "acc_one");
+    assert_eq!(accounts[0].name, "Production");
+    assert_eq!(accounts[1].id, "acc_two");
+    assert_eq!(accounts[1].name, "Staging");
+}
+
+#[tokio::test]
+async fn test_send_attaches_request_id_and_per_request_headers() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/ping"))
+        .and(header_exists("x-request-id"))
+        .and(header("idempotency-key", "key-123"))
+        .and(header("x-custom", "yes"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client =
+        CloudflareClient::with_base_url("test_token".to_string(), mock_server.uri()).unwrap();
+
+    let mut options = RequestOptions {
+        idempotency_key: Some("key-123".to_string()),
+        ..Default::default()
+    };
+    options
+        .headers
+        .insert("x-custom", reqwest::header::HeaderValue::from_static("yes"));
+
+    let response = client
+        .send(&format!("{}/ping", mock_server.uri()), &options)
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), 200);
+}
+
+#[tokio::test]
+async fn test_get_ruleset_includes_rules() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/zones/zone123/rulesets/rs_firewall"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": true,
+            "errors": [],
+            "messages": [],
+            "result": {
+                "id": "rs_firewall",
+                "name": "Custom Firewall Rules",
+                "phase": "http_request_firewall_custom",
+                "kind": "zone",
+                "version": "3",
+                "rules": [
+                    {
+                        "id": "rule_block_bad_ip",
+                        "action": "block",
+                        "expression": "ip.src eq 192.0.2.1",
+                        "enabled": true
+                    },
+                    {
+                        "id": "rule_challenge_bots",
+                        "action": "managed_challenge",
+                        "expression": "cf.client.bot",
+                        "description": "Challenge bots",
+                        "enabled": true
+                    }
+                ]
+            }
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client =
+        CloudflareClient::with_base_url("test_token".to_string(), mock_server.uri()).unwrap();
+
+    let ruleset = client.get_ruleset("zone123", "rs_firewall").await.unwrap();
+
+    assert_eq!(ruleset.id, "rs_firewall");
+    assert_eq!(ruleset.phase, "http_request_firewall_custom");
+    assert_eq!(ruleset.rules.len(), 2);
+    assert_eq!(ruleset.rules[0].id, "rule_block_bad_ip");
+    assert_eq!(ruleset.rules[1].id, "rule_challenge_bots");
+    assert_eq!(
+        ruleset.rules[1].description.as_deref(),
+        Some("Challenge bots")
+    );
+
+    let resource = ruleset.into_resource("zone123");
+    assert_eq!(
+        resource.metadata["rule_ids"],
+        serde_json::json!(["rule_block_bad_ip", "rule_challenge_bots"])
+    );
+}
+
+#[tokio::test]
+async fn test_get_ruleset_not_found() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/zones/zone123/rulesets/missing"))
+        .respond_with(ResponseTemplate::new(404).set_body_json(serde_json::json!({
+            "success": false,
+            "errors": [{ "code": 10003, "message": "could not find ruleset" }],
+            "messages": [],
+            "result": null
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client =
+        CloudflareClient::with_base_url("test_token".to_string(), mock_server.uri()).unwrap();
+
+    let result = client.get_ruleset("zone123", "missing").await;
+
+    if let Err(CloudflareError::Api { status, message }) = result {
+        assert_eq!(status, 404);
+        assert!(message.contains("could not find ruleset"));
+    } else {
+        panic!("Expected CloudflareError::Api, got {:?}", result);
+    }
+}
+
+#[tokio::test]
+async fn test_discover_ruleset_rules_without_rules_array() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/zones/zone123/rulesets/rs_empty"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": true,
+            "errors": [],
+            "result": {
+                "id": "rs_empty",
+                "name": "Empty redirects",
+                "phase": "http_request_dynamic_redirect",
+                "kind": "zone",
+                "version": "1"
+            }
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client =
+        CloudflareClient::with_base_url("test_token".to_string(), mock_server.uri()).unwrap();
+
+    let rules = client
+        .discover_ruleset_rules("zone123", "rs_empty")
+        .await
+        .unwrap();
+
+    assert!(rules.is_empty());
+}
+
+async fn mount_conflict_then_ok(mock_server: &MockServer) {
+    Mock::given(method("GET"))
+        .and(path("/ping"))
+        .respond_with(ResponseTemplate::new(409))
+        .up_to_n_times(1)
+        .mount(mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/ping"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(mock_server)
+        .await;
+}
+
+#[tokio::test]
+async fn test_conflict_retried_when_listed_in_retry_on() {
+    let mock_server = MockServer::start().await;
+    mount_conflict_then_ok(&mock_server).await;
+
+    let client = CloudflareClient::with_base_url("test_token".to_string(), mock_server.uri())
+        .unwrap()
+        .with_retry_on(vec![409]);
+
+    let response = client
+        .send(
+            &format!("{}/ping", mock_server.uri()),
+            &RequestOptions::default(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), 200);
+    let requests = mock_server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 2);
+    let request_id = |request: &wiremock::Request| {
+        request
+            .headers
+            .get(&"x-request-id".into())
+            .map(|values| values.to_string())
+    };
+    assert!(request_id(&requests[0]).is_some());
+    assert_eq!(
+        request_id(&requests[0]),
+        request_id(&requests[1]),
+        "retries must reuse the request id"
+    );
+}
+
+#[tokio::test]
+async fn test_conflict_not_retried_by_default() {
+    let mock_server = MockServer::start().await;
+    mount_conflict_then_ok(&mock_server).await;
+
+    let client =
+        CloudflareClient::with_base_url("test_token".to_string(), mock_server.uri()).unwrap();
+
+    let response = client
+        .send(
+            &format!("{}/ping", mock_server.uri()),
+            &RequestOptions::default(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), 409);
+    assert_eq!(mock_server.received_requests().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_request_counts_match_responses_consumed() {
+    let mock_server = MockServer::start().await;
+    mount_conflict_then_ok(&mock_server).await;
+    mount_dns_record_pages(
+        &mock_server,
+        ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": true,
+            "errors": [],
+            "result": [{ "id": "r3", "name": "c.example.com", "type": "A" }],
+            "result_info": { "page": 2, "per_page": 100, "total_count": 101 }
+        })),
+    )
+    .await;
+
+    let client = CloudflareClient::with_base_url("test_token".to_string(), mock_server.uri())
+        .unwrap()
+        .with_retry_on(vec![409]);
+    // Clones share the counters, so a whole run is counted wherever it sends from.
+    let clone = client.clone();
+    clone
+        .send(
+            &format!("{}/ping", mock_server.uri()),
+            &RequestOptions::default(),
+        )
+        .await
+        .unwrap();
+    client.discover_dns_records("zone123").await.unwrap();
+
+    let consumed = mock_server.received_requests().await.unwrap().len() as u64;
+    assert_eq!(consumed, 4);
+    assert_eq!(client.request_count(), consumed);
+    assert_eq!(client.unique_request_count(), 3);
+}
+
+#[tokio::test]
+async fn test_rate_limited_then_ok_succeeds_after_retry_after() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/zones/zone123/dns_records"))
+        .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "0"))
+        .up_to_n_times(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/zones/zone123/dns_records"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": true,
+            "errors": [],
+            "result": [{ "id": "rec1", "name": "api.example.com", "type": "A" }],
+            "result_info": { "page": 1, "per_page": 100, "total_count": 1 }
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client =
+        CloudflareClient::with_base_url("test_token".to_string(), mock_server.uri()).unwrap();
+
+    let records = client.discover_dns_records("zone123").await.unwrap();
+
+    assert_eq!(records.len(), 1);
+    assert_eq!(mock_server.received_requests().await.unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_server_error_then_ok_is_retried() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/ping"))
+        .respond_with(ResponseTemplate::new(503))
+        .up_to_n_times(2)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/ping"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&mock_server)
+        .await;
+
+    let client = CloudflareClient::with_base_url("test_token".to_string(), mock_server.uri())
+        .unwrap()
+        .with_retry_config(3, Duration::from_millis(1));
+
+    let response = client
+        .send(
+            &format!("{}/ping", mock_server.uri()),
+            &RequestOptions::default(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), 200);
+    assert_eq!(mock_server.received_requests().await.unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn test_persistent_rate_limit_is_rate_limited_error() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/ping"))
+        .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "0"))
+        .expect(3)
+        .mount(&mock_server)
+        .await;
+
+    let client = CloudflareClient::with_base_url("test_token".to_string(), mock_server.uri())
+        .unwrap()
+        .with_retry_config(2, Duration::from_millis(1));
+
+    let result = client
+        .send(
+            &format!("{}/ping", mock_server.uri()),
+            &RequestOptions::default(),
+        )
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(CloudflareError::RateLimited { retry_after: 0 })
+    ));
+}
+
+#[tokio::test]
+async fn test_zones_lists_every_accessible_zone() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/zones"))
+        .and(query_param("page", "1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": true,
+            "errors": [],
+            "result": [
+                { "id": "zone_one", "name": "example.com", "account": { "id": "acc_one", "name": "Production" } }
+            ],
+            "result_info": { "page": 1, "per_page": 50, "total_count": 51 }
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/zones"))
+        .and(query_param("page", "2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": true,
+            "errors": [],
+            "result": [
+                { "id": "zone_two", "name": "example.org", "account": { "id": "acc_two", "name": "Staging" } }
+            ],
+            "result_info": { "page": 2, "per_page": 50, "total_count": 51 }
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client =
+        CloudflareClient::with_base_url("test_token".to_string(), mock_server.uri()).unwrap();
+
+    let zones = client.zones().await.unwrap();
+
+    assert_eq!(zones.len(), 2);
+    assert_eq!(zones[0].zone_id, "zone_one");
+    assert_eq!(zones[0].account_id, "acc_one");
+    assert_eq!(zones[1].zone_id, "zone_two");
+    assert_eq!(zones[1].name, "example.org");
+}
+
+#[tokio::test]
+async fn test_with_page_size_sets_per_page_query() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/zones/zone123/dns_records"))
+        .and(query_param("per_page", "250"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": true,
+            "errors": [],
+            "result": [{ "id": "rec1", "name": "a.example.com", "type": "A" }],
+            "result_info": { "page": 1, "per_page": 250, "total_count": 1 }
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = CloudflareClient::with_base_url("test_token".to_string(), mock_server.uri())
+        .unwrap()
+        .with_page_size(250)
+        .unwrap();
+
+    let records = client.discover_dns_records("zone123").await.unwrap();
+
+    assert_eq!(records.len(), 1);
+}
+
+#[tokio::test]
+async fn test_with_page_size_is_capped_for_accounts() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/accounts"))
+        .and(query_param("per_page", "50"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": true,
+            "errors": [],
+            "result": [],
+            "result_info": { "page": 1, "per_page": 50, "total_count": 0 }
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = CloudflareClient::with_base_url("test_token".to_string(), mock_server.uri())
+        .unwrap()
+        .with_page_size(500)
+        .unwrap();
+
+    assert!(client.accounts().await.unwrap().is_empty());
+}
+
+#[test]
+fn test_with_page_size_clamps_and_rejects_zero() {
+    let client = || {
+        CloudflareClient::with_base_url("test_token".to_string(), "http://localhost".to_string())
+            .unwrap()
+    };
+
+    assert_eq!(client().page_size(), 100);
+    assert_eq!(client().with_page_size(2).unwrap().page_size(), 5);
+    assert_eq!(client().with_page_size(5000).unwrap().page_size(), 1000);
+    assert_eq!(
+        client()
+            .with_page_size(300)
+            .unwrap()
+            .with_timeouts(Some(Duration::from_secs(5)), None)
+            .unwrap()
+            .page_size(),
+        300
+    );
+    assert!(matches!(
+        client().with_page_size(0),
+        Err(CloudflareError::InvalidPageSize { page_size: 0 })
+    ));
+}
+
+#[tokio::test]
+async fn test_discover_rate_limits_follows_pages() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/zones/zone123/rate_limits"))
+        .and(query_param("page", "1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": true,
+            "errors": [],
+            "result": [
+                { "id": "rl1", "disabled": false, "description": "Login", "threshold": 10, "period": 60 }
+            ],
+            "result_info": { "page": 1, "per_page": 100, "total_count": 101 }
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/zones/zone123/rate_limits"))
+        .and(query_param("page", "2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": true,
+            "errors": [],
+            "result": [
+                { "id": "rl2", "disabled": true, "match": { "request": { "url": "example.com/api/*" } } }
+            ],
+            "result_info": { "page": 2, "per_page": 100, "total_count": 101 }
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client =
+        CloudflareClient::with_base_url("test_token".to_string(), mock_server.uri()).unwrap();
+
+    let rate_limits = client.discover_rate_limits("zone123").await.unwrap();
+
+    assert_eq!(rate_limits.len(), 2);
+    assert_eq!(rate_limits[0].id, "rl1");
+    assert!(!rate_limits[0].disabled);
+    assert_eq!(rate_limits[1].id, "rl2");
+    assert!(rate_limits[1].disabled);
+}
+
+/// A bare HTTP server that answers every request with an empty list after a short delay,
+/// recording the most requests it was ever handling at once.
+async fn start_counting_server() -> (String, Arc<AtomicUsize>) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let uri = format!("http://{}", listener.local_addr().unwrap());
+    let in_flight = Arc::new(AtomicUsize::new(0));
+    let peak = Arc::new(AtomicUsize::new(0));
+
+    let server_peak = peak.clone();
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let (in_flight, peak) = (in_flight.clone(), server_peak.clone());
+            tokio::spawn(async move {
+                let mut request = Vec::new();
+                let mut buf = [0u8; 4096];
+                while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                    let n = socket.read(&mut buf).await.unwrap();
+                    if n == 0 {
+                        return;
+                    }
+                    request.extend_from_slice(&buf[..n]);
+                }
+
+                let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+
+                let body = r#"{"success":true,"errors":[],"messages":[],"result":[]}"#;
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                socket.write_all(response.as_bytes()).await.unwrap();
+            });
+        }
+    });
+
+    (uri, peak)
+}
+
+#[tokio::test]
+async fn test_max_concurrency_bounds_requests_across_clones() {
+    let (uri, peak) = start_counting_server().await;
+
+    let client = CloudflareClient::with_base_url("test_token".to_string(), uri)
+        .unwrap()
+        .with_max_concurrency(2)
+        .unwrap();
+
+    let tasks: Vec<_> = (0..8)
+        .map(|i| {
+            let client = client.clone();
+            tokio::spawn(async move { client.discover_worker_routes(&format!("zone{}", i)).await })
+        })
+        .collect();
+    for task in tasks {
+        assert!(task.await.unwrap().unwrap().is_empty());
+    }
+
+    assert_eq!(peak.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn test_with_max_concurrency_rejects_zero() {
+    let client =
+        CloudflareClient::with_base_url("test_token".to_string(), "http://localhost".to_string())
+            .unwrap();
+
+    assert!(matches!(
+        client.with_max_concurrency(0),
+        Err(CloudflareError::InvalidMaxConcurrency { max_concurrency: 0 })
+    ));
+}
+
+#[tokio::test]
+async fn test_fetch_all_cursors_stops_on_repeated_cursor() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/rulesets"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": true,
+            "errors": [],
+            "result": [{"id": "rs1"}],
+            "result_info": { "cursors": { "after": "cursor_stuck" } }
+        })))
+        .expect(2)
+        .mount(&mock_server)
+        .await;
+
+    let client =
+        CloudflareClient::with_base_url("test_token".to_string(), mock_server.uri()).unwrap();
+
+    let result: Result<Vec<serde_json::Value>, _> = client
+        .fetch_all_cursors(
+            &format!("{}/rulesets", mock_server.uri()),
+            10,
+            |json| async move { Ok(serde_json::from_value(json).unwrap_or_default()) },
+        )
+        .await;
+
+    if let Err(CloudflareError::Api { message, .. }) = result {
+        assert!(message.contains("/rulesets"), "{}", message);
+        assert!(message.contains("cursor_stuck"), "{}", message);
+    } else {
+        panic!("Expected CloudflareError::Api, got {:?}", result);
+    }
+}
+
+#[tokio::test]
+async fn test_fetch_all_pages_stops_at_max_pages() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/dns_records"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": true,
+            "errors": [],
+            "result": [{"id": "r1"}],
+            "result_info": { "total_count": 1_000_000 }
+        })))
+        .expect(3)
+        .mount(&mock_server)
+        .await;
+
+    let client = CloudflareClient::with_base_url("test_token".to_string(), mock_server.uri())
+        .unwrap()
+        .with_max_pages(3);
+
+    let result: Result<Vec<serde_json::Value>, _> = client
+        .fetch_all_pages(
+            &format!("{}/dns_records", mock_server.uri()),
+            1,
+            |json| async move { Ok(serde_json::from_value(json).unwrap_or_default()) },
+        )
+        .await;
+
+    if let Err(CloudflareError::Api { message, .. }) = result {
+        assert!(message.contains("after 3"), "{}", message);
+    } else {
+        panic!("Expected CloudflareError::Api, got {:?}", result);
+    }
+}
@@ -0,0 +1,307 @@
+use std::fs;
+
+use tia::DiscoverConfig;
+use tia::output::diff::{write_state_diff, write_state_diff_json};
+use tia::providers::get_provider;
+use tia::terraform::drift::StateDiff;
+use tia::terraform::state::TerraformState;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const ZONE_ID: &str = "023e105f4ecef8ad9ca31a8372d0c353";
+
+/// Serves a zone whose resources partly overlap `tests/fixtures/cloudflare.tfstate`.
+async fn mount_zone(mock_server: &MockServer) {
+    let ok = |result: serde_json::Value| {
+        ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": true,
+            "errors": [],
+            "result": result,
+            "result_info": { "page": 1, "per_page": 100, "total_count": 3 }
+        }))
+    };
+
+    Mock::given(method("GET"))
+        .and(path("/user/tokens/verify"))
+        .respond_with(ok(serde_json::json!({ "id": "token", "status": "active" })))
+        .mount(mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(format!("/zones/{}", ZONE_ID)))
+        .respond_with(ok(serde_json::json!({
+            "id": ZONE_ID,
+            "name": "example.com",
+            "account": { "id": "01a7362d577a6c3019a474fd6f485823", "name": "Test Account" }
+        })))
+        .mount(mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(format!("/zones/{}/dns_records", ZONE_ID)))
+        .respond_with(ok(serde_json::json!([
+            { "id": "372e67954025e0ba6aaa6d586b9e0b59", "name": "api.example.com", "type": "A" },
+            { "id": "9a7806061c88ada191ed06f989cc3dac", "name": "example.com", "type": "MX" },
+            // Same name as a managed record but a different id: still an import candidate.
+            { "id": "e4d909c290d0fb1ca068ffaddf22cbd0", "name": "api.example.com", "type": "AAAA" }
+        ])))
+        .mount(mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(format!("/zones/{}/pagerules", ZONE_ID)))
+        .respond_with(ok(serde_json::json!([])))
+        .mount(mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(format!("/zones/{}/rulesets", ZONE_ID)))
+        .respond_with(ok(serde_json::json!([
+            {
+                "id": "2f2feab2026849078ba485f918791bdc",
+                "name": "Redirect rules",
+                "kind": "zone",
+                "phase": "http_request_dynamic_redirect"
+            }
+        ])))
+        .mount(mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path(format!(
+            "/zones/{}/rulesets/2f2feab2026849078ba485f918791bdc",
+            ZONE_ID
+        )))
+        .respond_with(ok(serde_json::json!({
+            "id": "2f2feab2026849078ba485f918791bdc",
+            "name": "Redirect rules",
+            "kind": "zone",
+            "phase": "http_request_dynamic_redirect",
+            "rules": [
+                { "id": "3a03d665bac047339bb530ecb439a90d", "action": "redirect" }
+            ]
+        })))
+        .mount(mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path(format!("/zones/{}/workers/routes", ZONE_ID)))
+        .respond_with(ok(serde_json::json!([])))
+        .mount(mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(format!("/zones/{}/custom_certificates", ZONE_ID)))
+        .respond_with(ok(serde_json::json!([])))
+        .mount(mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/certificates"))
+        .respond_with(ok(serde_json::json!([])))
+        .mount(mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(format!("/zones/{}/settings", ZONE_ID)))
+        .respond_with(ok(serde_json::json!([])))
+        .mount(mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(format!("/zones/{}/spectrum/apps", ZONE_ID)))
+        .respond_with(ok(serde_json::json!([])))
+        .mount(mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(format!("/zones/{}/custom_hostnames", ZONE_ID)))
+        .respond_with(ok(serde_json::json!([])))
+        .mount(mock_server)
+        .await;
+
+    // The zone has no load balancing, health checks or legacy rate limiting and the token
+    // can't read account lists or Workers scripts, so those types are skipped.
+    let forbidden = ResponseTemplate::new(403).set_body_json(serde_json::json!({
+        "success": false,
+        "errors": [{ "code": 10000, "message": "Authentication error" }],
+        "result": null
+    }));
+    Mock::given(method("GET"))
+        .and(path(format!("/zones/{}/rate_limits", ZONE_ID)))
+        .respond_with(forbidden.clone())
+        .mount(mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(format!("/zones/{}/load_balancers", ZONE_ID)))
+        .respond_with(forbidden.clone())
+        .mount(mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(format!("/zones/{}/healthchecks", ZONE_ID)))
+        .respond_with(forbidden.clone())
+        .mount(mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(
+            "/accounts/01a7362d577a6c3019a474fd6f485823/load_balancers/pools",
+        ))
+        .respond_with(forbidden.clone())
+        .mount(mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(
+            "/accounts/01a7362d577a6c3019a474fd6f485823/rules/lists",
+        ))
+        .respond_with(forbidden.clone())
+        .mount(mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(
+            "/accounts/01a7362d577a6c3019a474fd6f485823/workers/scripts",
+        ))
+        .respond_with(forbidden.clone())
+        .mount(mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(
+            "/accounts/01a7362d577a6c3019a474fd6f485823/pages/projects",
+        ))
+        .respond_with(forbidden.clone())
+        .mount(mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(
+            "/accounts/01a7362d577a6c3019a474fd6f485823/r2/buckets",
+        ))
+        .respond_with(forbidden.clone())
+        .mount(mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(
+            "/accounts/01a7362d577a6c3019a474fd6f485823/challenges/widgets",
+        ))
+        .respond_with(forbidden)
+        .mount(mock_server)
+        .await;
+}
+
+async fn diff_against_fixture(mock_server: &MockServer) -> StateDiff {
+    diff_against(mock_server, "tests/fixtures/cloudflare.tfstate").await
+}
+
+async fn diff_against(mock_server: &MockServer, state_path: &str) -> StateDiff {
+    let config = DiscoverConfig {
+        token: Some("test_token".to_string()),
+        zones: vec![ZONE_ID.to_string()],
+        api_base_url: Some(mock_server.uri()),
+        retry_on: Some(vec![]),
+        ..Default::default()
+    };
+    let provider = get_provider("cloudflare", None).unwrap();
+    let report = provider.discover_report(&config).await.unwrap();
+
+    let file = fs::File::open(state_path).unwrap();
+    let state = TerraformState::from_reader(file).unwrap();
+    StateDiff::between(
+        &report.resources,
+        &state,
+        &provider.resource_types(),
+        &report.zones,
+    )
+}
+
+#[tokio::test]
+async fn test_diff_reports_resources_missing_from_state() {
+    let mock_server = MockServer::start().await;
+    mount_zone(&mock_server).await;
+
+    let diff = diff_against_fixture(&mock_server).await;
+
+    assert!(diff.has_drift());
+    assert_eq!(diff.matched, 3);
+    assert_eq!(diff.to_import.len(), 1);
+    assert_eq!(
+        diff.to_import[0].resource_id,
+        "e4d909c290d0fb1ca068ffaddf22cbd0"
+    );
+    assert_eq!(
+        diff.state_only,
+        vec![
+            (
+                "cloudflare_dns_record".to_string(),
+                "b1946ac92492d2347c6235b4d2611184".to_string()
+            ),
+            (
+                "cloudflare_ruleset".to_string(),
+                "4814384a9e5d4991b9815dcfc25d2f1f".to_string()
+            ),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_diff_ignores_state_outside_discovered_types_and_zones() {
+    let mock_server = MockServer::start().await;
+    mount_zone(&mock_server).await;
+
+    let diff = diff_against(
+        &mock_server,
+        "tests/fixtures/cloudflare_managed_zone.tfstate",
+    )
+    .await;
+
+    assert_eq!(diff.matched, 1);
+    assert_eq!(
+        diff.state_only,
+        vec![(
+            "cloudflare_dns_record".to_string(),
+            "b1946ac92492d2347c6235b4d2611184".to_string()
+        )]
+    );
+}
+
+#[tokio::test]
+async fn test_diff_output_groups_by_type() {
+    let mock_server = MockServer::start().await;
+    mount_zone(&mock_server).await;
+
+    let diff = diff_against_fixture(&mock_server).await;
+    let mut out = Vec::new();
+    write_state_diff(&diff, true, &mut out).unwrap();
+
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "cloudflare_dns_record\n\
+         \x20 + api.example.com (e4d909c290d0fb1ca068ffaddf22cbd0)\n\
+         \x20 - b1946ac92492d2347c6235b4d2611184\n\
+         cloudflare_ruleset\n\
+         \x20 - 4814384a9e5d4991b9815dcfc25d2f1f\n\
+         import: 1, state-only: 2, matched: 3\n"
+    );
+}
+
+#[tokio::test]
+async fn test_diff_json_output() {
+    let mock_server = MockServer::start().await;
+    mount_zone(&mock_server).await;
+
+    let diff = diff_against_fixture(&mock_server).await;
+    let mut out = Vec::new();
+    write_state_diff_json(&diff, &mut out, true).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+
+    assert_eq!(json["in_sync"], 3);
+    let to_import = json["to_import"].as_array().unwrap();
+    assert_eq!(to_import.len(), 1);
+    assert_eq!(to_import[0]["resource_type"], "cloudflare_dns_record");
+    assert_eq!(
+        to_import[0]["resource_id"],
+        "e4d909c290d0fb1ca068ffaddf22cbd0"
+    );
+    assert_eq!(to_import[0]["name"], "api.example.com");
+    assert_eq!(
+        json["orphaned"],
+        serde_json::json!([
+            {
+                "resource_type": "cloudflare_dns_record",
+                "resource_id": "b1946ac92492d2347c6235b4d2611184"
+            },
+            {
+                "resource_type": "cloudflare_ruleset",
+                "resource_id": "4814384a9e5d4991b9815dcfc25d2f1f"
+            }
+        ])
+    );
+}
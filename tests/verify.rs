@@ -0,0 +1,285 @@
+use tia::DiscoverConfig;
+use tia::providers::cloudflare::CloudflareProvider;
+use tia::providers::{Access, ProviderError};
+use wiremock::matchers::{method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const ZONE_ID: &str = "023e105f4ecef8ad9ca31a8372d0c353";
+const SECOND_ZONE_ID: &str = "9a7806061c88ada191ed06f989cc3dac";
+const ACCOUNT_ID: &str = "01a7362d577a6c3019a474fd6f485823";
+
+fn ok(result: serde_json::Value) -> ResponseTemplate {
+    ResponseTemplate::new(200).set_body_json(serde_json::json!({
+        "success": true,
+        "errors": [],
+        "result": result,
+        "result_info": { "page": 1, "per_page": 1, "total_count": 1 }
+    }))
+}
+
+fn forbidden() -> ResponseTemplate {
+    ResponseTemplate::new(403).set_body_json(serde_json::json!({
+        "success": false,
+        "errors": [{ "code": 10000, "message": "Authentication error" }],
+        "result": null
+    }))
+}
+
+async fn mount_endpoint(mock_server: &MockServer, endpoint: &str, response: ResponseTemplate) {
+    Mock::given(method("GET"))
+        .and(path(endpoint))
+        .and(query_param("per_page", "1"))
+        .respond_with(response)
+        .mount(mock_server)
+        .await;
+}
+
+/// Two zones in one account; the token may read DNS records and rulesets but not page
+/// rules, Workers routes or load balancing.
+async fn mount_zones(mock_server: &MockServer) {
+    Mock::given(method("GET"))
+        .and(path("/user/tokens/verify"))
+        .respond_with(ok(serde_json::json!({ "id": "token", "status": "active" })))
+        .mount(mock_server)
+        .await;
+    for (zone_id, name) in [(ZONE_ID, "example.com"), (SECOND_ZONE_ID, "example.org")] {
+        Mock::given(method("GET"))
+            .and(path(format!("/zones/{}", zone_id)))
+            .respond_with(ok(serde_json::json!({
+                "id": zone_id,
+                "name": name,
+                "account": { "id": ACCOUNT_ID, "name": "Test Account" }
+            })))
+            .mount(mock_server)
+            .await;
+        mount_endpoint(
+            mock_server,
+            &format!("/zones/{}/dns_records", zone_id),
+            ok(serde_json::json!([])),
+        )
+        .await;
+        mount_endpoint(
+            mock_server,
+            &format!("/zones/{}/pagerules", zone_id),
+            forbidden(),
+        )
+        .await;
+        mount_endpoint(
+            mock_server,
+            &format!("/zones/{}/rulesets", zone_id),
+            ok(serde_json::json!([])),
+        )
+        .await;
+        mount_endpoint(
+            mock_server,
+            &format!("/zones/{}/rate_limits", zone_id),
+            ok(serde_json::json!([])),
+        )
+        .await;
+        mount_endpoint(
+            mock_server,
+            &format!("/zones/{}/workers/routes", zone_id),
+            forbidden(),
+        )
+        .await;
+        mount_endpoint(
+            mock_server,
+            &format!("/zones/{}/custom_certificates", zone_id),
+            ok(serde_json::json!([])),
+        )
+        .await;
+        Mock::given(method("GET"))
+            .and(path("/certificates"))
+            .and(query_param("zone_id", zone_id))
+            .and(query_param("per_page", "1"))
+            .respond_with(ok(serde_json::json!([])))
+            .mount(mock_server)
+            .await;
+        mount_endpoint(
+            mock_server,
+            &format!("/zones/{}/settings", zone_id),
+            ok(serde_json::json!([])),
+        )
+        .await;
+        mount_endpoint(
+            mock_server,
+            &format!("/zones/{}/load_balancers", zone_id),
+            forbidden(),
+        )
+        .await;
+        mount_endpoint(
+            mock_server,
+            &format!("/zones/{}/healthchecks", zone_id),
+            ok(serde_json::json!([])),
+        )
+        .await;
+        mount_endpoint(
+            mock_server,
+            &format!("/zones/{}/spectrum/apps", zone_id),
+            ok(serde_json::json!([])),
+        )
+        .await;
+        mount_endpoint(
+            mock_server,
+            &format!("/zones/{}/custom_hostnames", zone_id),
+            ok(serde_json::json!([])),
+        )
+        .await;
+    }
+    mount_endpoint(
+        mock_server,
+        &format!("/accounts/{}/load_balancers/pools", ACCOUNT_ID),
+        forbidden(),
+    )
+    .await;
+    mount_endpoint(
+        mock_server,
+        &format!("/accounts/{}/rules/lists", ACCOUNT_ID),
+        ok(serde_json::json!([])),
+    )
+    .await;
+    mount_endpoint(
+        mock_server,
+        &format!("/accounts/{}/workers/scripts", ACCOUNT_ID),
+        ok(serde_json::json!([])),
+    )
+    .await;
+    mount_endpoint(
+        mock_server,
+        &format!("/accounts/{}/pages/projects", ACCOUNT_ID),
+        ok(serde_json::json!([])),
+    )
+    .await;
+    mount_endpoint(
+        mock_server,
+        &format!("/accounts/{}/r2/buckets", ACCOUNT_ID),
+        ok(serde_json::json!({ "buckets": [] })),
+    )
+    .await;
+    mount_endpoint(
+        mock_server,
+        &format!("/accounts/{}/challenges/widgets", ACCOUNT_ID),
+        ok(serde_json::json!([])),
+    )
+    .await;
+}
+
+fn config(mock_server: &MockServer, zones: &[&str]) -> DiscoverConfig {
+    DiscoverConfig {
+        token: Some("test_token".to_string()),
+        zones: zones.iter().map(|z| z.to_string()).collect(),
+        api_base_url: Some(mock_server.uri()),
+        retry_on: Some(vec![]),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_verify_access_reports_each_resource_type() {
+    let mock_server = MockServer::start().await;
+    mount_zones(&mock_server).await;
+
+    let report = CloudflareProvider::new(None)
+        .verify_access(&config(&mock_server, &[ZONE_ID]))
+        .await
+        .unwrap();
+
+    assert_eq!(report.len(), 1);
+    assert_eq!(report[0].zone, "example.com");
+    let denied = Access::Forbidden("Authentication error".to_string());
+    assert_eq!(
+        report[0].checks,
+        vec![
+            ("cloudflare_dns_record".to_string(), Access::Ok),
+            ("cloudflare_page_rule".to_string(), denied.clone()),
+            ("cloudflare_ruleset".to_string(), Access::Ok),
+            ("cloudflare_ruleset_rule".to_string(), Access::Ok),
+            ("cloudflare_rate_limit".to_string(), Access::Ok),
+            ("cloudflare_workers_route".to_string(), denied.clone()),
+            ("cloudflare_custom_ssl".to_string(), Access::Ok),
+            ("cloudflare_origin_ca_certificate".to_string(), Access::Ok),
+            ("cloudflare_zone_setting".to_string(), Access::Ok),
+            ("cloudflare_load_balancer".to_string(), denied.clone()),
+            ("cloudflare_healthcheck".to_string(), Access::Ok),
+            ("cloudflare_spectrum_application".to_string(), Access::Ok),
+            ("cloudflare_custom_hostname".to_string(), Access::Ok),
+            ("cloudflare_load_balancer_pool".to_string(), denied),
+            ("cloudflare_list".to_string(), Access::Ok),
+            ("cloudflare_workers_script".to_string(), Access::Ok),
+            ("cloudflare_pages_project".to_string(), Access::Ok),
+            ("cloudflare_r2_bucket".to_string(), Access::Ok),
+            ("cloudflare_turnstile_widget".to_string(), Access::Ok),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_verify_access_probes_account_endpoints_once_per_account() {
+    let mock_server = MockServer::start().await;
+    mount_zones(&mock_server).await;
+
+    let report = CloudflareProvider::new(None)
+        .verify_access(&config(&mock_server, &[ZONE_ID, SECOND_ZONE_ID]))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        report.iter().map(|z| z.zone.as_str()).collect::<Vec<_>>(),
+        vec!["example.com", "example.org"]
+    );
+    assert_eq!(report[0].checks, report[1].checks);
+
+    let requests = mock_server.received_requests().await.unwrap();
+    let pool_probes = requests
+        .iter()
+        .filter(|r| r.url.path().ends_with("/load_balancers/pools"))
+        .count();
+    assert_eq!(pool_probes, 1);
+}
+
+#[tokio::test]
+async fn test_verify_access_only_probes_requested_types() {
+    let mock_server = MockServer::start().await;
+    mount_zones(&mock_server).await;
+    let config = DiscoverConfig {
+        resource_types: Some(vec!["cloudflare_dns_record".to_string()]),
+        ..config(&mock_server, &[ZONE_ID])
+    };
+
+    let report = CloudflareProvider::new(None)
+        .verify_access(&config)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        report[0].checks,
+        vec![("cloudflare_dns_record".to_string(), Access::Ok)]
+    );
+    let requests = mock_server.received_requests().await.unwrap();
+    assert!(
+        !requests
+            .iter()
+            .any(|r| r.url.path().ends_with("/pagerules"))
+    );
+}
+
+#[tokio::test]
+async fn test_verify_access_fails_on_invalid_token() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/user/tokens/verify"))
+        .respond_with(ResponseTemplate::new(401).set_body_json(serde_json::json!({
+            "success": false,
+            "errors": [{ "code": 1000, "message": "Invalid API Token" }],
+            "result": null
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let err = CloudflareProvider::new(None)
+        .verify_access(&config(&mock_server, &[ZONE_ID]))
+        .await
+        .unwrap_err();
+
+    assert!(matches!(err, ProviderError::Auth(_)), "{:?}", err);
+}